@@ -7,6 +7,7 @@ import {
   TransactionInstruction,
   sendAndConfirmTransaction,
   SystemProgram,
+  LAMPORTS_PER_SOL,
 } from "@solana/web3.js";
 import { Command } from "commander";
 import * as fs from "fs";
@@ -23,15 +24,43 @@ const PROGRAM_ID = new PublicKey(
   "9tN5NBvynubfJwQWDqrSoHEE3Xy2MVj3BmHdLu13wCcS"
 );
 
+// Read-only config PDA that ends every user instruction (pause switch)
+const [CONFIG_PDA] = PublicKey.findProgramAddressSync(
+  [Buffer.from("config")],
+  PROGRAM_ID
+);
+const CONFIG_META = { pubkey: CONFIG_PDA, isSigner: false, isWritable: false };
+
+// Treasury PDA that collects protocol fees on sends
+const [TREASURY_PDA] = PublicKey.findProgramAddressSync(
+  [Buffer.from("treasury")],
+  PROGRAM_ID
+);
+const TREASURY_META = { pubkey: TREASURY_PDA, isSigner: false, isWritable: true };
+
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s"
+);
+
 const DISCRIMINATORS = {
   // Instruction Discriminator (Hex)
   initializeThread: "cf4e5bb957f48e0b", // [207, 78, 91, 185, 87, 244, 142, 11]
   sendMessage: "392822b2bd0a411a", // [57, 40, 34, 178, 189, 10, 65, 26]
+  acceptThread: "1e4bde895793c90a", // [30, 75, 222, 137, 87, 147, 201, 10]
+  rejectThread: "45e8dfae7c5231bc", // [69, 232, 223, 174, 124, 82, 49, 188]
   initializeChannel: "e85bb1d47a5ee3fa", // [232, 91, 177, 212, 122, 94, 227, 250]
+  initializeGatedChannel: "8815279648710c14", // [136, 21, 39, 150, 72, 113, 12, 20]
+  sendMessageWithTip: "124ca83bcdb6be37", // [18, 76, 168, 59, 205, 182, 190, 55]
+  claimHandle: "5d8e2f6fa48663b5", // [93, 142, 47, 111, 164, 134, 99, 181]
+  releaseHandle: "133acd29d869c30e", // [19, 58, 205, 41, 216, 105, 195, 14]
   sendBroadcast: "e9f1484d97932059", // [233, 241, 72, 77, 151, 147, 32, 89]
   subscribeChannel: "ca978c2427df6cb1", // [202, 151, 140, 36, 39, 223, 108, 177]
+  unsubscribeChannel: "bc097151bba58da5", // [188, 9, 113, 81, 187, 165, 141, 165]
   closeThread: "35e71031f7656d0b", // [53, 231, 16, 49, 247, 101, 109, 11]
+  requestThreadClose: "8f1b33061f0f295a", // [143, 27, 51, 6, 31, 15, 41, 90]
   closeChannel: "006824014200679d", // [0, 104, 36, 1, 66, 0, 103, 157]
+  blockUser: "0aa4b206e7afb9bf", // [10, 164, 178, 6, 231, 175, 185, 191]
+  unblockUser: "d8d080624ad21272", // [216, 208, 128, 98, 74, 210, 18, 114]
 };
 
 
@@ -72,6 +101,12 @@ function createConnection(cluster: string): Connection {
 }
 
 
+// Thread PDAs sort the participant pair so either side derives the same address
+function sortedParticipants(a: PublicKey, b: PublicKey): Buffer[] {
+  const [first, second] = [a.toBuffer(), b.toBuffer()];
+  return Buffer.compare(first, second) <= 0 ? [first, second] : [second, first];
+}
+
 function deriveThreadPDA(
   participantA: PublicKey,
   participantB: PublicKey,
@@ -80,8 +115,7 @@ function deriveThreadPDA(
   return PublicKey.findProgramAddressSync(
     [
       Buffer.from("message_thread"),
-      participantA.toBuffer(),
-      participantB.toBuffer(),
+      ...sortedParticipants(participantA, participantB),
       threadId,
     ],
     PROGRAM_ID
@@ -114,6 +148,102 @@ function deriveSubscriptionPDA(
   );
 }
 
+function deriveProfilePDA(owner: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("user_profile"), owner.toBuffer()],
+    PROGRAM_ID
+  );
+}
+
+// Optional accounts are passed as the program ID when absent
+function deriveHandlePDA(handle: string): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("handle"), Buffer.from(handle)],
+    PROGRAM_ID
+  );
+}
+
+// Accept either a base58 address or an @handle registered on-chain
+async function resolveRecipient(
+  connection: Connection,
+  recipient: string
+): Promise<PublicKey> {
+  if (!recipient.startsWith("@")) {
+    return new PublicKey(recipient);
+  }
+  const [handlePDA] = deriveHandlePDA(recipient.slice(1).toLowerCase());
+  const info = await connection.getAccountInfo(handlePDA);
+  if (!info) {
+    throw new Error(`Handle ${recipient} is not registered`);
+  }
+  return new PublicKey(info.data.subarray(8, 40));
+}
+
+async function optionalProfileAccount(
+  connection: Connection,
+  owner: PublicKey
+): Promise<PublicKey> {
+  const [profilePDA] = deriveProfilePDA(owner);
+  const info = await connection.getAccountInfo(profilePDA);
+  return info ? profilePDA : PROGRAM_ID;
+}
+
+// Today's stats account of a channel, counted when it has been opened
+async function channelStatsMeta(connection: Connection, channel: PublicKey) {
+  const day = Buffer.alloc(4);
+  day.writeUInt32LE(Math.floor(Date.now() / 1000 / 86400));
+  const [statsPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from("channel_stats"), channel.toBuffer(), day],
+    PROGRAM_ID
+  );
+  const exists = !!(await connection.getAccountInfo(statsPDA));
+  return {
+    pubkey: exists ? statsPDA : PROGRAM_ID,
+    isSigner: false,
+    isWritable: exists,
+  };
+}
+
+// A thread is indexed at the next pointer of each participant's inbox
+async function inboxAccounts(
+  connection: Connection,
+  owner: PublicKey
+): Promise<[PublicKey, PublicKey]> {
+  const [inbox] = PublicKey.findProgramAddressSync(
+    [Buffer.from("user_inbox"), owner.toBuffer()],
+    PROGRAM_ID
+  );
+  const info = await connection.getAccountInfo(inbox);
+  const index = Buffer.alloc(8);
+  index.writeBigUInt64LE(info ? info.data.readBigUInt64LE(8 + 32) : BigInt(0));
+  const [pointer] = PublicKey.findProgramAddressSync(
+    [Buffer.from("thread_pointer"), owner.toBuffer(), index],
+    PROGRAM_ID
+  );
+  return [inbox, pointer];
+}
+
+function deriveBlockEntryPDA(
+  blocker: PublicKey,
+  blocked: PublicKey
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("block_entry"), blocker.toBuffer(), blocked.toBuffer()],
+    PROGRAM_ID
+  );
+}
+
+
+// Borsh Option<u32>: a 0 tag for None, or 1 followed by the LE index
+function encodeReplyTo(replyTo?: string): Buffer {
+  if (replyTo === undefined) {
+    return Buffer.from([0]);
+  }
+  const encoded = Buffer.alloc(5);
+  encoded.writeUInt8(1, 0);
+  encoded.writeUInt32LE(parseInt(replyTo, 10), 1);
+  return encoded;
+}
 
 function encryptMessage(message: string, sharedSecret: string): Buffer {
   const cipher = crypto.createCipheriv(
@@ -257,7 +387,7 @@ async function initThreadCommand(options: any) {
     spinner.start("Loading wallet and connecting to Solana...");
     const wallet = loadWallet(options.wallet);
     const connection = createConnection(options.cluster);
-    const participantB = new PublicKey(options.recipient);
+    const participantB = await resolveRecipient(connection, options.recipient);
     const threadId = crypto.randomBytes(32);
     const [threadPDA] = deriveThreadPDA(
       wallet.publicKey,
@@ -274,6 +404,30 @@ async function initThreadCommand(options: any) {
       Buffer.from(DISCRIMINATORS.initializeThread, "hex"),
       threadId,
     ]);
+    const [blockByA] = deriveBlockEntryPDA(wallet.publicKey, participantB);
+    const [blockByB] = deriveBlockEntryPDA(participantB, wallet.publicKey);
+    const profileA = await optionalProfileAccount(connection, wallet.publicKey);
+    const profileB = await optionalProfileAccount(connection, participantB);
+    // Pre-accepted when the recipient has saved us as a contact
+    const [contactPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("contact"), participantB.toBuffer(), wallet.publicKey.toBuffer()],
+      PROGRAM_ID
+    );
+    const recipientContact = (await connection.getAccountInfo(contactPDA))
+      ? contactPDA
+      : PROGRAM_ID;
+    // Only used when the recipient requires an outreach deposit
+    const [threadVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("thread_vault"), threadPDA.toBuffer()],
+      PROGRAM_ID
+    );
+    // Only used when the recipient charges a DM fee
+    const [dmEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("dm_escrow"), threadPDA.toBuffer()],
+      PROGRAM_ID
+    );
+    const [inboxA, pointerA] = await inboxAccounts(connection, wallet.publicKey);
+    const [inboxB, pointerB] = await inboxAccounts(connection, participantB);
     const initIx = new TransactionInstruction({
       programId: PROGRAM_ID,
       keys: [
@@ -281,6 +435,20 @@ async function initThreadCommand(options: any) {
         { pubkey: wallet.publicKey, isSigner: true, isWritable: true },
         { pubkey: participantB, isSigner: false, isWritable: false },
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: blockByA, isSigner: false, isWritable: false },
+        { pubkey: blockByB, isSigner: false, isWritable: false },
+        { pubkey: profileA, isSigner: false, isWritable: false }, // profile_a (optional)
+        { pubkey: profileB, isSigner: false, isWritable: false }, // profile_b (optional)
+        { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // contact_proof (optional)
+        { pubkey: threadVault, isSigner: false, isWritable: true }, // thread_vault
+        { pubkey: deriveProfilePDA(participantB)[0], isSigner: false, isWritable: false },
+        { pubkey: recipientContact, isSigner: false, isWritable: false }, // optional
+        { pubkey: inboxA, isSigner: false, isWritable: true },
+        { pubkey: pointerA, isSigner: false, isWritable: true },
+        { pubkey: inboxB, isSigner: false, isWritable: true },
+        { pubkey: pointerB, isSigner: false, isWritable: true },
+        CONFIG_META,
+        { pubkey: dmEscrow, isSigner: false, isWritable: true }, // dm_escrow
       ],
       data: initData,
     });
@@ -320,7 +488,7 @@ async function sendMessageCommand(options: any) {
       threadPDA,
       "thread"
     );
-    const messageCount = accountData.readUInt32LE(metadata.messageCountOffset);
+    const messageCount = Number(accountData.readBigUInt64LE(metadata.messageCountOffset));
 
     spinner.succeed(chalk.green(`Thread found`));
     console.log(chalk.gray(`  Current messages: ${messageCount}\n`));
@@ -332,19 +500,77 @@ async function sendMessageCommand(options: any) {
     console.log(chalk.gray(`  Size: ${encrypted.length} bytes\n`));
 
     spinner.start("Sending message...");
+    const tipLamports = options.tip
+      ? BigInt(Math.round(parseFloat(options.tip) * LAMPORTS_PER_SOL))
+      : BigInt(0);
     const messageData = Buffer.concat([
-      Buffer.from(DISCRIMINATORS.sendMessage, "hex"),
+      Buffer.from(
+        tipLamports > 0 ? DISCRIMINATORS.sendMessageWithTip : DISCRIMINATORS.sendMessage,
+        "hex"
+      ),
       Buffer.from(new Uint32Array([messageCount]).buffer), 
       Buffer.from(new Uint32Array([encrypted.length]).buffer), 
       encrypted, 
     ]);
+    const [blockByA] = deriveBlockEntryPDA(
+      metadata.participantA!,
+      metadata.participantB!
+    );
+    const [blockByB] = deriveBlockEntryPDA(
+      metadata.participantB!,
+      metadata.participantA!
+    );
+    const keys = [
+      { pubkey: threadPDA, isSigner: false, isWritable: true },
+      { pubkey: wallet.publicKey, isSigner: true, isWritable: true },
+      { pubkey: blockByA, isSigner: false, isWritable: false },
+      { pubkey: blockByB, isSigner: false, isWritable: false },
+    ];
+    const recipient = metadata.participantA!.equals(wallet.publicKey)
+      ? metadata.participantB!
+      : metadata.participantA!;
+    // send_message_with_tip takes a tip amount where send_message takes
+    // reply_to_index and attachment (None)
+    let data =
+      tipLamports > 0
+        ? messageData
+        : Buffer.concat([messageData, encodeReplyTo(options.replyTo), Buffer.from([0])]);
+    // Fee accounts follow the config PDA
+    const feeKeys = [TREASURY_META];
+    if (tipLamports === BigInt(0)) {
+      const [contactPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("contact"), recipient.toBuffer(), wallet.publicKey.toBuffer()],
+        PROGRAM_ID
+      );
+      // Senders saved as a contact by the recipient skip the message fee
+      const contactInfo = await connection.getAccountInfo(contactPDA);
+      feeKeys.push(
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        {
+          pubkey: contactInfo ? contactPDA : PROGRAM_ID,
+          isSigner: false,
+          isWritable: false,
+        }
+      );
+    }
+    if (tipLamports > 0) {
+      const amount = Buffer.alloc(8);
+      amount.writeBigUInt64LE(tipLamports);
+      data = Buffer.concat([messageData, amount]);
+      keys.splice(2, 0, { pubkey: recipient, isSigner: false, isWritable: true });
+      keys.push(
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        // SOL tip: mint, token accounts and token program are omitted
+        { pubkey: PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: PROGRAM_ID, isSigner: false, isWritable: false },
+        { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }
+      );
+    }
     const sendIx = new TransactionInstruction({
       programId: PROGRAM_ID,
-      keys: [
-        { pubkey: threadPDA, isSigner: false, isWritable: true },
-        { pubkey: wallet.publicKey, isSigner: true, isWritable: false },
-      ],
-      data: messageData,
+      keys: [...keys, CONFIG_META, ...feeKeys],
+      data,
     });
     const tx = new Transaction().add(sendIx);
     const sig = await sendAndConfirmTransaction(connection, tx, [wallet], {
@@ -361,6 +587,196 @@ async function sendMessageCommand(options: any) {
   }
 }
 
+async function acceptThreadCommand(options: any) {
+  console.log(chalk.bold.cyan("\n🤝 Accept Message Thread\n"));
+  const spinner = ora();
+  try {
+    spinner.start("Loading wallet and connecting to Solana...");
+    const wallet = loadWallet(options.wallet);
+    const connection = createConnection(options.cluster);
+    const threadPDA = new PublicKey(options.thread);
+    spinner.succeed(chalk.green(`Connected to ${options.cluster}`));
+    console.log(
+      chalk.gray(`  Signer (Participant B): ${wallet.publicKey.toBase58()}`)
+    );
+    console.log(chalk.gray(`  Thread: ${threadPDA.toBase58()}\n`));
+
+    spinner.start("Accepting thread...");
+    const acceptData = Buffer.from(DISCRIMINATORS.acceptThread, "hex");
+    const acceptIx = new TransactionInstruction({
+      programId: PROGRAM_ID,
+      keys: [
+        { pubkey: threadPDA, isSigner: false, isWritable: true },
+        { pubkey: wallet.publicKey, isSigner: true, isWritable: false }, // participant_b
+        CONFIG_META,
+      ],
+      data: acceptData,
+    });
+    const tx = new Transaction().add(acceptIx);
+    const sig = await sendAndConfirmTransaction(connection, tx, [wallet], {
+      commitment: "confirmed",
+    });
+    spinner.succeed(chalk.green(`Thread accepted!`));
+    console.log(chalk.gray(`  Signature: ${sig}`));
+    process.exit(0);
+  } catch (error: any) {
+    spinner.fail(chalk.red("Failed to accept thread"));
+    console.error(chalk.red(`\n❌ Error: ${error.message}`));
+    process.exit(1);
+  }
+}
+
+async function rejectThreadCommand(options: any) {
+  console.log(chalk.bold.cyan("\n🚫 Reject Message Thread\n"));
+  const spinner = ora();
+  try {
+    spinner.start("Loading wallet and connecting to Solana...");
+    const wallet = loadWallet(options.wallet);
+    const connection = createConnection(options.cluster);
+    const threadPDA = new PublicKey(options.thread);
+    spinner.succeed(chalk.green(`Connected to ${options.cluster}`));
+    console.log(
+      chalk.gray(`  Signer (Participant B): ${wallet.publicKey.toBase58()}`)
+    );
+    console.log(chalk.gray(`  Thread: ${threadPDA.toBase58()}\n`));
+
+    spinner.start("Rejecting thread...");
+    const rejectData = Buffer.from(DISCRIMINATORS.rejectThread, "hex");
+    const rejectIx = new TransactionInstruction({
+      programId: PROGRAM_ID,
+      keys: [
+        { pubkey: threadPDA, isSigner: false, isWritable: true },
+        { pubkey: wallet.publicKey, isSigner: true, isWritable: false }, // participant_b
+        CONFIG_META,
+      ],
+      data: rejectData,
+    });
+    const tx = new Transaction().add(rejectIx);
+    const sig = await sendAndConfirmTransaction(connection, tx, [wallet], {
+      commitment: "confirmed",
+    });
+    spinner.succeed(chalk.green(`Thread rejected!`));
+    console.log(chalk.gray(`  Signature: ${sig}`));
+    process.exit(0);
+  } catch (error: any) {
+    spinner.fail(chalk.red("Failed to reject thread"));
+    console.error(chalk.red(`\n❌ Error: ${error.message}`));
+    process.exit(1);
+  }
+}
+
+async function blockUserCommand(options: any) {
+  console.log(chalk.bold.cyan("\n⛔ Block User\n"));
+  const spinner = ora();
+  try {
+    spinner.start("Loading wallet and connecting to Solana...");
+    const wallet = loadWallet(options.wallet);
+    const connection = createConnection(options.cluster);
+    const blocked = new PublicKey(options.user);
+    const [blockEntryPDA] = deriveBlockEntryPDA(wallet.publicKey, blocked);
+    spinner.succeed(chalk.green(`Connected to ${options.cluster}`));
+    console.log(chalk.gray(`  Blocking: ${blocked.toBase58()}`));
+    console.log(chalk.gray(`  Block entry PDA: ${blockEntryPDA.toBase58()}\n`));
+
+    spinner.start(options.unblock ? "Removing block..." : "Creating block...");
+    const keys = options.unblock
+      ? [
+          { pubkey: blockEntryPDA, isSigner: false, isWritable: true },
+          { pubkey: wallet.publicKey, isSigner: true, isWritable: true },
+        ]
+      : [
+          { pubkey: blockEntryPDA, isSigner: false, isWritable: true },
+          { pubkey: wallet.publicKey, isSigner: true, isWritable: true },
+          { pubkey: blocked, isSigner: false, isWritable: false },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ];
+    const blockIx = new TransactionInstruction({
+      programId: PROGRAM_ID,
+      keys: [...keys, CONFIG_META],
+      data: Buffer.from(
+        options.unblock ? DISCRIMINATORS.unblockUser : DISCRIMINATORS.blockUser,
+        "hex"
+      ),
+    });
+    const tx = new Transaction().add(blockIx);
+    const sig = await sendAndConfirmTransaction(connection, tx, [wallet], {
+      commitment: "confirmed",
+    });
+    spinner.succeed(
+      chalk.green(options.unblock ? `User unblocked!` : `User blocked!`)
+    );
+    console.log(chalk.gray(`  Signature: ${sig}`));
+    process.exit(0);
+  } catch (error: any) {
+    spinner.fail(chalk.red("Failed to update block"));
+    console.error(chalk.red(`\n❌ Error: ${error.message}`));
+    process.exit(1);
+  }
+}
+
+async function handleCommand(options: any) {
+  console.log(chalk.bold.cyan("\n🏷️  Handle\n"));
+  const spinner = ora();
+  try {
+    spinner.start("Loading wallet and connecting to Solana...");
+    const wallet = loadWallet(options.wallet);
+    const connection = createConnection(options.cluster);
+    const handle = options.name.replace(/^@/, "").toLowerCase();
+    const [handlePDA] = deriveHandlePDA(handle);
+    spinner.succeed(chalk.green(`Connected to ${options.cluster}`));
+    console.log(chalk.gray(`  Handle: @${handle}`));
+    console.log(chalk.gray(`  Handle PDA: ${handlePDA.toBase58()}\n`));
+
+    spinner.start(options.release ? "Releasing handle..." : "Claiming handle...");
+    let ix: TransactionInstruction;
+    if (options.release) {
+      ix = new TransactionInstruction({
+        programId: PROGRAM_ID,
+        keys: [
+          { pubkey: handlePDA, isSigner: false, isWritable: true },
+          { pubkey: wallet.publicKey, isSigner: true, isWritable: true },
+          CONFIG_META,
+        ],
+        data: Buffer.from(DISCRIMINATORS.releaseHandle, "hex"),
+      });
+    } else {
+      const [treasury] = PublicKey.findProgramAddressSync(
+        [Buffer.from("treasury")],
+        PROGRAM_ID
+      );
+      const handleBytes = Buffer.from(handle);
+      ix = new TransactionInstruction({
+        programId: PROGRAM_ID,
+        keys: [
+          { pubkey: handlePDA, isSigner: false, isWritable: true },
+          { pubkey: wallet.publicKey, isSigner: true, isWritable: true },
+          { pubkey: treasury, isSigner: false, isWritable: true },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          CONFIG_META,
+        ],
+        data: Buffer.concat([
+          Buffer.from(DISCRIMINATORS.claimHandle, "hex"),
+          Buffer.from(new Uint32Array([handleBytes.length]).buffer),
+          handleBytes,
+        ]),
+      });
+    }
+    const tx = new Transaction().add(ix);
+    const sig = await sendAndConfirmTransaction(connection, tx, [wallet], {
+      commitment: "confirmed",
+    });
+    spinner.succeed(
+      chalk.green(options.release ? `Handle released!` : `Handle claimed!`)
+    );
+    console.log(chalk.gray(`  Signature: ${sig}`));
+    process.exit(0);
+  } catch (error: any) {
+    spinner.fail(chalk.red("Failed to update handle"));
+    console.error(chalk.red(`\n❌ Error: ${error.message}`));
+    process.exit(1);
+  }
+}
+
 async function readMessagesCommand(options: any) {
   console.log(chalk.bold.cyan("\n📖 Read Messages (Direct Thread)\n"));
   const spinner = ora();
@@ -379,7 +795,7 @@ async function readMessagesCommand(options: any) {
       threadPDA,
       "thread"
     );
-    const messageCount = accountData.readUInt32LE(metadata.messageCountOffset);
+    const messageCount = Number(accountData.readBigUInt64LE(metadata.messageCountOffset));
 
     const messages = await _fetchNewMessages(
       connection,
@@ -430,11 +846,24 @@ async function createChannelCommand(options: any) {
     spinner.start("Creating broadcast channel...");
     // Build initialize instruction
     const channelNameBytes = Buffer.from(options.name, "utf8");
+    const descriptionBytes = Buffer.from(options.description || "", "utf8");
+    const metadataUriBytes = Buffer.from(options.metadataUri || "", "utf8");
+    // A collection mint makes the channel holder-only
+    const discriminator = options.collection
+      ? DISCRIMINATORS.initializeGatedChannel
+      : DISCRIMINATORS.initializeChannel;
     const initData = Buffer.concat([
       // Use the hex discriminator for clarity and verification
-      Buffer.from(DISCRIMINATORS.initializeChannel, "hex"),
+      Buffer.from(discriminator, "hex"),
       Buffer.from(new Uint32Array([channelNameBytes.length]).buffer),
       channelNameBytes,
+      Buffer.from(new Uint32Array([descriptionBytes.length]).buffer),
+      descriptionBytes,
+      Buffer.from(new Uint32Array([metadataUriBytes.length]).buffer),
+      metadataUriBytes,
+      options.collection
+        ? new PublicKey(options.collection).toBuffer()
+        : Buffer.alloc(0),
     ]);
     const initIx = new TransactionInstruction({
       programId: PROGRAM_ID,
@@ -442,6 +871,8 @@ async function createChannelCommand(options: any) {
         { pubkey: channelPDA, isSigner: false, isWritable: true },
         { pubkey: wallet.publicKey, isSigner: true, isWritable: true },
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        CONFIG_META,
+        { pubkey: wallet.publicKey, isSigner: true, isWritable: true }, // payer
       ],
       data: initData,
     });
@@ -479,7 +910,7 @@ async function sendBroadcastCommand(options: any) {
       channelPDA,
       "channel"
     );
-    const messageCount = accountData.readUInt32LE(metadata.messageCountOffset);
+    const messageCount = Number(accountData.readBigUInt64LE(metadata.messageCountOffset));
 
     spinner.succeed(chalk.green(`Channel found`));
     console.log(chalk.gray(`  Current broadcasts: ${messageCount}\n`));
@@ -497,12 +928,33 @@ async function sendBroadcastCommand(options: any) {
       Buffer.from(new Uint32Array([messageCount]).buffer), // message_index (u32)
       Buffer.from(new Uint32Array([encrypted.length]).buffer), // content length (u32)
       encrypted, // encrypted_content (Vec<u8>)
+      Buffer.from([options.burnAfterSeen ? 1 : 0]), // burn_after_seen (bool)
+      encodeReplyTo(options.replyTo), // reply_to_index (Option<u32>)
+      Buffer.from([0]), // attachment: None
     ]);
+    // Non-owners broadcast through their admin role
+    const [adminPDA] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("channel_admin"),
+        channelPDA.toBuffer(),
+        wallet.publicKey.toBuffer(),
+      ],
+      PROGRAM_ID
+    );
+    const adminInfo = await connection.getAccountInfo(adminPDA);
+    const channelAdmin = adminInfo ? adminPDA : PROGRAM_ID;
     const sendIx = new TransactionInstruction({
       programId: PROGRAM_ID,
       keys: [
         { pubkey: channelPDA, isSigner: false, isWritable: true },
-        { pubkey: wallet.publicKey, isSigner: true, isWritable: false }, // Sender is the channel owner
+        { pubkey: wallet.publicKey, isSigner: true, isWritable: true }, // Sender: owner or admin
+        { pubkey: channelAdmin, isSigner: false, isWritable: false }, // channel_admin (optional)
+        CONFIG_META,
+        TREASURY_META,
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // fee_payer (optional)
+        { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // sender_device (optional)
+        await channelStatsMeta(connection, channelPDA), // channel_stats (optional)
       ],
       data: broadcastData,
     });
@@ -544,8 +996,89 @@ async function subscribeChannelCommand(options: any) {
     );
 
     spinner.start("Creating subscription account...");
+    // The welcome thread PDA is only used if the channel has one enabled
+    const channelInfo = await connection.getAccountInfo(channelPDA);
+    if (!channelInfo) {
+      throw new Error("Channel not found. Invalid PDA or account doesn't exist.");
+    }
+    const channelOwner = new PublicKey(channelInfo.data.slice(8, 40));
+    const [welcomeThreadPDA] = deriveThreadPDA(
+      channelOwner,
+      wallet.publicKey,
+      channelPDA.toBuffer()
+    );
+    // Paid channels collect the fee into their vault
+    const [vaultPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("channel_vault"), channelPDA.toBuffer()],
+      PROGRAM_ID
+    );
+    const vaultInfo = await connection.getAccountInfo(vaultPDA);
+    const channelVault = vaultInfo ? vaultPDA : PROGRAM_ID;
+    // Token-gated channels check the subscriber's associated token account
+    const gateTokenAccount = options.gateTokenAccount
+      ? new PublicKey(options.gateTokenAccount)
+      : PROGRAM_ID;
+    // Collection-gated channels need the NFT's token account and metadata PDA
+    const nftAccounts = [];
+    if (options.nftMint && options.nftTokenAccount) {
+      const [metadataPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("metadata"),
+          TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+          new PublicKey(options.nftMint).toBuffer(),
+        ],
+        TOKEN_METADATA_PROGRAM_ID
+      );
+      nftAccounts.push(
+        {
+          pubkey: new PublicKey(options.nftTokenAccount),
+          isSigner: false,
+          isWritable: false,
+        },
+        { pubkey: metadataPDA, isSigner: false, isWritable: false }
+      );
+    }
+
+    // Private channels take an invite, and its code for code invites
+    const inviteCode = options.inviteCode
+      ? Buffer.from(options.inviteCode, "utf8")
+      : undefined;
+    let invitePDA = PROGRAM_ID;
+    if (options.invite) {
+      invitePDA = new PublicKey(options.invite);
+    } else {
+      const inviteSeed = inviteCode
+        ? crypto.createHash("sha256").update(inviteCode).digest()
+        : wallet.publicKey.toBuffer();
+      const [derived] = PublicKey.findProgramAddressSync(
+        [Buffer.from("channel_invite"), channelPDA.toBuffer(), inviteSeed],
+        PROGRAM_ID
+      );
+      if (await connection.getAccountInfo(derived)) {
+        invitePDA = derived;
+      }
+    }
+
+    const [banEntryPDA] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("channel_ban"),
+        channelPDA.toBuffer(),
+        wallet.publicKey.toBuffer(),
+      ],
+      PROGRAM_ID
+    );
+
     // Build subscribe instruction
-    const subscribeData = Buffer.from(DISCRIMINATORS.subscribeChannel, "hex");
+    const subscribeData = Buffer.concat([
+      Buffer.from(DISCRIMINATORS.subscribeChannel, "hex"),
+      inviteCode // invite_code (Option<Vec<u8>>)
+        ? Buffer.concat([
+            Buffer.from([1]),
+            Buffer.from(new Uint32Array([inviteCode.length]).buffer),
+            inviteCode,
+          ])
+        : Buffer.from([0]),
+    ]);
 
     const subscribeIx = new TransactionInstruction({
       programId: PROGRAM_ID,
@@ -554,6 +1087,24 @@ async function subscribeChannelCommand(options: any) {
         { pubkey: channelPDA, isSigner: false, isWritable: true }, // broadcast_channel (mut)
         { pubkey: wallet.publicKey, isSigner: true, isWritable: true }, // subscriber (signer, mut, payer)
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: welcomeThreadPDA, isSigner: false, isWritable: true }, // welcome_thread (optional)
+        { pubkey: channelVault, isSigner: false, isWritable: !!vaultInfo }, // channel_vault (optional)
+        { pubkey: gateTokenAccount, isSigner: false, isWritable: false }, // gate_token_account (optional)
+        CONFIG_META,
+        {
+          pubkey: invitePDA,
+          isSigner: false,
+          isWritable: !invitePDA.equals(PROGRAM_ID),
+        }, // channel_invite (optional)
+        { pubkey: banEntryPDA, isSigner: false, isWritable: false }, // ban_entry (must not exist)
+        await channelStatsMeta(connection, channelPDA), // channel_stats (optional)
+        { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // owner_profile (optional)
+        { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // subscriber_profile (optional)
+        { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // owner_inbox (optional)
+        { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // owner_pointer (optional)
+        { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // subscriber_inbox (optional)
+        { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // subscriber_pointer (optional)
+        ...nftAccounts, // remaining accounts for collection-gated channels
       ],
       data: subscribeData,
     });
@@ -596,7 +1147,7 @@ async function readBroadcastsCommand(options: any) {
       channelPDA,
       "channel"
     );
-    const messageCount = accountData.readUInt32LE(metadata.messageCountOffset);
+    const messageCount = Number(accountData.readBigUInt64LE(metadata.messageCountOffset));
 
     const messages = await _fetchNewMessages(
       connection,
@@ -640,30 +1191,103 @@ async function closeThreadCommand(options: any) {
     const wallet = loadWallet(options.wallet);
     const connection = createConnection(options.cluster);
     const threadPDA = new PublicKey(options.thread);
-    const recipient = new PublicKey(options.recipient || wallet.publicKey); // Default refund to self
+    // The other participant co-signs for an immediate close
+    const coSigner = options.coSigner ? loadWallet(options.coSigner) : null;
 
-    spinner.succeed(chalk.green(`Connected to ${options.cluster}`));
-    console.log(
-      chalk.gray(`  Signer (Participant A): ${wallet.publicKey.toBase58()}`)
+    const threadInfo = await connection.getAccountInfo(threadPDA);
+    if (!threadInfo) {
+      throw new Error("Thread not found");
+    }
+    // participant_a follows the discriminator and receives every rent refund
+    const participantA = new PublicKey(threadInfo.data.slice(8, 40));
+    const [closeRequestPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("thread_close_request"), threadPDA.toBuffer()],
+      PROGRAM_ID
     );
+    const closeRequest = coSigner
+      ? null
+      : await connection.getAccountInfo(closeRequestPDA);
+
+    spinner.succeed(chalk.green(`Connected to ${options.cluster}`));
+    console.log(chalk.gray(`  Signer: ${wallet.publicKey.toBase58()}`));
     console.log(chalk.gray(`  Thread: ${threadPDA.toBase58()}`));
-    console.log(chalk.gray(`  Recipient (Refund): ${recipient.toBase58()}\n`));
+    console.log(chalk.gray(`  Rent refund: ${participantA.toBase58()}\n`));
+
+    if (!coSigner && !closeRequest) {
+      // Without the other side's signature, start the veto window first
+      spinner.start("Requesting thread close...");
+      const requestIx = new TransactionInstruction({
+        programId: PROGRAM_ID,
+        keys: [
+          { pubkey: threadPDA, isSigner: false, isWritable: false },
+          { pubkey: closeRequestPDA, isSigner: false, isWritable: true },
+          { pubkey: wallet.publicKey, isSigner: true, isWritable: true }, // proposer
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          CONFIG_META,
+        ],
+        data: Buffer.from(DISCRIMINATORS.requestThreadClose, "hex"),
+      });
+      const sig = await sendAndConfirmTransaction(
+        connection,
+        new Transaction().add(requestIx),
+        [wallet],
+        { commitment: "confirmed" }
+      );
+      spinner.succeed(chalk.green("Close requested"));
+      console.log(chalk.gray(`  Signature: ${sig}`));
+      console.log(
+        chalk.yellow(
+          "  Run close-thread again in 3 days unless the other participant vetoes it."
+        )
+      );
+      process.exit(0);
+    }
 
     spinner.start("Closing thread and refunding rent...");
     // Build close instruction
     const closeData = Buffer.from(DISCRIMINATORS.closeThread, "hex");
+    // Inbox pointers to this thread are closed with it (ThreadPointer is 89 bytes)
+    const pointers = await connection.getProgramAccounts(PROGRAM_ID, {
+      filters: [
+        { dataSize: 89 },
+        { memcmp: { offset: 8 + 32, bytes: threadPDA.toBase58() } },
+      ],
+    });
+    const [pointerA, pointerB] = [0, 1].map((i) =>
+      pointers[i] ? pointers[i].pubkey : PROGRAM_ID
+    );
+    const [threadVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("thread_vault"), threadPDA.toBuffer()],
+      PROGRAM_ID
+    );
+    const [dmEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("dm_escrow"), threadPDA.toBuffer()],
+      PROGRAM_ID
+    );
 
     const closeIx = new TransactionInstruction({
       programId: PROGRAM_ID,
       keys: [
         { pubkey: threadPDA, isSigner: false, isWritable: true },
-        { pubkey: wallet.publicKey, isSigner: true, isWritable: false }, // participant_a
-        { pubkey: recipient, isSigner: false, isWritable: true }, // recipient (mut)
+        { pubkey: wallet.publicKey, isSigner: true, isWritable: true }, // closer
+        { pubkey: participantA, isSigner: false, isWritable: true }, // participant_a (mut)
+        { pubkey: pointerA, isSigner: false, isWritable: !pointerA.equals(PROGRAM_ID) },
+        { pubkey: pointerB, isSigner: false, isWritable: !pointerB.equals(PROGRAM_ID) },
+        CONFIG_META,
+        { pubkey: threadVault, isSigner: false, isWritable: false },
+        { pubkey: dmEscrow, isSigner: false, isWritable: false },
+        coSigner
+          ? { pubkey: coSigner.publicKey, isSigner: true, isWritable: false }
+          : { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // counterparty
+        closeRequest
+          ? { pubkey: closeRequestPDA, isSigner: false, isWritable: true }
+          : { pubkey: PROGRAM_ID, isSigner: false, isWritable: false }, // close_request
       ],
       data: closeData,
     });
     const tx = new Transaction().add(closeIx);
-    const sig = await sendAndConfirmTransaction(connection, tx, [wallet], {
+    const signers = coSigner ? [wallet, coSigner] : [wallet];
+    const sig = await sendAndConfirmTransaction(connection, tx, signers, {
       commitment: "confirmed",
     });
     spinner.succeed(chalk.green(`Thread closed successfully!`));
@@ -679,6 +1303,56 @@ async function closeThreadCommand(options: any) {
 /**
  * Close a broadcast channel and refund rent.
  */
+async function unsubscribeChannelCommand(options: any) {
+  console.log(chalk.bold.cyan("\n➖ Unsubscribe from Channel\n"));
+  const spinner = ora();
+  try {
+    spinner.start("Loading wallet and connecting to Solana...");
+    const wallet = loadWallet(options.wallet);
+    const connection = createConnection(options.cluster);
+    const channelPDA = new PublicKey(options.channel);
+    const [subscriptionPDA] = deriveSubscriptionPDA(
+      channelPDA,
+      wallet.publicKey
+    );
+    spinner.succeed(chalk.green(`Connected to ${options.cluster}`));
+    console.log(chalk.gray(`  Subscriber: ${wallet.publicKey.toBase58()}`));
+    console.log(chalk.gray(`  Channel: ${channelPDA.toBase58()}`));
+    console.log(
+      chalk.gray(`  Subscription PDA: ${subscriptionPDA.toBase58()}\n`)
+    );
+
+    spinner.start("Closing subscription and refunding rent...");
+    const unsubscribeData = Buffer.from(
+      DISCRIMINATORS.unsubscribeChannel,
+      "hex"
+    );
+
+    const unsubscribeIx = new TransactionInstruction({
+      programId: PROGRAM_ID,
+      keys: [
+        { pubkey: subscriptionPDA, isSigner: false, isWritable: true }, // subscription PDA (close)
+        { pubkey: channelPDA, isSigner: false, isWritable: true }, // broadcast_channel (mut)
+        { pubkey: wallet.publicKey, isSigner: true, isWritable: true }, // subscriber (signer, refund)
+        CONFIG_META,
+        await channelStatsMeta(connection, channelPDA), // channel_stats (optional)
+      ],
+      data: unsubscribeData,
+    });
+    const tx = new Transaction().add(unsubscribeIx);
+    const sig = await sendAndConfirmTransaction(connection, tx, [wallet], {
+      commitment: "confirmed",
+    });
+    spinner.succeed(chalk.green(`Unsubscribed successfully!`));
+    console.log(chalk.gray(`  Signature: ${sig}`));
+    process.exit(0);
+  } catch (error: any) {
+    spinner.fail(chalk.red("Failed to unsubscribe from channel"));
+    console.error(chalk.red(`\n❌ Error: ${error.message}`));
+    process.exit(1);
+  }
+}
+
 async function closeChannelCommand(options: any) {
   console.log(chalk.bold.cyan("\n🗑️ Close Broadcast Channel\n"));
   const spinner = ora();
@@ -704,6 +1378,7 @@ async function closeChannelCommand(options: any) {
         { pubkey: channelPDA, isSigner: false, isWritable: true },
         { pubkey: wallet.publicKey, isSigner: true, isWritable: false }, // owner
         { pubkey: recipient, isSigner: false, isWritable: true }, // recipient (mut)
+        CONFIG_META,
       ],
       data: closeData,
     });
@@ -711,7 +1386,17 @@ async function closeChannelCommand(options: any) {
     const sig = await sendAndConfirmTransaction(connection, tx, [wallet], {
       commitment: "confirmed",
     });
-    spinner.succeed(chalk.green(`Channel closed successfully!`));
+    // The first close only moves the channel into its closing period
+    if (await connection.getAccountInfo(channelPDA, "confirmed")) {
+      spinner.succeed(chalk.green(`Channel is now closing`));
+      console.log(
+        chalk.gray(
+          `  Run close-channel again once subscribers have left, or after 7 days.`
+        )
+      );
+    } else {
+      spinner.succeed(chalk.green(`Channel closed successfully!`));
+    }
     console.log(chalk.gray(`  Signature: ${sig}`));
     process.exit(0);
   } catch (error: any) {
@@ -771,7 +1456,7 @@ async function monitorPdaUpdates(options: any) {
       targetPDA,
       isThread ? "thread" : "channel"
     );
-    initialCount = accountData.readUInt32LE(metadata.messageCountOffset);
+    initialCount = Number(accountData.readBigUInt64LE(metadata.messageCountOffset));
 
     console.log(
       chalk.green(`Initial message/broadcast count: ${initialCount}\n`)
@@ -782,7 +1467,7 @@ async function monitorPdaUpdates(options: any) {
       targetPDA,
       async (updatedAccountInfo, context) => {
         const updatedData = updatedAccountInfo.data;
-        const newCount = updatedData.readUInt32LE(metadata.messageCountOffset);
+        const newCount = Number(updatedData.readBigUInt64LE(metadata.messageCountOffset));
 
         if (newCount > initialCount) {
           const timestamp = new Date().toLocaleTimeString();
@@ -886,11 +1571,49 @@ program
 program
   .command("init-thread")
   .description("Initialize a new message thread with another user")
-  .requiredOption("-r, --recipient <address>", "Recipient's public key")
+  .requiredOption("-r, --recipient <address>", "Recipient's public key or @handle")
   .option("-w, --wallet <path>", "Path to wallet keypair file")
   .option("-c, --cluster <cluster>", "Solana cluster", "devnet")
   .action(initThreadCommand);
 
+// Accept thread
+program
+  .command("accept-thread")
+  .description("Accept a thread opened with you so the initiator can send")
+  .requiredOption("-t, --thread <address>", "Thread PDA address")
+  .option("-w, --wallet <path>", "Path to wallet keypair file")
+  .option("-c, --cluster <cluster>", "Solana cluster", "devnet")
+  .action(acceptThreadCommand);
+
+// Reject thread
+program
+  .command("reject-thread")
+  .description("Decline a pending message request")
+  .requiredOption("-t, --thread <address>", "Thread PDA address")
+  .option("-w, --wallet <path>", "Path to wallet keypair file")
+  .option("-c, --cluster <cluster>", "Solana cluster", "devnet")
+  .action(rejectThreadCommand);
+
+// Block / unblock user
+program
+  .command("block")
+  .description("Block a user from opening or messaging threads with you")
+  .requiredOption("-u, --user <address>", "Wallet address to block")
+  .option("--unblock", "Remove an existing block instead")
+  .option("-w, --wallet <path>", "Path to wallet keypair file")
+  .option("-c, --cluster <cluster>", "Solana cluster", "devnet")
+  .action(blockUserCommand);
+
+// Claim / release handle
+program
+  .command("handle")
+  .description("Claim an @handle so others can message you by name")
+  .requiredOption("-n, --name <handle>", "Handle to claim (a-z, 0-9, _)")
+  .option("--release", "Release a handle you own instead")
+  .option("-w, --wallet <path>", "Path to wallet keypair file")
+  .option("-c, --cluster <cluster>", "Solana cluster", "devnet")
+  .action(handleCommand);
+
 // Send message
 program
   .command("send")
@@ -898,6 +1621,8 @@ program
   .requiredOption("-t, --thread <address>", "Thread PDA address")
   .requiredOption("-m, --message <text>", "Message to send")
   .option("-k, --key <secret>", "Encryption key (shared secret)")
+  .option("--tip <sol>", "Tip the other participant this much SOL")
+  .option("-r, --reply-to <index>", "Index of the message this one replies to")
   .option("-w, --wallet <path>", "Path to wallet keypair file")
   .option("-c, --cluster <cluster>", "Solana cluster", "devnet")
   .action(sendMessageCommand);
@@ -914,11 +1639,13 @@ program
 // Close thread
 program
   .command("close-thread")
-  .description("Close a message thread and refund rent to an account")
+  .description(
+    "Close a message thread (co-signed, or after a vetoable close request)"
+  )
   .requiredOption("-t, --thread <address>", "Thread PDA address")
   .option(
-    "-r, --recipient <address>",
-    "Account to receive the rent refund (defaults to signer)"
+    "--co-signer <path>",
+    "Other participant's keypair file, for an immediate close"
   )
   .option("-w, --wallet <path>", "Path to wallet keypair file")
   .option("-c, --cluster <cluster>", "Solana cluster", "devnet")
@@ -933,6 +1660,15 @@ program
   .command("create-channel")
   .description("Create a broadcast channel")
   .requiredOption("-n, --name <name>", "Channel name (max 32 chars)")
+  .option("-d, --description <text>", "Channel description (max 200 chars)")
+  .option(
+    "-u, --metadata-uri <uri>",
+    "Off-chain metadata JSON URI (max 200 chars)"
+  )
+  .option(
+    "--collection <mint>",
+    "Verified NFT collection mint subscribers must hold"
+  )
   .option("-w, --wallet <path>", "Path to wallet keypair file")
   .option("-c, --cluster <cluster>", "Solana cluster", "devnet")
   .action(createChannelCommand);
@@ -942,10 +1678,30 @@ program
   .command("subscribe")
   .description("Subscribe to a broadcast channel")
   .requiredOption("-ch, --channel <address>", "Channel PDA address")
+  .option(
+    "-g, --gate-token-account <address>",
+    "Your associated token account for a token-gated channel"
+  )
+  .option("--nft-mint <mint>", "Mint of your NFT for a collection-gated channel")
+  .option("--invite <address>", "Channel invite PDA for a private channel")
+  .option("--invite-code <code>", "Invite code for a private channel")
+  .option(
+    "--nft-token-account <address>",
+    "Token account holding that NFT"
+  )
   .option("-w, --wallet <path>", "Path to wallet keypair file")
   .option("-c, --cluster <cluster>", "Solana cluster", "devnet")
   .action(subscribeChannelCommand);
 
+// Unsubscribe channel
+program
+  .command("unsubscribe")
+  .description("Unsubscribe from a channel and refund the subscription rent")
+  .requiredOption("-ch, --channel <address>", "Channel PDA address")
+  .option("-w, --wallet <path>", "Path to wallet keypair file")
+  .option("-c, --cluster <cluster>", "Solana cluster", "devnet")
+  .action(unsubscribeChannelCommand);
+
 // Send broadcast
 program
   .command("send-broadcast")
@@ -956,6 +1712,11 @@ program
     "-k, --key <secret>",
     "Encryption key (shared secret for all channel messages)"
   )
+  .option(
+    "-b, --burn-after-seen",
+    "Ask subscriber clients to delete the broadcast once seen"
+  )
+  .option("-r, --reply-to <index>", "Index of the broadcast this one replies to")
   .option("-w, --wallet <path>", "Path to wallet keypair file")
   .option("-c, --cluster <cluster>", "Solana cluster", "devnet")
   .action(sendBroadcastCommand);
@@ -1007,6 +1768,9 @@ program.on("--help", () => {
   console.log(chalk.bold("Examples:"));
   console.log(chalk.yellow("  --- Direct Messaging ---"));
   console.log("  $ sol-msg init-thread -r <recipient_pubkey> -c devnet");
+  console.log("  $ sol-msg accept-thread -t <thread_pda> -c devnet");
+  console.log("  $ sol-msg reject-thread -t <thread_pda> -c devnet");
+  console.log("  $ sol-msg block -u <wallet_address>");
   console.log(
     '  $ sol-msg send -t <thread_pda> -m "Hello!" -k mykey -c devnet'
   );
@@ -1017,6 +1781,7 @@ program.on("--help", () => {
   console.log(chalk.yellow("\n  --- Broadcast Channels ---"));
   console.log('  $ sol-msg create-channel -n "Announcements"');
   console.log("  $ sol-msg subscribe -ch <channel_pda>");
+  console.log("  $ sol-msg unsubscribe -ch <channel_pda>");
   console.log(
     '  $ sol-msg send-broadcast -ch <channel_pda> -m "New Update" -k channelkey'
   );
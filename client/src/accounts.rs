@@ -0,0 +1,16 @@
+//! Account deserialization
+
+use anchor_lang::AccountDeserialize;
+
+use crate::ClientResult;
+
+pub use whatsapp_sol::{
+    BroadcastChannel, ChannelAdmin, ChannelDirectory, ChannelSubscription, Contact, DirectoryPage,
+    Handle, MessageThread, PaymentRequest, ThreadPointer, UserInbox, UserProfile,
+};
+
+/// Deserialize raw account data, checking the Anchor discriminator
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> ClientResult<T> {
+    let mut data = data;
+    Ok(T::try_deserialize(&mut data)?)
+}
@@ -0,0 +1,39 @@
+use std::fmt;
+
+use solana_rpc_client_api::client_error::Error as RpcError;
+
+pub type ClientResult<T> = Result<T, ClientError>;
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// The RPC request failed
+    Rpc(Box<RpcError>),
+    /// Account data did not deserialize as the expected type
+    Deserialize(anchor_lang::error::Error),
+    /// No account exists at the address
+    AccountNotFound(anchor_lang::prelude::Pubkey),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Rpc(err) => write!(f, "RPC error: {err}"),
+            Self::Deserialize(err) => write!(f, "failed to deserialize account: {err}"),
+            Self::AccountNotFound(address) => write!(f, "account {address} not found"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<RpcError> for ClientError {
+    fn from(err: RpcError) -> Self {
+        Self::Rpc(Box::new(err))
+    }
+}
+
+impl From<anchor_lang::error::Error> for ClientError {
+    fn from(err: anchor_lang::error::Error) -> Self {
+        Self::Deserialize(err)
+    }
+}
@@ -0,0 +1,423 @@
+//! Typed builders for the core messaging instructions
+//!
+//! Each builder derives the PDAs it needs and leaves optional accounts unset
+//! (Anchor encodes them as the program id).
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use whatsapp_sol::{accounts, instruction, Attachment, BroadcastDescriptor, MessageDescriptor, ID};
+
+use crate::pda;
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Open a thread from `participant_a` to `participant_b`
+/// Set `recipient_has_contact` when `participant_b` already lists `participant_a` as a
+/// contact, so the thread starts accepted and no outreach deposit is taken.
+/// `inbox_indices` are both inboxes' `next_index` (0 for a missing inbox, see
+/// `WhatsappSolClient::next_inbox_index`); with `None` the thread is not indexed.
+pub fn initialize_thread(
+    participant_a: &Pubkey,
+    participant_b: &Pubkey,
+    thread_id: [u8; 32],
+    recipient_has_contact: bool,
+    inbox_indices: Option<(u64, u64)>,
+) -> Instruction {
+    let (message_thread, _) = pda::message_thread(participant_a, participant_b, &thread_id);
+    let inbox = |owner: &Pubkey, index: Option<u64>| {
+        index.map(|index| {
+            (
+                pda::user_inbox(owner).0,
+                pda::thread_pointer(owner, index).0,
+            )
+        })
+    };
+    let inbox_a = inbox(participant_a, inbox_indices.map(|(a, _)| a));
+    let inbox_b = inbox(participant_b, inbox_indices.map(|(_, b)| b));
+    build(
+        accounts::InitializeThread {
+            message_thread,
+            participant_a: *participant_a,
+            participant_b: *participant_b,
+            system_program: system_program::ID,
+            block_by_a: pda::block_entry(participant_a, participant_b).0,
+            block_by_b: pda::block_entry(participant_b, participant_a).0,
+            profile_a: None,
+            profile_b: None,
+            contact_proof: None,
+            thread_vault: Some(pda::thread_vault(&message_thread).0),
+            recipient_profile: pda::user_profile(participant_b).0,
+            recipient_contact: recipient_has_contact
+                .then(|| pda::contact(participant_b, participant_a).0),
+            inbox_a: inbox_a.map(|(inbox, _)| inbox),
+            pointer_a: inbox_a.map(|(_, pointer)| pointer),
+            inbox_b: inbox_b.map(|(inbox, _)| inbox),
+            pointer_b: inbox_b.map(|(_, pointer)| pointer),
+            config: pda::config().0,
+            dm_escrow: Some(pda::dm_escrow(&message_thread).0),
+        },
+        instruction::InitializeThread { thread_id },
+    )
+}
+
+pub fn accept_thread(message_thread: &Pubkey, participant_b: &Pubkey) -> Instruction {
+    build(
+        accounts::RespondToThread {
+            message_thread: *message_thread,
+            participant_b: *participant_b,
+            config: pda::config().0,
+        },
+        instruction::AcceptThread {},
+    )
+}
+
+pub fn reject_thread(message_thread: &Pubkey, participant_b: &Pubkey) -> Instruction {
+    build(
+        accounts::RespondToThread {
+            message_thread: *message_thread,
+            participant_b: *participant_b,
+            config: pda::config().0,
+        },
+        instruction::RejectThread {},
+    )
+}
+
+/// Send message number `message_index` (the thread's current `message_count`)
+/// Set `sender_is_contact` when the recipient lists the sender as a contact, which
+/// waives the protocol message fee. With `session_owner` set, `sender` is a device
+/// key sending through that participant's session key. `device_key` names one of the
+/// sending participant's registered devices, referenced in the event for auditing.
+#[allow(clippy::too_many_arguments)]
+pub fn send_message(
+    thread: &whatsapp_sol::MessageThread,
+    message_thread: &Pubkey,
+    sender: &Pubkey,
+    message_index: u32,
+    encrypted_content: Vec<u8>,
+    reply_to_index: Option<u32>,
+    attachment: Option<Attachment>,
+    sender_is_contact: bool,
+    session_owner: Option<&Pubkey>,
+    device_key: Option<&[u8; 32]>,
+) -> Instruction {
+    let (a, b) = (&thread.participant_a, &thread.participant_b);
+    let participant = session_owner.unwrap_or(sender);
+    build(
+        accounts::SendMessage {
+            message_thread: *message_thread,
+            sender: *sender,
+            block_by_a: pda::block_entry(a, b).0,
+            block_by_b: pda::block_entry(b, a).0,
+            config: pda::config().0,
+            treasury: Some(pda::treasury().0),
+            system_program: Some(system_program::ID),
+            sender_contact: sender_is_contact
+                .then(|| pda::contact(&thread.other_participant(participant), participant).0),
+            session_key: session_owner.map(|owner| pda::session_key(owner, sender).0),
+            sender_device: device_key.map(|key| pda::device(participant, key).0),
+        },
+        instruction::SendMessage {
+            message_index,
+            encrypted_content,
+            reply_to_index,
+            attachment,
+        },
+    )
+}
+
+/// Send `messages` as numbers `first_message_index..`, with the same accounts as
+/// [`send_message`]
+pub fn send_message_batch(
+    thread: &whatsapp_sol::MessageThread,
+    message_thread: &Pubkey,
+    sender: &Pubkey,
+    first_message_index: u32,
+    messages: Vec<MessageDescriptor>,
+    sender_is_contact: bool,
+    session_owner: Option<&Pubkey>,
+) -> Instruction {
+    let (a, b) = (&thread.participant_a, &thread.participant_b);
+    let participant = session_owner.unwrap_or(sender);
+    build(
+        accounts::SendMessage {
+            message_thread: *message_thread,
+            sender: *sender,
+            block_by_a: pda::block_entry(a, b).0,
+            block_by_b: pda::block_entry(b, a).0,
+            config: pda::config().0,
+            treasury: Some(pda::treasury().0),
+            system_program: Some(system_program::ID),
+            sender_contact: sender_is_contact
+                .then(|| pda::contact(&thread.other_participant(participant), participant).0),
+            session_key: session_owner.map(|owner| pda::session_key(owner, sender).0),
+            sender_device: None,
+        },
+        instruction::SendMessageBatch {
+            first_message_index,
+            messages,
+        },
+    )
+}
+
+/// Send message number `message_index` for `sender`, with `relayer` paying the fees
+/// Must directly follow an Ed25519 instruction in which `sender` signs
+/// `whatsapp_sol::relay_message(message_thread, message_index, hash(encrypted_content))`.
+pub fn send_message_relayed(
+    thread: &whatsapp_sol::MessageThread,
+    message_thread: &Pubkey,
+    sender: &Pubkey,
+    relayer: &Pubkey,
+    message_index: u32,
+    encrypted_content: Vec<u8>,
+    sender_is_contact: bool,
+) -> Instruction {
+    let (a, b) = (&thread.participant_a, &thread.participant_b);
+    build(
+        accounts::SendMessageRelayed {
+            message_thread: *message_thread,
+            sender: *sender,
+            relayer: *relayer,
+            block_by_a: pda::block_entry(a, b).0,
+            block_by_b: pda::block_entry(b, a).0,
+            instructions_sysvar: sysvar::instructions::ID,
+            config: pda::config().0,
+            treasury: Some(pda::treasury().0),
+            system_program: Some(system_program::ID),
+            sender_contact: sender_is_contact
+                .then(|| pda::contact(&thread.other_participant(sender), sender).0),
+        },
+        instruction::SendMessageRelayed {
+            message_index,
+            encrypted_content,
+        },
+    )
+}
+
+/// Close a thread, refunding its rent (and that of the given inbox pointers) to
+/// participant A. Pass the other participant as `counterparty` to close at once,
+/// or set `with_close_request` once the closer's own request has matured.
+pub fn close_thread(
+    message_thread: &Pubkey,
+    closer: &Pubkey,
+    participant_a: &Pubkey,
+    counterparty: Option<Pubkey>,
+    with_close_request: bool,
+    pointer_a: Option<Pubkey>,
+    pointer_b: Option<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::CloseThread {
+            message_thread: *message_thread,
+            closer: *closer,
+            participant_a: *participant_a,
+            pointer_a,
+            pointer_b,
+            config: pda::config().0,
+            thread_vault: pda::thread_vault(message_thread).0,
+            dm_escrow: pda::dm_escrow(message_thread).0,
+            counterparty,
+            close_request: with_close_request.then(|| pda::thread_close_request(message_thread).0),
+        },
+        instruction::CloseThread {},
+    )
+}
+
+/// Ask to close a thread alone, subject to the other participant's veto
+pub fn request_thread_close(message_thread: &Pubkey, proposer: &Pubkey) -> Instruction {
+    build(
+        accounts::RequestThreadClose {
+            message_thread: *message_thread,
+            close_request: pda::thread_close_request(message_thread).0,
+            proposer: *proposer,
+            system_program: system_program::ID,
+            config: pda::config().0,
+        },
+        instruction::RequestThreadClose {},
+    )
+}
+
+/// Veto (or, as the proposer, withdraw) a pending thread close request
+pub fn veto_thread_close(
+    message_thread: &Pubkey,
+    proposer: &Pubkey,
+    participant: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::VetoThreadClose {
+            message_thread: *message_thread,
+            close_request: pda::thread_close_request(message_thread).0,
+            proposer: *proposer,
+            participant: *participant,
+            config: pda::config().0,
+        },
+        instruction::VetoThreadClose {},
+    )
+}
+
+/// Create a channel owned by `owner`, with `payer` covering the rent
+pub fn initialize_channel(
+    owner: &Pubkey,
+    payer: &Pubkey,
+    channel_name: String,
+    description: String,
+    metadata_uri: String,
+) -> Instruction {
+    build(
+        accounts::InitializeChannel {
+            broadcast_channel: pda::broadcast_channel(owner, &channel_name).0,
+            owner: *owner,
+            system_program: system_program::ID,
+            config: pda::config().0,
+            payer: *payer,
+        },
+        instruction::InitializeChannel {
+            channel_name,
+            description,
+            metadata_uri,
+        },
+    )
+}
+
+/// Subscribe to an ungated channel without a subscription fee or welcome thread
+/// A private channel needs `channel_invite` ([`pda::channel_invite`]), and
+/// `invite_code` when it is a code invite. `stats_day` counts the subscribe in that
+/// day's [`pda::channel_stats`], which must already be open.
+pub fn subscribe_channel(
+    broadcast_channel: &Pubkey,
+    subscriber: &Pubkey,
+    channel_invite: Option<Pubkey>,
+    invite_code: Option<Vec<u8>>,
+    stats_day: Option<u32>,
+) -> Instruction {
+    build(
+        accounts::SubscribeChannel {
+            subscription: pda::subscription(broadcast_channel, subscriber).0,
+            broadcast_channel: *broadcast_channel,
+            subscriber: *subscriber,
+            system_program: system_program::ID,
+            welcome_thread: None,
+            channel_vault: None,
+            gate_token_account: None,
+            config: pda::config().0,
+            channel_invite,
+            ban_entry: pda::channel_ban(broadcast_channel, subscriber).0,
+            channel_stats: stats_day.map(|day| pda::channel_stats(broadcast_channel, day).0),
+            owner_profile: None,
+            subscriber_profile: None,
+            owner_inbox: None,
+            owner_pointer: None,
+            subscriber_inbox: None,
+            subscriber_pointer: None,
+        },
+        instruction::SubscribeChannel { invite_code },
+    )
+}
+
+pub fn unsubscribe_channel(
+    broadcast_channel: &Pubkey,
+    subscriber: &Pubkey,
+    stats_day: Option<u32>,
+) -> Instruction {
+    build(
+        accounts::UnsubscribeChannel {
+            subscription: pda::subscription(broadcast_channel, subscriber).0,
+            broadcast_channel: *broadcast_channel,
+            subscriber: *subscriber,
+            config: pda::config().0,
+            channel_stats: stats_day.map(|day| pda::channel_stats(broadcast_channel, day).0),
+        },
+        instruction::UnsubscribeChannel {},
+    )
+}
+
+/// Broadcast as the owner, or as an admin when `as_admin` is set
+/// `device_key` names one of the sender's registered devices, for auditing, and
+/// `stats_day` counts the broadcast in that day's [`pda::channel_stats`]
+#[allow(clippy::too_many_arguments)]
+pub fn send_broadcast(
+    broadcast_channel: &Pubkey,
+    sender: &Pubkey,
+    message_index: u32,
+    encrypted_content: Vec<u8>,
+    burn_after_seen: bool,
+    reply_to_index: Option<u32>,
+    attachment: Option<Attachment>,
+    as_admin: bool,
+    device_key: Option<&[u8; 32]>,
+    stats_day: Option<u32>,
+) -> Instruction {
+    build(
+        accounts::SendBroadcast {
+            broadcast_channel: *broadcast_channel,
+            sender: *sender,
+            channel_admin: as_admin.then(|| pda::channel_admin(broadcast_channel, sender).0),
+            config: pda::config().0,
+            treasury: Some(pda::treasury().0),
+            system_program: Some(system_program::ID),
+            fee_payer: Some(*sender),
+            sender_device: device_key.map(|key| pda::device(sender, key).0),
+            channel_stats: stats_day.map(|day| pda::channel_stats(broadcast_channel, day).0),
+        },
+        instruction::SendBroadcast {
+            message_index,
+            encrypted_content,
+            burn_after_seen,
+            reply_to_index,
+            attachment,
+        },
+    )
+}
+
+/// Broadcast `broadcasts` as numbers `first_message_index..`, with the same
+/// accounts as [`send_broadcast`]
+pub fn send_broadcast_batch(
+    broadcast_channel: &Pubkey,
+    sender: &Pubkey,
+    first_message_index: u32,
+    broadcasts: Vec<BroadcastDescriptor>,
+    as_admin: bool,
+    stats_day: Option<u32>,
+) -> Instruction {
+    build(
+        accounts::SendBroadcast {
+            broadcast_channel: *broadcast_channel,
+            sender: *sender,
+            channel_admin: as_admin.then(|| pda::channel_admin(broadcast_channel, sender).0),
+            config: pda::config().0,
+            treasury: Some(pda::treasury().0),
+            system_program: Some(system_program::ID),
+            fee_payer: Some(*sender),
+            sender_device: None,
+            channel_stats: stats_day.map(|day| pda::channel_stats(broadcast_channel, day).0),
+        },
+        instruction::SendBroadcastBatch {
+            first_message_index,
+            broadcasts,
+        },
+    )
+}
+
+/// Close a channel, sending its rent to `recipient`
+pub fn close_channel(
+    broadcast_channel: &Pubkey,
+    owner: &Pubkey,
+    recipient: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::CloseChannel {
+            broadcast_channel: *broadcast_channel,
+            owner: *owner,
+            recipient: *recipient,
+            config: pda::config().0,
+        },
+        instruction::CloseChannel {},
+    )
+}
@@ -0,0 +1,21 @@
+//! Rust client for the whatsapp-sol program
+//!
+//! - [`pda`]: address derivation for every program-derived account
+//! - [`instructions`]: typed builders for the core messaging instructions
+//! - [`accounts`]: account deserialization
+//! - [`rpc`]: async helpers for fetching a user's threads, channels and subscriptions
+//!
+//! Anything not covered by a builder can still be assembled from
+//! [`whatsapp_sol::accounts`] and [`whatsapp_sol::instruction`], which are re-exported
+//! through [`program`].
+
+pub mod accounts;
+pub mod error;
+pub mod instructions;
+pub mod rpc;
+
+pub use error::{ClientError, ClientResult};
+pub use rpc::WhatsappSolClient;
+pub use whatsapp_sol as program;
+pub use whatsapp_sol::ID as PROGRAM_ID;
+pub use whatsapp_sol_interface::pda;
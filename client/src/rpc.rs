@@ -0,0 +1,194 @@
+//! Async fetch helpers built on `getProgramAccounts` filters
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient;
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use whatsapp_sol::{
+    BroadcastChannel, ChannelDirectory, ChannelSubscription, DirectoryPage, MessageThread,
+    ThreadPointer, UserInbox, ID,
+};
+
+use crate::accounts::decode;
+use crate::{pda, ClientError, ClientResult};
+
+/// Byte offsets of the fields used as filters, after the 8-byte discriminator
+const THREAD_PARTICIPANT_A_OFFSET: usize = 8;
+const THREAD_PARTICIPANT_B_OFFSET: usize = 8 + 32;
+const CHANNEL_OWNER_OFFSET: usize = 8;
+const SUBSCRIPTION_SUBSCRIBER_OFFSET: usize = 8;
+const SUBSCRIPTION_CHANNEL_OFFSET: usize = 8 + 32;
+const POINTER_THREAD_OFFSET: usize = 8 + 32;
+
+pub struct WhatsappSolClient {
+    rpc: RpcClient,
+}
+
+impl WhatsappSolClient {
+    pub fn new(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
+
+    pub fn from_url(url: impl Into<String>) -> Self {
+        Self::new(RpcClient::new(url.into()))
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    /// Fetch and deserialize a single account
+    pub async fn fetch<T: AccountDeserialize>(&self, address: &Pubkey) -> ClientResult<T> {
+        let data = self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())
+            .await?
+            .value
+            .ok_or(ClientError::AccountNotFound(*address))?
+            .data;
+        decode(&data)
+    }
+
+    /// Every thread `user` participates in, on either side
+    pub async fn threads_for(&self, user: &Pubkey) -> ClientResult<Vec<(Pubkey, MessageThread)>> {
+        let mut threads = self
+            .accounts_where::<MessageThread>(THREAD_PARTICIPANT_A_OFFSET, user)
+            .await?;
+        threads.extend(
+            self.accounts_where::<MessageThread>(THREAD_PARTICIPANT_B_OFFSET, user)
+                .await?
+                .into_iter()
+                // A thread with yourself would match both filters
+                .filter(|(_, thread)| thread.participant_a != *user),
+        );
+        Ok(threads)
+    }
+
+    /// Index the next inbox pointer of `user` will use (0 if they have no inbox yet)
+    pub async fn next_inbox_index(&self, user: &Pubkey) -> ClientResult<u64> {
+        match self.fetch::<UserInbox>(&pda::user_inbox(user).0).await {
+            Ok(inbox) => Ok(inbox.next_index),
+            Err(ClientError::AccountNotFound(_)) => Ok(0),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Threads listed in `user`'s inbox, read with one call per 100 pointers
+    /// Cheaper than [`Self::threads_for`], but only covers indexed threads
+    pub async fn inbox_threads(&self, user: &Pubkey) -> ClientResult<Vec<ThreadPointer>> {
+        let pointers: Vec<Pubkey> = (0..self.next_inbox_index(user).await?)
+            .map(|index| pda::thread_pointer(user, index).0)
+            .collect();
+        self.fetch_existing(&pointers)
+            .await
+            .map(|found| found.into_iter().map(|(_, pointer)| pointer).collect())
+    }
+
+    /// Inbox pointers to `thread`, to pass when closing it
+    pub async fn pointers_to(&self, thread: &Pubkey) -> ClientResult<Vec<(Pubkey, ThreadPointer)>> {
+        self.accounts_where(POINTER_THREAD_OFFSET, thread).await
+    }
+
+    /// Every channel listed in the public directory, page by page
+    pub async fn directory_channels(&self) -> ClientResult<Vec<Pubkey>> {
+        let page_count = match self
+            .fetch::<ChannelDirectory>(&pda::channel_directory().0)
+            .await
+        {
+            Ok(directory) => directory.page_count,
+            Err(ClientError::AccountNotFound(_)) => 0,
+            Err(err) => return Err(err),
+        };
+        let pages: Vec<Pubkey> = (0..page_count)
+            .map(|index| pda::directory_page(index).0)
+            .collect();
+        Ok(self
+            .fetch_existing::<DirectoryPage>(&pages)
+            .await?
+            .into_iter()
+            .flat_map(|(_, page)| page.channels)
+            .collect())
+    }
+
+    /// Channels owned by `owner`
+    pub async fn channels_owned_by(
+        &self,
+        owner: &Pubkey,
+    ) -> ClientResult<Vec<(Pubkey, BroadcastChannel)>> {
+        self.accounts_where(CHANNEL_OWNER_OFFSET, owner).await
+    }
+
+    /// Subscriptions held by `subscriber`
+    pub async fn subscriptions_of(
+        &self,
+        subscriber: &Pubkey,
+    ) -> ClientResult<Vec<(Pubkey, ChannelSubscription)>> {
+        self.accounts_where(SUBSCRIPTION_SUBSCRIBER_OFFSET, subscriber)
+            .await
+    }
+
+    /// Subscriptions to `channel`
+    pub async fn subscribers_of(
+        &self,
+        channel: &Pubkey,
+    ) -> ClientResult<Vec<(Pubkey, ChannelSubscription)>> {
+        self.accounts_where(SUBSCRIPTION_CHANNEL_OFFSET, channel)
+            .await
+    }
+
+    /// Channels `subscriber` is subscribed to
+    pub async fn channels_subscribed_by(
+        &self,
+        subscriber: &Pubkey,
+    ) -> ClientResult<Vec<(Pubkey, BroadcastChannel)>> {
+        let subscriptions = self.subscriptions_of(subscriber).await?;
+        let addresses: Vec<Pubkey> = subscriptions.iter().map(|(_, s)| s.channel).collect();
+        self.fetch_existing(&addresses).await
+    }
+
+    /// Deserialize whichever of `addresses` exist, skipping missing accounts
+    async fn fetch_existing<T: AccountDeserialize>(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<(Pubkey, T)>> {
+        let mut found = Vec::with_capacity(addresses.len());
+        // getMultipleAccounts accepts at most 100 addresses per call
+        for chunk in addresses.chunks(100) {
+            let accounts = self.rpc.get_multiple_accounts(chunk).await?;
+            for (address, account) in chunk.iter().zip(accounts) {
+                if let Some(account) = account {
+                    found.push((*address, decode(&account.data)?));
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    /// Program accounts of type `T` whose pubkey at `offset` equals `key`
+    async fn accounts_where<T: AccountDeserialize + Discriminator>(
+        &self,
+        offset: usize,
+        key: &Pubkey,
+    ) -> ClientResult<Vec<(Pubkey, T)>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, T::DISCRIMINATOR)),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(offset, key.as_ref())),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        self.rpc
+            .get_program_accounts_with_config(&ID, config)
+            .await?
+            .into_iter()
+            .map(|(address, account)| Ok((address, decode(&account.data)?)))
+            .collect()
+    }
+}
@@ -0,0 +1,138 @@
+//! CPI interface for calling whatsapp-sol from other Anchor programs
+//!
+//! A calling program can act as a thread participant or channel owner through a PDA:
+//! every `Signer` in whatsapp-sol accepts a PDA signed with `invoke_signed`. Where that
+//! signer also pays rent (opening a thread or channel), the PDA must be a data-less,
+//! system-owned account holding enough lamports.
+//!
+//! Account metas, in order, for the helpers below. Every user instruction also ends
+//! with the read-only `config` PDA ([`pda::config`]), checked for the pause switch.
+//!
+//! `initialize_thread`
+//! 1. `message_thread` (w): [`pda::message_thread`]
+//! 2. `participant_a` (w, s): the sender, and payer
+//! 3. `participant_b`: the recipient
+//! 4. `system_program`
+//! 5. `block_by_a`: [`pda::block_entry`]`(a, b)`
+//! 6. `block_by_b`: [`pda::block_entry`]`(b, a)`
+//! 7. `profile_a`, `profile_b`, `contact_proof`: optional, pass `None`
+//! 8. `thread_vault` (w): [`pda::thread_vault`], locked if `participant_b` asks for a deposit
+//! 9. `recipient_profile`: [`pda::user_profile`]`(b)`
+//! 10. `recipient_contact`: optional [`pda::contact`]`(b, a)`, if it exists
+//! 11. `inbox_a` (w), `pointer_a` (w): optional [`pda::user_inbox`]`(a)` and
+//!     [`pda::thread_pointer`]`(a, inbox.next_index)`, to index the thread
+//! 12. `inbox_b` (w), `pointer_b` (w): the same for `participant_b`
+//! 13. `config`
+//! 14. `dm_escrow` (w): [`pda::dm_escrow`], funded if `participant_b` charges a DM fee
+//!
+//! `send_message`
+//! 1. `message_thread` (w)
+//! 2. `sender` (w, s): pays the protocol message fee, if any
+//! 3. `block_by_a`, `block_by_b`: as above, from the thread's stored participants
+//! 4. `config`
+//! 5. `treasury` (w), `system_program`: [`pda::treasury`], needed while a fee is charged
+//! 6. `sender_contact`: optional [`pda::contact`]`(recipient, sender)`, waives the fee
+//! 7. `session_key`: optional [`pda::session_key`]`(owner, sender)`, lets a device key
+//!    send as `owner`; the contact above is then derived for `owner`
+//! 8. `sender_device`: optional [`pda::device`]`(sender, device_key)`, or for `owner`
+//!    with a session key; referenced in `MessageSent`
+//!
+//! `send_broadcast`
+//! 1. `broadcast_channel` (w): [`pda::broadcast_channel`]
+//! 2. `sender` (s): channel owner or admin; writable when it pays the fee itself
+//! 3. `channel_admin`: optional [`pda::channel_admin`], when the sender is an admin
+//! 4. `config`
+//! 5. `treasury` (w), `system_program`: as above; verified channels pay no fee
+//! 6. `fee_payer` (w, s): optional, pays the fee instead of the sender, e.g. when the
+//!    sender is a PDA that holds data
+//! 7. `sender_device`: optional [`pda::device`]`(sender, device_key)`, referenced in
+//!    `BroadcastSent`
+//! 8. `channel_stats` (w): optional [`pda::channel_stats`] for the current UTC day
+//!
+//! `deliver_notification`
+//! 1. `notification_inbox` (w): [`pda::notification_inbox`] of the recipient
+//! 2. `sender` (w, s): the calling program's [`pda::notifier_signer`]
+//! 3. `config`
+//! 4. `notifier`: [`pda::notifier`]`(calling program)`, set by the config authority;
+//!    without it the sender pays a fee and needs `treasury` and `system_program`
+
+pub mod pda;
+
+use anchor_lang::prelude::*;
+use whatsapp_sol::cpi::accounts::{
+    DeliverNotification, InitializeThread, SendBroadcast, SendMessage,
+};
+
+pub use whatsapp_sol::cpi;
+pub use whatsapp_sol::program::WhatsappSol;
+pub use whatsapp_sol::Attachment;
+pub use whatsapp_sol::{BroadcastChannel, ChannelSubscription, MessageThread, ID};
+
+/// Open a thread, signing for `participant_a` with `signer_seeds`
+pub fn initialize_thread<'info>(
+    program: AccountInfo<'info>,
+    accounts: InitializeThread<'info>,
+    signer_seeds: &[&[&[u8]]],
+    thread_id: [u8; 32],
+) -> Result<()> {
+    cpi::initialize_thread(
+        CpiContext::new_with_signer(program, accounts, signer_seeds),
+        thread_id,
+    )
+}
+
+/// Send a thread message, signing for `sender` with `signer_seeds`
+pub fn send_message<'info>(
+    program: AccountInfo<'info>,
+    accounts: SendMessage<'info>,
+    signer_seeds: &[&[&[u8]]],
+    message_index: u32,
+    encrypted_content: Vec<u8>,
+    reply_to_index: Option<u32>,
+    attachment: Option<Attachment>,
+) -> Result<()> {
+    cpi::send_message(
+        CpiContext::new_with_signer(program, accounts, signer_seeds),
+        message_index,
+        encrypted_content,
+        reply_to_index,
+        attachment,
+    )
+}
+
+/// Broadcast to a channel, signing for `sender` with `signer_seeds`
+#[allow(clippy::too_many_arguments)]
+pub fn send_broadcast<'info>(
+    program: AccountInfo<'info>,
+    accounts: SendBroadcast<'info>,
+    signer_seeds: &[&[&[u8]]],
+    message_index: u32,
+    encrypted_content: Vec<u8>,
+    burn_after_seen: bool,
+    reply_to_index: Option<u32>,
+    attachment: Option<Attachment>,
+) -> Result<()> {
+    cpi::send_broadcast(
+        CpiContext::new_with_signer(program, accounts, signer_seeds),
+        message_index,
+        encrypted_content,
+        burn_after_seen,
+        reply_to_index,
+        attachment,
+    )
+}
+
+/// Push a notification to a user's inbox, signing for `sender` with `signer_seeds`
+pub fn deliver_notification<'info>(
+    program: AccountInfo<'info>,
+    accounts: DeliverNotification<'info>,
+    signer_seeds: &[&[&[u8]]],
+    kind: u16,
+    payload_hash: [u8; 32],
+) -> Result<()> {
+    cpi::deliver_notification(
+        CpiContext::new_with_signer(program, accounts, signer_seeds),
+        kind,
+        payload_hash,
+    )
+}
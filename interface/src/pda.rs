@@ -0,0 +1,222 @@
+//! Program-derived addresses, mirroring the seeds in the program
+//!
+//! Usable on-chain and off-chain.
+
+use anchor_lang::prelude::Pubkey;
+use whatsapp_sol::{sorted_participants, ID};
+
+fn find(seeds: &[&[u8]]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(seeds, &ID)
+}
+
+/// Thread between two participants; the pair is sorted, so either side derives the same address
+pub fn message_thread(a: &Pubkey, b: &Pubkey, thread_id: &[u8; 32]) -> (Pubkey, u8) {
+    let (first, second) = sorted_participants(a, b);
+    find(&[
+        b"message_thread",
+        first.as_ref(),
+        second.as_ref(),
+        thread_id,
+    ])
+}
+
+pub fn broadcast_channel(creator: &Pubkey, channel_name: &str) -> (Pubkey, u8) {
+    find(&[
+        b"broadcast_channel",
+        creator.as_ref(),
+        channel_name.as_bytes(),
+    ])
+}
+
+pub fn subscription(channel: &Pubkey, subscriber: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"subscription", channel.as_ref(), subscriber.as_ref()])
+}
+
+pub fn subscription_wallet(subscription: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"subscription_wallet", subscription.as_ref()])
+}
+
+pub fn channel_admin(channel: &Pubkey, admin: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"channel_admin", channel.as_ref(), admin.as_ref()])
+}
+
+pub fn channel_vault(channel: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"channel_vault", channel.as_ref()])
+}
+
+/// Entry recording that `blocker` blocked `blocked`
+pub fn block_entry(blocker: &Pubkey, blocked: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"block_entry", blocker.as_ref(), blocked.as_ref()])
+}
+
+pub fn user_profile(user: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"user_profile", user.as_ref()])
+}
+
+/// `contact` in `owner`'s address book
+pub fn contact(owner: &Pubkey, contact: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"contact", owner.as_ref(), contact.as_ref()])
+}
+
+pub fn handle(handle: &str) -> (Pubkey, u8) {
+    find(&[b"handle", handle.as_bytes()])
+}
+
+pub fn user_inbox(owner: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"user_inbox", owner.as_ref()])
+}
+
+/// Pointer number `index` in `owner`'s inbox
+pub fn thread_pointer(owner: &Pubkey, index: u64) -> (Pubkey, u8) {
+    find(&[b"thread_pointer", owner.as_ref(), &index.to_le_bytes()])
+}
+
+pub fn thread_vault(thread: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"thread_vault", thread.as_ref()])
+}
+
+pub fn dm_escrow(thread: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"dm_escrow", thread.as_ref()])
+}
+
+pub fn thread_close_request(thread: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"thread_close_request", thread.as_ref()])
+}
+
+pub fn payment_request(thread: &Pubkey, request_id: u64) -> (Pubkey, u8) {
+    find(&[
+        b"payment_request",
+        thread.as_ref(),
+        &request_id.to_le_bytes(),
+    ])
+}
+
+/// Compressed log of a thread or channel
+pub fn compressed_log(target: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"compressed_log", target.as_ref()])
+}
+
+pub fn quiet_hours(user: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"quiet_hours", user.as_ref()])
+}
+
+pub fn archive_preference(user: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"archive_preference", user.as_ref()])
+}
+
+pub fn channel_directory() -> (Pubkey, u8) {
+    find(&[b"channel_directory"])
+}
+
+pub fn directory_page(index: u32) -> (Pubkey, u8) {
+    find(&[b"directory_page", &index.to_le_bytes()])
+}
+
+pub fn directory_listing(channel: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"directory_listing", channel.as_ref()])
+}
+
+pub fn config() -> (Pubkey, u8) {
+    find(&[b"config"])
+}
+
+pub fn treasury() -> (Pubkey, u8) {
+    find(&[b"treasury"])
+}
+
+pub fn session_key(owner: &Pubkey, device: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"session_key", owner.as_ref(), device.as_ref()])
+}
+
+/// `seed` is the invitee's key, or the invite code's hash
+pub fn channel_invite(channel: &Pubkey, seed: &[u8; 32]) -> (Pubkey, u8) {
+    find(&[b"channel_invite", channel.as_ref(), seed])
+}
+
+pub fn channel_ban(channel: &Pubkey, subscriber: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"channel_ban", channel.as_ref(), subscriber.as_ref()])
+}
+
+pub fn report(target: &Pubkey, message_index: u32, reporter: &Pubkey) -> (Pubkey, u8) {
+    find(&[
+        b"report",
+        target.as_ref(),
+        &message_index.to_le_bytes(),
+        reporter.as_ref(),
+    ])
+}
+
+pub fn scheduled_broadcast(channel: &Pubkey, content_hash: &[u8; 32]) -> (Pubkey, u8) {
+    find(&[b"scheduled_broadcast", channel.as_ref(), content_hash])
+}
+
+pub fn schedule_index(channel: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"schedule_index", channel.as_ref()])
+}
+
+pub fn pinned_messages(target: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"pinned_messages", target.as_ref()])
+}
+
+pub fn device(owner: &Pubkey, device_key: &[u8; 32]) -> (Pubkey, u8) {
+    find(&[b"device", owner.as_ref(), device_key])
+}
+
+pub fn channel_key_wrap(channel: &Pubkey, subscriber: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"channel_key_wrap", channel.as_ref(), subscriber.as_ref()])
+}
+
+pub fn thread_escrow(thread: &Pubkey, escrow_id: u64) -> (Pubkey, u8) {
+    find(&[b"thread_escrow", thread.as_ref(), &escrow_id.to_le_bytes()])
+}
+
+/// `day` is the UTC day as days since the Unix epoch
+pub fn channel_stats(channel: &Pubkey, day: u32) -> (Pubkey, u8) {
+    find(&[b"channel_stats", channel.as_ref(), &day.to_le_bytes()])
+}
+
+pub fn notification_inbox(owner: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"notification_inbox", owner.as_ref()])
+}
+
+pub fn notifier(program: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"notifier", program.as_ref()])
+}
+
+/// The `["notifier"]` PDA a whitelisted `program` signs deliveries with; derived
+/// under the calling program, not whatsapp-sol
+pub fn notifier_signer(program: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"notifier"], program)
+}
+
+pub fn subscriber_snapshot(channel: &Pubkey, snapshot_id: u64) -> (Pubkey, u8) {
+    find(&[
+        b"subscriber_snapshot",
+        channel.as_ref(),
+        &snapshot_id.to_le_bytes(),
+    ])
+}
+
+pub fn status(user_profile: &Pubkey, content_hash: &[u8; 32]) -> (Pubkey, u8) {
+    find(&[b"status", user_profile.as_ref(), content_hash])
+}
+
+pub fn status_view(status: &Pubkey, viewer: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"status_view", status.as_ref(), viewer.as_ref()])
+}
+
+pub fn seen_pointer(thread: &Pubkey, participant: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"seen_pointer", thread.as_ref(), participant.as_ref()])
+}
+
+pub fn call_session(thread: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"call_session", thread.as_ref()])
+}
+
+pub fn reward_pool(channel: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"reward_pool", channel.as_ref()])
+}
+
+pub fn reward_claim(reward_pool: &Pubkey, subscriber: &Pubkey) -> (Pubkey, u8) {
+    find(&[b"reward_claim", reward_pool.as_ref(), subscriber.as_ref()])
+}
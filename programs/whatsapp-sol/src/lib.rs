@@ -1,7 +1,116 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::system_program::{
+    allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer,
+};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::metadata::mpl_token_metadata::accounts::Metadata;
+use anchor_spl::metadata::MetadataAccount;
+use anchor_spl::token_interface::{
+    close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface,
+    TransferChecked,
+};
+use solana_instructions_sysvar::get_instruction_relative;
+use solana_sdk_ids::ed25519_program;
+use solana_sha256_hasher::{hash, hashv};
 
 declare_id!("9tN5NBvynubfJwQWDqrSoHEE3Xy2MVj3BmHdLu13wCcS");
 
+/// Number of minutes in a UTC day, used for quiet hour windows
+pub const MINUTES_PER_DAY: u16 = 1440;
+
+/// Number of seconds in a UTC day, used to bucket channel stats
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// How long before expiry `process_renewal` may renew a subscription
+pub const RENEWAL_WINDOW_SECONDS: i64 = SECONDS_PER_DAY;
+
+/// Default number of messages one party may send in a row before a reply
+pub const DEFAULT_MAX_CONSECUTIVE: u32 = 10;
+
+/// How long after sending the latest thread message may still be edited
+pub const EDIT_WINDOW_SECONDS: i64 = 15 * 60;
+
+/// Lamports paid to the protocol treasury to claim a handle
+pub const HANDLE_REGISTRATION_FEE: u64 = 10_000_000;
+
+/// Seconds a closing channel stays open for subscribers to leave
+pub const CHANNEL_CLOSING_GRACE_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+/// Seconds a recipient has to engage before a DM fee can be refunded
+pub const DM_FEE_TIMEOUT_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+/// Seconds the other participant has to veto a one-sided thread close
+pub const THREAD_CLOSE_TIMELOCK_SECONDS: i64 = 3 * 24 * 60 * 60;
+
+/// Deepest subscriber snapshot proof accepted (trees of up to 2^32 leaves)
+pub const MAX_SNAPSHOT_PROOF_LEN: usize = 32;
+
+/// Notifications kept per inbox, and the fee for a non-whitelisted delivery
+pub const MAX_NOTIFICATIONS: usize = 16;
+pub const NOTIFICATION_FEE_LAMPORTS: u64 = 50_000;
+
+/// Lamports a reporter locks in a `Report`, forfeited if it is dismissed
+pub const REPORT_DEPOSIT_LAMPORTS: u64 = 10_000_000;
+
+/// Allowed handle length in bytes
+pub const MIN_HANDLE_LEN: usize = 3;
+pub const MAX_HANDLE_LEN: usize = 20;
+
+/// Current account layout version, written to every account on creation
+/// Bump it with each schema change and teach the `migrate_*` instructions the upgrade
+pub const ACCOUNT_VERSION: u8 = 12;
+
+/// Channels per public directory page
+pub const DIRECTORY_PAGE_CAPACITY: usize = 32;
+
+/// Attachment limits: content id, MIME type and wrapped-key bytes, and file size
+pub const MAX_ATTACHMENT_CID_LEN: usize = 100;
+pub const MAX_ATTACHMENT_MIME_LEN: usize = 64;
+pub const MAX_ATTACHMENT_KEY_WRAP_LEN: usize = 128;
+pub const MAX_ATTACHMENT_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Bytes of a channel key sealed to one subscriber
+pub const MAX_CHANNEL_KEY_WRAP_LEN: usize = 128;
+
+/// Options per poll, and bytes per option label
+pub const MAX_POLL_OPTIONS: usize = 10;
+pub const MAX_POLL_OPTION_LEN: usize = 50;
+
+/// Pinned messages per thread or channel
+pub const MAX_PINNED_MESSAGES: usize = 5;
+
+/// Key changes kept in a thread's `KeyTransparencyLog`
+pub const MAX_KEY_LOG_ENTRIES: usize = 16;
+
+/// Scheduled broadcasts a channel may have pending at once
+pub const MAX_SCHEDULED_BROADCASTS: usize = 16;
+
+/// Messages per `send_message_batch` or `send_broadcast_batch`
+pub const MAX_BATCH_MESSAGES: usize = 10;
+
+/// Status updates: longest content URI and lifetime
+pub const MAX_STATUS_URI_LEN: usize = 200;
+pub const MAX_STATUS_LIFETIME_SECONDS: i64 = SECONDS_PER_DAY;
+
+/// Seconds an unanswered call rings before anyone can clear it
+pub const CALL_RING_TIMEOUT_SECONDS: i64 = 60;
+
+/// spl-account-compression program, which owns concurrent Merkle trees
+pub const SPL_ACCOUNT_COMPRESSION_ID: Pubkey =
+    pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+
+/// spl-noop program, used by spl-account-compression to log tree changes
+pub const SPL_NOOP_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+/// Prefix of the bytes a sender signs for a relayed message, so the signature
+/// can't be mistaken for any other signed payload
+pub const RELAY_DOMAIN: &[u8] = b"relay_message";
+
+/// Prefix of the bytes hashed into a contact verification commitment
+pub const VERIFICATION_DOMAIN: &[u8] = b"contact_verification";
+
 #[program]
 pub mod whatsapp_sol {
     use super::*;
@@ -9,77 +118,691 @@ pub fn initialize_thread(
         ctx: Context<InitializeThread>,
         thread_id: [u8; 32],
     ) -> Result<()> {
+        ensure_not_blocked(&ctx.accounts.block_by_a)?;
+        ensure_not_blocked(&ctx.accounts.block_by_b)?;
+
+        // Participant B keeping A in their contacts pre-accepts the thread
+        let is_contact = ctx.accounts.recipient_contact.is_some();
+        let config = read_config(&ctx.accounts.config)?;
         let thread = &mut ctx.accounts.message_thread;
+        let now = Clock::get()?.unix_timestamp;
         
-        thread.participant_a = ctx.accounts.participant_a.key();
-        thread.participant_b = ctx.accounts.participant_b.key();
-        thread.thread_id = thread_id;
-        thread.message_count = 0;
-        thread.created_at = Clock::get()?.unix_timestamp;
-        thread.last_message_at = 0;
+        thread.set_inner(new_thread(
+            ctx.accounts.participant_a.key(),
+            ctx.accounts.participant_b.key(),
+            thread_id,
+            (
+                ctx.accounts.profile_a.as_ref().map_or(0, |p| p.key_version),
+                ctx.accounts.profile_b.as_ref().map_or(0, |p| p.key_version),
+            ),
+            config.as_ref(),
+            ctx.bumps.message_thread,
+            now,
+        ));
+        if is_contact {
+            thread.status = ThreadStatus::Accepted;
+        }
+
+        emit!(ThreadInitialized {
+            thread: thread.key(),
+            participant_a: thread.participant_a,
+            participant_b: thread.participant_b,
+            thread_id,
+            timestamp: now,
+        });
 
         msg!("Message thread initialized!");
         msg!("Participant A: {}", thread.participant_a);
         msg!("Participant B: {}", thread.participant_b);
         msg!("Thread ID: {:?}", thread_id);
 
+        // Index the thread in each participant's inbox that was passed
+        let participant_a = ctx.accounts.participant_a.key();
+        let participant_b = ctx.accounts.participant_b.key();
+        let thread_key = ctx.accounts.message_thread.key();
+        let payer = ctx.accounts.participant_a.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        for (inbox, pointer, owner) in [
+            (
+                ctx.accounts.inbox_a.as_mut(),
+                ctx.accounts.pointer_a.as_ref(),
+                participant_a,
+            ),
+            (
+                ctx.accounts.inbox_b.as_mut(),
+                ctx.accounts.pointer_b.as_ref(),
+                participant_b,
+            ),
+        ] {
+            if let Some(inbox) = inbox {
+                let pointer = pointer.ok_or(MessagingError::InvalidThreadPointer)?;
+                register_thread_pointer(
+                    inbox,
+                    pointer,
+                    owner,
+                    thread_key,
+                    payer.clone(),
+                    system_program.clone(),
+                    ctx.program_id,
+                    now,
+                )?;
+            }
+        }
+
+        // Cold outreach: escrow the recipient's DM fee and lock their required
+        // deposit unless the two are verified or saved contacts
+        let (required, dm_fee) = read_profile(&ctx.accounts.recipient_profile)?
+            .map_or((0, 0), |p| (p.outreach_deposit_lamports, p.dm_fee_lamports));
+        if is_contact
+            || are_verified_contacts(
+                ctx.accounts.contact_proof.as_ref(),
+                &participant_a,
+                &participant_b,
+            )
+        {
+            return Ok(());
+        }
+
+        if dm_fee > 0 {
+            let escrow = ctx
+                .accounts
+                .dm_escrow
+                .as_ref()
+                .ok_or(MessagingError::DmFeeRequired)?;
+            let (expected_escrow, bump) =
+                Pubkey::find_program_address(&[b"dm_escrow", thread_key.as_ref()], ctx.program_id);
+            require_keys_eq!(escrow.key(), expected_escrow, MessagingError::DmFeeRequired);
+
+            let escrow_info = escrow.to_account_info();
+            create_pda_account(
+                ctx.accounts.participant_a.to_account_info(),
+                escrow_info.clone(),
+                &[b"dm_escrow", thread_key.as_ref(), &[bump]],
+                8 + DmEscrow::INIT_SPACE,
+                ctx.accounts.system_program.to_account_info(),
+                ctx.program_id,
+            )?;
+            transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.participant_a.to_account_info(),
+                        to: escrow_info.clone(),
+                    },
+                ),
+                dm_fee,
+            )?;
+
+            let expires_at = now
+                .checked_add(DM_FEE_TIMEOUT_SECONDS)
+                .ok_or(MessagingError::Overflow)?;
+            let fee = DmEscrow {
+                thread: thread_key,
+                sender: participant_a,
+                recipient: participant_b,
+                amount: dm_fee,
+                expires_at,
+                version: ACCOUNT_VERSION,
+            };
+            fee.try_serialize(&mut &mut escrow_info.try_borrow_mut_data()?[..])?;
+
+            emit!(DmFeeEscrowed {
+                thread: thread_key,
+                escrow: expected_escrow,
+                sender: participant_a,
+                recipient: participant_b,
+                amount: dm_fee,
+                expires_at,
+            });
+
+            msg!("DM fee escrowed: {} lamports", dm_fee);
+        }
+
+        if required == 0 {
+            return Ok(());
+        }
+
+        let vault = ctx
+            .accounts
+            .thread_vault
+            .as_ref()
+            .ok_or(MessagingError::DepositRequired)?;
+        let (expected_vault, bump) =
+            Pubkey::find_program_address(&[b"thread_vault", thread_key.as_ref()], ctx.program_id);
+        require_keys_eq!(vault.key(), expected_vault, MessagingError::DepositRequired);
+
+        let vault_info = vault.to_account_info();
+        create_pda_account(
+            ctx.accounts.participant_a.to_account_info(),
+            vault_info.clone(),
+            &[b"thread_vault", thread_key.as_ref(), &[bump]],
+            8 + ThreadVault::INIT_SPACE,
+            ctx.accounts.system_program.to_account_info(),
+            ctx.program_id,
+        )?;
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.participant_a.to_account_info(),
+                    to: vault_info.clone(),
+                },
+            ),
+            required,
+        )?;
+
+        let deposit = ThreadVault {
+            thread: thread_key,
+            depositor: participant_a,
+            recipient: participant_b,
+            amount: required,
+            version: ACCOUNT_VERSION,
+        };
+        deposit.try_serialize(&mut &mut vault_info.try_borrow_mut_data()?[..])?;
+
+        emit!(OutreachDepositLocked {
+            thread: thread_key,
+            vault: expected_vault,
+            depositor: participant_a,
+            recipient: participant_b,
+            amount: required,
+            timestamp: now,
+        });
+
+        msg!("Outreach deposit locked: {} lamports", required);
+
         Ok(())
     }
 
     /// Send a message in a thread
-    /// The message content is stored in transaction data, not in the PDA
+    /// The message content is stored in transaction data, not in the PDA;
+    /// only a hash of the latest message is kept for edits.
+    /// `reply_to_index` quotes an earlier message of the thread.
+    /// The signer may be a participant's device key when its session key is passed
     pub fn send_message(
         ctx: Context<SendMessage>,
         message_index: u32,
-        _encrypted_content: Vec<u8>, // Prefixed with _ since we don't store it
+        encrypted_content: Vec<u8>,
+        reply_to_index: Option<u32>,
+        attachment: Option<Attachment>,
+    ) -> Result<()> {
+        check_reply_to(reply_to_index, message_index)?;
+        if let Some(attachment) = &attachment {
+            attachment.validate()?;
+        }
+        ensure_not_blocked(&ctx.accounts.block_by_a)?;
+        ensure_not_blocked(&ctx.accounts.block_by_b)?;
+
+        let thread = &mut ctx.accounts.message_thread;
+        let content_hash = hash(&encrypted_content).to_bytes();
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        // A session key sends as its owner
+        let sender = match &ctx.accounts.session_key {
+            Some(session) => session.authorize(&thread.key(), timestamp)?,
+            None => ctx.accounts.sender.key(),
+        };
+
+        thread.record_message(sender, message_index, content_hash, timestamp)?;
+
+        emit!(MessageSent {
+            thread: thread.key(),
+            sender,
+            message_index,
+            content_hash,
+            timestamp,
+            reply_to_index,
+            attachment,
+            device: ctx
+                .accounts
+                .sender_device
+                .as_ref()
+                .map(|device| device.key()),
+        });
+
+        msg!("Message {} sent by {}", message_index, sender);
+        msg!("Thread messages: {}", thread.message_count);
+
+        charge_message_fee(ctx.accounts, 1)?;
+
+        Ok(())
+    }
+
+    /// Forward a thread message or broadcast into a thread
+    /// The content is re-encrypted for the destination. A `ForwardRecord` keeps the
+    /// source, index and original sender, all checked on-chain: a thread message's
+    /// sender comes from its `MessageRecord` (or the thread, for the latest message),
+    /// a broadcast is attributed to the channel owner.
+    pub fn forward_message(
+        ctx: Context<ForwardMessage>,
+        source_index: u32,
+        message_index: u32,
+        encrypted_content: Vec<u8>,
+    ) -> Result<()> {
+        let source_key = ctx.accounts.source.key();
+        let forwarder = ctx.accounts.forwarder.key();
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        let source = read_message_host(&ctx.accounts.source, MessagingError::InvalidForwardSource)?;
+        require!(
+            u64::from(source_index) < source.message_count(),
+            MessagingError::InvalidMessageIndex
+        );
+        let original_sender = match &source {
+            MessageHost::Thread(thread) => {
+                require!(
+                    thread.is_participant(&forwarder),
+                    MessagingError::UnauthorizedSender
+                );
+                match &ctx.accounts.source_record {
+                    Some(record) => {
+                        require!(
+                            record.thread == source_key && record.message_index == source_index,
+                            MessagingError::InvalidForwardSource
+                        );
+                        record.sender
+                    }
+                    None => {
+                        require!(
+                            u64::from(source_index) + 1 == thread.message_count,
+                            MessagingError::ProvenanceUnavailable
+                        );
+                        thread.last_sender
+                    }
+                }
+            }
+            MessageHost::Channel(channel) => {
+                if forwarder != channel.owner {
+                    let subscription = ctx
+                        .accounts
+                        .source_subscription
+                        .as_ref()
+                        .ok_or(MessagingError::SubscriptionInactive)?;
+                    require!(
+                        subscription.belongs_to(&source_key, channel.created_at)
+                            && subscription.subscriber == forwarder,
+                        MessagingError::SubscriptionMismatch
+                    );
+                    require!(
+                        subscription.is_active(timestamp),
+                        MessagingError::SubscriptionInactive
+                    );
+                }
+                channel.owner
+            }
+        };
+
+        let destination = &mut ctx.accounts.destination_thread;
+        require!(
+            destination.is_participant(&forwarder),
+            MessagingError::UnauthorizedSender
+        );
+
+        let content_hash = hash(&encrypted_content).to_bytes();
+        destination.record_message(forwarder, message_index, content_hash, timestamp)?;
+
+        let record = &mut ctx.accounts.forward_record;
+
+        record.destination_thread = destination.key();
+        record.message_index = message_index;
+        record.source = source_key;
+        record.source_kind = source.kind();
+        record.source_index = source_index;
+        record.original_sender = original_sender;
+        record.forwarder = forwarder;
+        record.forwarded_at = timestamp;
+        record.version = ACCOUNT_VERSION;
+
+        emit!(MessageForwarded {
+            source: source_key,
+            source_index,
+            destination_thread: destination.key(),
+            message_index,
+            forwarder,
+            content_hash,
+            timestamp,
+            original_sender,
+        });
+
+        msg!("Message {} forwarded by {}", source_index, forwarder);
+        msg!("Destination messages: {}", destination.message_count);
+
+        charge_message_fee(ctx.accounts, 1)?;
+
+        Ok(())
+    }
+
+    /// Edit the latest message in a thread
+    /// Only its sender may edit, and only within the edit window
+    pub fn edit_latest_message(
+        ctx: Context<EditLatestMessage>,
+        message_index: u32,
+        encrypted_content: Vec<u8>,
     ) -> Result<()> {
         let thread = &mut ctx.accounts.message_thread;
         let sender = ctx.accounts.sender.key();
-        
-        // Verify sender is a participant
+        let now = Clock::get()?.unix_timestamp;
+
         require!(
             sender == thread.participant_a || sender == thread.participant_b,
             MessagingError::UnauthorizedSender
         );
-
-        // Verify message index is sequential
+        require!(thread.message_count > 0, MessagingError::NoMessages);
         require!(
-            message_index == thread.message_count,
+            u64::from(message_index) == thread.message_count - 1,
             MessagingError::InvalidMessageIndex
         );
+        require!(
+            sender == thread.last_sender,
+            MessagingError::UnauthorizedSender
+        );
+        require!(
+            now - thread.last_message_at <= EDIT_WINDOW_SECONDS,
+            MessagingError::EditWindowClosed
+        );
 
-        thread.message_count += 1;
-        thread.last_message_at = Clock::get()?.unix_timestamp;
+        // last_message_at is left alone so edits can't extend the window
+        thread.last_content_hash = hash(&encrypted_content).to_bytes();
 
-        msg!("Message {} sent by {}", message_index, sender);
+        emit!(MessageEdited {
+            thread: thread.key(),
+            sender,
+            message_index,
+            content_hash: thread.last_content_hash,
+            timestamp: now,
+        });
+
+        msg!("Message {} edited by {}", message_index, sender);
+
+        Ok(())
+    }
+
+    /// Accept a thread opened by participant A so messages can flow
+    /// Also reopens a thread participant B previously rejected
+    pub fn accept_thread(ctx: Context<RespondToThread>) -> Result<()> {
+        let thread = &mut ctx.accounts.message_thread;
+
+        thread.status = ThreadStatus::Accepted;
+
+        emit!(ThreadAccepted {
+            thread: thread.key(),
+            participant_b: thread.participant_b,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Thread accepted by {}", thread.participant_b);
+
+        Ok(())
+    }
+
+    /// Decline a pending message request; no further messages can be sent
+    pub fn reject_thread(ctx: Context<RespondToThread>) -> Result<()> {
+        let thread = &mut ctx.accounts.message_thread;
+
+        require!(
+            thread.status == ThreadStatus::Pending,
+            MessagingError::ThreadNotPending
+        );
+
+        thread.status = ThreadStatus::Rejected;
+
+        emit!(ThreadRejected {
+            thread: thread.key(),
+            participant_b: thread.participant_b,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Thread rejected by {}", thread.participant_b);
+
+        Ok(())
+    }
+
+    /// Record the message count a participant's client has observed
+    /// A mismatch with `message_count` tells clients to resync
+    pub fn reconcile_count(ctx: Context<ReconcileCount>, observed_count: u64) -> Result<()> {
+        let thread = &mut ctx.accounts.message_thread;
+        let participant = ctx.accounts.participant.key();
+
+        if participant == thread.participant_a {
+            thread.a_observed_count = observed_count;
+        } else if participant == thread.participant_b {
+            thread.b_observed_count = observed_count;
+        } else {
+            return err!(MessagingError::UnauthorizedSender);
+        }
+
+        emit!(CountReconciled {
+            thread: thread.key(),
+            participant,
+            message_count: thread.message_count,
+            a_observed_count: thread.a_observed_count,
+            b_observed_count: thread.b_observed_count,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Observed count {} reported by {}",
+            observed_count,
+            participant
+        );
         msg!("Thread messages: {}", thread.message_count);
 
         Ok(())
     }
 
+    /// Record a signed acknowledgment of the thread's latest message
+    /// The recipient signs `ack_message(thread, index, content_hash)` off-chain;
+    /// the signature is checked by an Ed25519 instruction placed just before this one
+    pub fn sign_acknowledgment(ctx: Context<SignAcknowledgment>, message_index: u32) -> Result<()> {
+        let thread = &ctx.accounts.message_thread;
+        let recipient = ctx.accounts.recipient.key();
+
+        require!(
+            recipient == thread.participant_a || recipient == thread.participant_b,
+            MessagingError::UnauthorizedSender
+        );
+        require!(thread.message_count > 0, MessagingError::NoMessages);
+        require!(
+            u64::from(message_index) == thread.message_count - 1,
+            MessagingError::InvalidMessageIndex
+        );
+        // Only the receiving side can acknowledge
+        require!(
+            recipient != thread.last_sender,
+            MessagingError::UnauthorizedSender
+        );
+
+        let message = ack_message(&thread.key(), message_index, &thread.last_content_hash);
+        let ed25519_ix = get_instruction_relative(-1, &ctx.accounts.instructions_sysvar)
+            .map_err(|_| MessagingError::InvalidSignature)?;
+        let signature = verify_ed25519_instruction(&ed25519_ix, &recipient, &message)?;
+
+        let receipt = &mut ctx.accounts.ack_receipt;
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        receipt.thread = thread.key();
+        receipt.message_index = message_index;
+        receipt.recipient = recipient;
+        receipt.content_hash = thread.last_content_hash;
+        receipt.signature = signature;
+        receipt.acknowledged_at = timestamp;
+        receipt.version = ACCOUNT_VERSION;
+
+        emit!(MessageAcknowledged {
+            thread: receipt.thread,
+            recipient,
+            message_index,
+            content_hash: receipt.content_hash,
+            timestamp,
+        });
+
+        msg!("Message {} acknowledged by {}", message_index, recipient);
+
+        Ok(())
+    }
+
+    /// Set how many messages in a row either party may send before a reply
+    /// Only the recipient (participant B) can change it; 0 disables the limit
+    pub fn set_max_consecutive(
+        ctx: Context<UpdateRecipientSettings>,
+        max_consecutive: u32,
+    ) -> Result<()> {
+        let thread = &mut ctx.accounts.message_thread;
+
+        thread.max_consecutive = max_consecutive;
+
+        emit!(MaxConsecutiveUpdated {
+            thread: thread.key(),
+            max_consecutive,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Max consecutive messages set to {}", max_consecutive);
+
+        Ok(())
+    }
+
     /// Send a broadcast message to all subscribers
+    /// `burn_after_seen` asks subscriber clients to delete it once seen;
+    /// `reply_to_index` quotes an earlier broadcast of the channel
     pub fn send_broadcast(
         ctx: Context<SendBroadcast>,
         message_index: u32,
-        _encrypted_content: Vec<u8>,
+        encrypted_content: Vec<u8>,
+        burn_after_seen: bool,
+        reply_to_index: Option<u32>,
+        attachment: Option<Attachment>,
     ) -> Result<()> {
+        check_reply_to(reply_to_index, message_index)?;
+        if let Some(attachment) = &attachment {
+            attachment.validate()?;
+        }
         let channel = &mut ctx.accounts.broadcast_channel;
-        
+
+        // The owner or an admin with broadcast permission may send
+        let is_admin = ctx
+            .accounts
+            .channel_admin
+            .as_ref()
+            .is_some_and(|admin| admin.has_permission(ChannelAdmin::BROADCAST));
+        let authorized = ctx.accounts.sender.key() == channel.owner || is_admin;
+        let now = Clock::get()?.unix_timestamp;
+        channel.record_broadcast(authorized, message_index, now)?;
+        if let Some(stats) = ctx.accounts.channel_stats.as_mut() {
+            stats.record(&channel.key(), now, 1, 0)?;
+        }
+
+        emit!(BroadcastSent {
+            channel: channel.key(),
+            sender: ctx.accounts.sender.key(),
+            message_index,
+            content_hash: hash(&encrypted_content).to_bytes(),
+            burn_after_seen,
+            timestamp: channel.last_broadcast_at,
+            reply_to_index,
+            attachment,
+            device: ctx
+                .accounts
+                .sender_device
+                .as_ref()
+                .map(|device| device.key()),
+        });
+
+        msg!("Broadcast {} sent", message_index);
+        msg!("Total broadcasts: {}", channel.message_count);
+
+        charge_message_fee(ctx.accounts, 1)?;
+
+        Ok(())
+    }
+
+    /// Cross-post the same broadcast to several channels owned by the sender
+    /// Channels are passed as writable remaining accounts, each with its own
+    /// expected sequential index in `message_indices`. The protocol fee is charged
+    /// once per unverified channel
+    pub fn send_multi_broadcast<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SendMultiBroadcast<'info>>,
+        message_indices: Vec<u32>,
+        encrypted_content: Vec<u8>,
+    ) -> Result<()> {
+        let sender = ctx.accounts.sender.key();
+
         require!(
-            ctx.accounts.sender.key() == channel.owner,
-            MessagingError::UnauthorizedSender
+            !ctx.remaining_accounts.is_empty(),
+            MessagingError::EmptyBatch
         );
-
         require!(
-            message_index == channel.message_count,
+            message_indices.len() == ctx.remaining_accounts.len(),
             MessagingError::InvalidMessageIndex
         );
 
-        channel.message_count += 1;
-        channel.last_broadcast_at = Clock::get()?.unix_timestamp;
+        let content_hash = hash(&encrypted_content).to_bytes();
+        let timestamp = Clock::get()?.unix_timestamp;
 
-        msg!("Broadcast {} sent", message_index);
-        msg!("Total broadcasts: {}", channel.message_count);
+        let mut charged_channels: u64 = 0;
+        let batch = ctx.remaining_accounts.iter().zip(message_indices);
+        for (channel_info, message_index) in batch {
+            let mut channel = Account::<BroadcastChannel>::try_from(channel_info)?;
+
+            require!(sender == channel.owner, MessagingError::NotChannelOwner);
+            let expected = Pubkey::create_program_address(
+                &[
+                    b"broadcast_channel",
+                    channel.creator.as_ref(),
+                    channel.channel_name.as_bytes(),
+                    &[channel.bump],
+                ],
+                ctx.program_id,
+            )
+            .map_err(|_| MessagingError::InvalidChannelAccount)?;
+            require_keys_eq!(
+                channel.key(),
+                expected,
+                MessagingError::InvalidChannelAccount
+            );
+            require!(
+                u64::from(message_index) == channel.message_count,
+                MessagingError::InvalidMessageIndex
+            );
+            require!(
+                channel.status == ChannelStatus::Active,
+                MessagingError::ChannelInactive
+            );
+            channel.check_broadcast_interval(timestamp)?;
+            if !channel.verified {
+                charged_channels += 1;
+            }
+
+            channel.message_count = channel
+                .message_count
+                .checked_add(1)
+                .ok_or(MessagingError::Overflow)?;
+            channel.last_broadcast_at = timestamp;
+
+            // Persist right away so a channel passed twice fails its index check
+            channel.exit(ctx.program_id)?;
+
+            emit!(BroadcastSent {
+                channel: channel.key(),
+                sender,
+                message_index,
+                content_hash,
+                burn_after_seen: false,
+                timestamp,
+                reply_to_index: None,
+                attachment: None,
+                device: None,
+            });
+
+            msg!(
+                "Broadcast {} sent to {}",
+                message_index,
+                channel.channel_name
+            );
+        }
+
+        // One fee per channel; verified channels broadcast fee-free
+        charge_message_fee(ctx.accounts, charged_channels)?;
 
         Ok(())
     }
@@ -88,254 +811,16165 @@ pub fn initialize_thread(
     pub fn initialize_channel(
         ctx: Context<InitializeChannel>,
         channel_name: String,
+        description: String,
+        metadata_uri: String,
+    ) -> Result<()> {
+        open_channel(
+            ctx.accounts,
+            ctx.bumps.broadcast_channel,
+            channel_name,
+            description,
+            metadata_uri,
+            Pubkey::default(),
+        )
+    }
+
+    /// Initialize a holder-only channel gated by a verified NFT collection
+    /// Subscribers present an NFT from `collection_mint` as remaining accounts
+    pub fn initialize_gated_channel(
+        ctx: Context<InitializeChannel>,
+        channel_name: String,
+        description: String,
+        metadata_uri: String,
+        collection_mint: Pubkey,
+    ) -> Result<()> {
+        open_channel(
+            ctx.accounts,
+            ctx.bumps.broadcast_channel,
+            channel_name,
+            description,
+            metadata_uri,
+            collection_mint,
+        )?;
+
+        msg!("Gated by collection: {}", collection_mint);
+
+        Ok(())
+    }
+
+    /// Update a channel's description and metadata URI (owner only)
+    pub fn set_channel_metadata(
+        ctx: Context<SetChannelMetadata>,
+        description: String,
+        metadata_uri: String,
     ) -> Result<()> {
-        let channel = &mut ctx.accounts.broadcast_channel;
-        
         require!(
-            channel_name.len() <= 32,
-            MessagingError::ChannelNameTooLong
+            description.len() <= 200 && metadata_uri.len() <= 200,
+            MessagingError::MetadataTooLong
         );
 
-        channel.owner = ctx.accounts.owner.key();
-        channel.channel_name = channel_name;
-        channel.message_count = 0;
-        channel.subscriber_count = 0;
-        channel.created_at = Clock::get()?.unix_timestamp;
-        channel.last_broadcast_at = 0;
+        let channel = &mut ctx.accounts.broadcast_channel;
+
+        channel.description = description;
+        channel.metadata_uri = metadata_uri;
+
+        emit!(ChannelMetadataUpdated {
+            channel: channel.key(),
+            description: channel.description.clone(),
+            metadata_uri: channel.metadata_uri.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Channel metadata updated: {}", channel.channel_name);
+        msg!("Metadata URI: {}", channel.metadata_uri);
+
+        Ok(())
+    }
+
+    /// Enable or disable the welcome DM thread opened for new subscribers (owner only)
+    pub fn set_welcome_thread(
+        ctx: Context<SetWelcomeThread>,
+        enabled: bool,
+        welcome_content_hash: [u8; 32],
+    ) -> Result<()> {
+        let channel = &mut ctx.accounts.broadcast_channel;
+
+        channel.auto_welcome_thread = enabled;
+        channel.welcome_content_hash = welcome_content_hash;
+
+        emit!(WelcomeThreadUpdated {
+            channel: channel.key(),
+            enabled,
+            welcome_content_hash,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
-        msg!("Broadcast channel initialized!");
-        msg!("Owner: {}", channel.owner);
-        msg!("Channel: {}", channel.channel_name);
+        msg!("Welcome thread for {}: {}", channel.channel_name, enabled);
 
         Ok(())
     }
 
     /// Subscribe to a broadcast channel
-    pub fn subscribe_channel(ctx: Context<SubscribeChannel>) -> Result<()> {
+    /// When the channel has a welcome thread enabled, a thread from the owner
+    /// to the subscriber is opened with the welcome message as message 0.
+    /// A private channel takes a `ChannelInvite`, plus its code for code invites.
+    pub fn subscribe_channel(
+        ctx: Context<SubscribeChannel>,
+        invite_code: Option<Vec<u8>>,
+    ) -> Result<()> {
+        let config = read_config(&ctx.accounts.config)?;
         let subscription = &mut ctx.accounts.subscription;
         let channel = &mut ctx.accounts.broadcast_channel;
+        let subscriber = ctx.accounts.subscriber.key();
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            channel.status == ChannelStatus::Active,
+            MessagingError::ChannelInactive
+        );
+        require!(
+            ctx.accounts.ban_entry.data_is_empty(),
+            MessagingError::SubscriberBanned
+        );
+
+        if channel.private {
+            let invite = ctx
+                .accounts
+                .channel_invite
+                .as_mut()
+                .ok_or(MessagingError::InviteRequired)?;
+            require_keys_eq!(invite.channel, channel.key(), MessagingError::InvalidInvite);
+            invite.redeem(&subscriber, invite_code.as_deref(), now)?;
+
+            emit!(ChannelInviteUsed {
+                channel: channel.key(),
+                invite: invite.key(),
+                subscriber,
+                uses: invite.uses,
+                timestamp: now,
+            });
+        }
+
+        if channel.gate_mint != Pubkey::default() {
+            let gate_account = ctx
+                .accounts
+                .gate_token_account
+                .as_ref()
+                .ok_or(MessagingError::InvalidGateAccount)?;
+            let balance = gate_token_balance(&subscriber, &channel.gate_mint, gate_account)?;
+            require!(
+                balance >= channel.gate_min_balance,
+                MessagingError::InsufficientGateBalance
+            );
+        }
+
+        // Collection-gated channels expect [nft_token_account, nft_metadata]
+        // as remaining accounts
+        if channel.gate_collection != Pubkey::default() {
+            let [token_account, metadata] = ctx.remaining_accounts else {
+                return err!(MessagingError::InvalidCollectionNft);
+            };
+            verify_collection_nft(
+                &subscriber,
+                &channel.gate_collection,
+                token_account,
+                metadata,
+            )?;
+        }
+
+        pay_subscription_fee(
+            channel,
+            ctx.accounts.channel_vault.as_mut(),
+            &ctx.accounts.subscriber,
+            &ctx.accounts.system_program,
+        )?;
         
-        subscription.subscriber = ctx.accounts.subscriber.key();
-        subscription.channel = channel.key();
-        subscription.subscribed_at = Clock::get()?.unix_timestamp;
-        subscription.last_read_index = 0;
+        subscription.set_inner(new_subscription(
+            subscriber,
+            channel,
+            Pubkey::default(),
+            ctx.bumps.subscription,
+            now,
+        )?);
+
+        channel.subscriber_count = channel
+            .subscriber_count
+            .checked_add(1)
+            .ok_or(MessagingError::Overflow)?;
+        if let Some(stats) = ctx.accounts.channel_stats.as_mut() {
+            stats.record(&channel.key(), now, 0, 1)?;
+        }
 
-        channel.subscriber_count += 1;
+        emit!(Subscribed {
+            channel: channel.key(),
+            subscription: subscription.key(),
+            subscriber,
+            subscriber_count: channel.subscriber_count,
+            timestamp: now,
+        });
 
         msg!("Subscribed to channel: {}", channel.channel_name);
         msg!("Total subscribers: {}", channel.subscriber_count);
 
+        if !channel.auto_welcome_thread {
+            return Ok(());
+        }
+
+        let welcome_thread = ctx
+            .accounts
+            .welcome_thread
+            .as_ref()
+            .ok_or(MessagingError::InvalidWelcomeThread)?;
+
+        // One welcome thread per (channel, subscriber): the channel key is the thread ID
+        let owner = channel.owner;
+        let thread_id = channel.key().to_bytes();
+        let (first, second) = sorted_participants(&owner, &subscriber);
+        let (expected_thread, bump) = Pubkey::find_program_address(
+            &[
+                b"message_thread",
+                first.as_ref(),
+                second.as_ref(),
+                thread_id.as_ref(),
+            ],
+            ctx.program_id,
+        );
+        require_keys_eq!(
+            welcome_thread.key(),
+            expected_thread,
+            MessagingError::InvalidWelcomeThread
+        );
+
+        // An existing welcome thread is left as is
+        if !welcome_thread.data_is_empty() {
+            return Ok(());
+        }
+
+        let signer_seeds: &[&[u8]] = &[
+            b"message_thread",
+            first.as_ref(),
+            second.as_ref(),
+            thread_id.as_ref(),
+            &[bump],
+        ];
+
+        let thread_info = welcome_thread.to_account_info();
+        create_pda_account(
+            ctx.accounts.subscriber.to_account_info(),
+            thread_info.clone(),
+            signer_seeds,
+            8 + MessageThread::INIT_SPACE,
+            ctx.accounts.system_program.to_account_info(),
+            ctx.program_id,
+        )?;
+
+        // Subscribing is consent, so the thread starts out accepted, with the
+        // welcome message already sent by the owner
+        let mut thread = new_thread(
+            owner,
+            subscriber,
+            thread_id,
+            (
+                ctx.accounts
+                    .owner_profile
+                    .as_ref()
+                    .map_or(0, |p| p.key_version),
+                ctx.accounts
+                    .subscriber_profile
+                    .as_ref()
+                    .map_or(0, |p| p.key_version),
+            ),
+            config.as_ref(),
+            bump,
+            now,
+        );
+        thread.status = ThreadStatus::Accepted;
+        thread.record_message(owner, 0, channel.welcome_content_hash, now)?;
+
+        thread.try_serialize(&mut &mut thread_info.try_borrow_mut_data()?[..])?;
+
+        emit!(WelcomeThreadOpened {
+            channel: channel.key(),
+            thread: expected_thread,
+            owner,
+            subscriber,
+            content_hash: channel.welcome_content_hash,
+            timestamp: now,
+        });
+
+        msg!("Welcome thread opened: {}", expected_thread);
+
+        // Index the thread in each participant's inbox that was passed
+        let payer = ctx.accounts.subscriber.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        for (inbox, pointer, participant) in [
+            (
+                ctx.accounts.owner_inbox.as_mut(),
+                ctx.accounts.owner_pointer.as_ref(),
+                owner,
+            ),
+            (
+                ctx.accounts.subscriber_inbox.as_mut(),
+                ctx.accounts.subscriber_pointer.as_ref(),
+                subscriber,
+            ),
+        ] {
+            if let Some(inbox) = inbox {
+                let pointer = pointer.ok_or(MessagingError::InvalidThreadPointer)?;
+                register_thread_pointer(
+                    inbox,
+                    pointer,
+                    participant,
+                    expected_thread,
+                    payer.clone(),
+                    system_program.clone(),
+                    ctx.program_id,
+                    now,
+                )?;
+            }
+        }
+
         Ok(())
     }
 
-    /// Close a message thread and refund rent
-    pub fn close_thread(ctx: Context<CloseThread>) -> Result<()> {
-        let thread = &ctx.accounts.message_thread;
+    /// Overwrite the cached subscriber count with a verified recount (owner only)
+    /// Every live subscription must be passed as a remaining account, sorted by
+    /// address; expired paid subscriptions are passed over and not counted
+    pub fn recount_subscribers<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RecountSubscribers<'info>>,
+    ) -> Result<()> {
+        let channel_key = ctx.accounts.broadcast_channel.key();
+        let created_at = ctx.accounts.broadcast_channel.created_at;
+        let now = Clock::get()?.unix_timestamp;
+        let mut counted: u64 = 0;
+        let mut last_key: Option<Pubkey> = None;
 
-        msg!("Closing message thread");
-        msg!("Messages exchanged: {}", thread.message_count);
-        msg!("Refunding rent to: {}", ctx.accounts.recipient.key());
+        for subscription_info in ctx.remaining_accounts.iter() {
+            // Strictly increasing addresses rule out repeats without a lookup
+            require!(
+                last_key.is_none_or(|last| last < *subscription_info.key),
+                MessagingError::DuplicateAccount
+            );
+            last_key = Some(*subscription_info.key);
+
+            let subscription = Account::<ChannelSubscription>::try_from(subscription_info)?;
+
+            // Foreign or stale subscriptions would inflate the count
+            require!(
+                subscription.belongs_to(&channel_key, created_at),
+                MessagingError::SubscriptionMismatch
+            );
+            if subscription.is_active(now) {
+                counted += 1;
+            }
+        }
+
+        let channel = &mut ctx.accounts.broadcast_channel;
+        let previous = channel.subscriber_count;
+        channel.subscriber_count = counted;
+
+        emit!(SubscribersRecounted {
+            channel: channel.key(),
+            previous_count: previous,
+            subscriber_count: channel.subscriber_count,
+            timestamp: now,
+        });
+
+        msg!("Recounted subscribers for {}", channel.channel_name);
+        msg!("Subscribers: {} -> {}", previous, channel.subscriber_count);
 
         Ok(())
     }
 
-    /// Close a broadcast channel
-    pub fn close_channel(ctx: Context<CloseChannel>) -> Result<()> {
-        let channel = &ctx.accounts.broadcast_channel;
+    /// Tip a channel owner in lamports as a subscriber
+    pub fn tip_channel(ctx: Context<TipChannel>, amount: u64) -> Result<()> {
+        require!(amount > 0, MessagingError::InvalidAmount);
 
-        msg!("Closing broadcast channel: {}", channel.channel_name);
-        msg!("Total broadcasts: {}", channel.message_count);
-        msg!("Subscribers: {}", channel.subscriber_count);
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.subscriber.to_account_info(),
+                    to: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let channel = &mut ctx.accounts.broadcast_channel;
+        let subscription = &mut ctx.accounts.subscription;
+
+        channel.total_tips = channel
+            .total_tips
+            .checked_add(amount)
+            .ok_or(MessagingError::Overflow)?;
+        subscription.tipped_amount = subscription
+            .tipped_amount
+            .checked_add(amount)
+            .ok_or(MessagingError::Overflow)?;
+
+        emit!(ChannelTipped {
+            channel: channel.key(),
+            subscriber: subscription.subscriber,
+            amount,
+            total_tips: channel.total_tips,
+            tipped_amount: subscription.tipped_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Channel {} tipped {} lamports",
+            channel.channel_name,
+            amount
+        );
+        msg!("Total tips: {}", channel.total_tips);
 
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-#[instruction(thread_id: [u8; 32])]
-pub struct InitializeThread<'info> {
-    #[account(
-        init,
-        payer = participant_a,
-        space = 8 + MessageThread::INIT_SPACE,
-        seeds = [
-            b"message_thread",
+    /// Confirm a broadcast reached the subscriber's client
+    /// Each subscriber counts once per broadcast via a receipt marker PDA; an expired
+    /// paid subscription cannot confirm
+    pub fn confirm_broadcast_delivery(
+        ctx: Context<ConfirmBroadcastDelivery>,
+        message_index: u32,
+    ) -> Result<()> {
+        let channel = &ctx.accounts.broadcast_channel;
+
+        require!(
+            u64::from(message_index) < channel.message_count,
+            MessagingError::InvalidMessageIndex
+        );
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.subscription.is_active(timestamp),
+            MessagingError::SubscriptionInactive
+        );
+        let delivery = &mut ctx.accounts.broadcast_delivery;
+
+        delivery.channel = channel.key();
+        delivery.message_index = message_index;
+        delivery.delivery_count = delivery
+            .delivery_count
+            .checked_add(1)
+            .ok_or(MessagingError::Overflow)?;
+        delivery.version = ACCOUNT_VERSION;
+
+        let receipt = &mut ctx.accounts.delivery_receipt;
+
+        receipt.subscriber = ctx.accounts.subscriber.key();
+        receipt.delivery = delivery.key();
+        receipt.confirmed_at = timestamp;
+        receipt.version = ACCOUNT_VERSION;
+
+        emit!(BroadcastDelivered {
+            channel: channel.key(),
+            subscriber: receipt.subscriber,
+            message_index,
+            delivery_count: delivery.delivery_count,
+            timestamp,
+        });
+
+        msg!(
+            "Broadcast {} delivered to {}",
+            message_index,
+            receipt.subscriber
+        );
+        msg!("Total deliveries: {}", delivery.delivery_count);
+
+        Ok(())
+    }
+
+    /// Record that a subscriber has seen a burn-after-seen broadcast,
+    /// signalling their clients to delete it
+    pub fn burn_broadcast_for_self(
+        ctx: Context<BurnBroadcastForSelf>,
+        message_index: u32,
+    ) -> Result<()> {
+        let channel = &ctx.accounts.broadcast_channel;
+
+        require!(
+            u64::from(message_index) < channel.message_count,
+            MessagingError::InvalidMessageIndex
+        );
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.subscription.is_active(timestamp),
+            MessagingError::SubscriptionInactive
+        );
+        let burn = &mut ctx.accounts.broadcast_burn;
+
+        burn.channel = channel.key();
+        burn.message_index = message_index;
+        burn.subscriber = ctx.accounts.subscriber.key();
+        burn.burned_at = timestamp;
+        burn.version = ACCOUNT_VERSION;
+
+        emit!(BroadcastBurned {
+            channel: burn.channel,
+            subscriber: burn.subscriber,
+            message_index,
+            timestamp,
+        });
+
+        msg!("Broadcast {} burned for {}", message_index, burn.subscriber);
+
+        Ok(())
+    }
+
+    /// Close a message thread and refund its rent to participant A, who paid it
+    /// Either participant may close with the other co-signing, or alone with a
+    /// close request that outlived its veto window (see `request_thread_close`).
+    pub fn close_thread(ctx: Context<CloseThread>) -> Result<()> {
+        let thread = &ctx.accounts.message_thread;
+        let closer = ctx.accounts.closer.key();
+        let now = Clock::get()?.unix_timestamp;
+
+        // Either both participants sign, or the closer's own request has
+        // outlived its veto window
+        let other = thread.other_participant(&closer);
+        let consented = ctx
+            .accounts
+            .counterparty
+            .as_ref()
+            .is_some_and(|c| c.key() == other);
+        if !consented {
+            let request = ctx
+                .accounts
+                .close_request
+                .as_ref()
+                .ok_or(MessagingError::ThreadCloseNotApproved)?;
+            require!(
+                now >= request.executable_after,
+                MessagingError::ThreadCloseTimelocked
+            );
+        }
+
+        // Once the recipient has decided, deposits must be settled first so
+        // closing the thread cannot change who they are paid to
+        if thread.status != ThreadStatus::Pending {
+            require!(
+                ctx.accounts.thread_vault.data_is_empty() && ctx.accounts.dm_escrow.data_is_empty(),
+                MessagingError::DepositUnsettled
+            );
+        }
+
+        emit!(ThreadClosed {
+            thread: thread.key(),
+            closed_by: closer,
+            recipient: thread.participant_a,
+            message_count: thread.message_count,
+            timestamp: now,
+        });
+
+        msg!("Closing message thread");
+        msg!("Messages exchanged: {}", thread.message_count);
+        msg!("Refunding rent to: {}", thread.participant_a);
+
+        for pointer in [&ctx.accounts.pointer_a, &ctx.accounts.pointer_b]
+            .into_iter()
+            .flatten()
+        {
+            emit!(ThreadPointerRemoved {
+                owner: pointer.owner,
+                thread: thread.key(),
+                pointer: pointer.key(),
+                index: pointer.index,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Close a broadcast channel in two steps
+    /// The first call moves it to `Closing`: no more subscribes or broadcasts, while
+    /// subscribers unsubscribe to reclaim their rent. A second call closes it once
+    /// everyone has left or `CHANNEL_CLOSING_GRACE_SECONDS` have passed; any
+    /// subscriptions left can then be closed with `close_orphaned_subscription`.
+    pub fn close_channel(ctx: Context<CloseChannel>) -> Result<()> {
+        let channel = &mut ctx.accounts.broadcast_channel;
+        let now = Clock::get()?.unix_timestamp;
+
+        if channel.status == ChannelStatus::Active {
+            channel.status = ChannelStatus::Closing;
+            channel.closing_at = now;
+
+            emit!(ChannelClosing {
+                channel: channel.key(),
+                owner: channel.owner,
+                subscriber_count: channel.subscriber_count,
+                closes_after: now + CHANNEL_CLOSING_GRACE_SECONDS,
+                timestamp: now,
+            });
+
+            msg!("Broadcast channel {} is closing", channel.channel_name);
+            msg!("Subscribers: {}", channel.subscriber_count);
+
+            return Ok(());
+        }
+
+        require!(
+            channel.subscriber_count == 0
+                || now >= channel.closing_at + CHANNEL_CLOSING_GRACE_SECONDS,
+            MessagingError::ChannelClosingPending
+        );
+
+        emit!(ChannelClosed {
+            channel: channel.key(),
+            owner: channel.owner,
+            recipient: ctx.accounts.recipient.key(),
+            message_count: channel.message_count,
+            subscriber_count: channel.subscriber_count,
+            timestamp: now,
+        });
+
+        msg!("Closing broadcast channel: {}", channel.channel_name);
+        msg!("Total broadcasts: {}", channel.message_count);
+        msg!("Subscribers: {}", channel.subscriber_count);
+
+        ctx.accounts
+            .broadcast_channel
+            .close(ctx.accounts.recipient.to_account_info())
+    }
+
+    /// Set the caller's recurring quiet hours (minutes from midnight UTC)
+    /// A window with start > end wraps around midnight
+    pub fn set_quiet_hours(
+        ctx: Context<SetQuietHours>,
+        start_minute: u16,
+        end_minute: u16,
+    ) -> Result<()> {
+        require!(
+            start_minute < MINUTES_PER_DAY && end_minute < MINUTES_PER_DAY,
+            MessagingError::InvalidQuietHours
+        );
+        require!(
+            start_minute != end_minute,
+            MessagingError::InvalidQuietHours
+        );
+
+        let quiet_hours = &mut ctx.accounts.quiet_hours;
+
+        quiet_hours.user = ctx.accounts.user.key();
+        quiet_hours.start_minute = start_minute;
+        quiet_hours.end_minute = end_minute;
+        quiet_hours.updated_at = Clock::get()?.unix_timestamp;
+        quiet_hours.version = ACCOUNT_VERSION;
+
+        emit!(QuietHoursSet {
+            user: quiet_hours.user,
+            start_minute,
+            end_minute,
+            timestamp: quiet_hours.updated_at,
+        });
+
+        msg!("Quiet hours set for {}", quiet_hours.user);
+        msg!("Window: {} -> {} (UTC minutes)", start_minute, end_minute);
+
+        Ok(())
+    }
+
+    /// Set how long a thread may sit idle before anyone can archive it for the
+    /// signer; zero turns automatic archival off
+    pub fn set_auto_archive(ctx: Context<SetAutoArchive>, auto_archive_after: i64) -> Result<()> {
+        require!(
+            auto_archive_after >= 0,
+            MessagingError::InvalidAutoArchivePeriod
+        );
+
+        let preference = &mut ctx.accounts.archive_preference;
+
+        preference.user = ctx.accounts.user.key();
+        preference.auto_archive_after = auto_archive_after;
+        preference.updated_at = Clock::get()?.unix_timestamp;
+        preference.version = ACCOUNT_VERSION;
+
+        msg!(
+            "Auto-archive for {} set to {}s",
+            preference.user,
+            auto_archive_after
+        );
+
+        Ok(())
+    }
+
+    /// Mute a sender across every thread (clients suppress their notifications)
+    pub fn mute_sender_globally(ctx: Context<MuteSenderGlobally>) -> Result<()> {
+        let mute = &mut ctx.accounts.global_sender_mute;
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        mute.user = ctx.accounts.user.key();
+        mute.muted_sender = ctx.accounts.muted_sender.key();
+        mute.muted_at = timestamp;
+        mute.version = ACCOUNT_VERSION;
+
+        emit!(SenderMutedGlobally {
+            user: mute.user,
+            muted_sender: mute.muted_sender,
+            timestamp,
+        });
+
+        msg!("{} muted {} globally", mute.user, mute.muted_sender);
+
+        Ok(())
+    }
+
+    /// Unmute a globally muted sender and refund the mute's rent
+    pub fn unmute_sender_globally(ctx: Context<UnmuteSenderGlobally>) -> Result<()> {
+        let mute = &ctx.accounts.global_sender_mute;
+
+        emit!(SenderUnmutedGlobally {
+            user: mute.user,
+            muted_sender: mute.muted_sender,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("{} unmuted {} globally", mute.user, mute.muted_sender);
+
+        Ok(())
+    }
+
+    /// Create a folder for grouping the caller's threads and channels
+    pub fn create_folder(ctx: Context<CreateFolder>, folder_id: u32, name: String) -> Result<()> {
+        require!(name.len() <= 32, MessagingError::FolderNameTooLong);
+
+        let folder = &mut ctx.accounts.folder;
+
+        folder.user = ctx.accounts.user.key();
+        folder.folder_id = folder_id;
+        folder.name = name;
+        folder.members = Vec::new();
+        folder.created_at = Clock::get()?.unix_timestamp;
+        folder.version = ACCOUNT_VERSION;
+
+        emit!(FolderCreated {
+            folder: folder.key(),
+            user: folder.user,
+            folder_id,
+            name: folder.name.clone(),
+            timestamp: folder.created_at,
+        });
+
+        msg!("Folder created: {}", folder.name);
+
+        Ok(())
+    }
+
+    /// Add a thread or channel to one of the caller's folders
+    pub fn add_to_folder(ctx: Context<UpdateFolder>, member: Pubkey) -> Result<()> {
+        let folder = &mut ctx.accounts.folder;
+
+        require!(
+            !folder.members.contains(&member),
+            MessagingError::AlreadyInFolder
+        );
+        require!(
+            folder.members.len() < Folder::MAX_MEMBERS,
+            MessagingError::FolderFull
+        );
+
+        folder.members.push(member);
+
+        emit!(FolderMemberAdded {
+            folder: folder.key(),
+            user: folder.user,
+            member,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Added {} to folder {}", member, folder.name);
+        msg!("Folder members: {}", folder.members.len());
+
+        Ok(())
+    }
+
+    /// Remove a thread or channel from one of the caller's folders
+    pub fn remove_from_folder(ctx: Context<UpdateFolder>, member: Pubkey) -> Result<()> {
+        let folder = &mut ctx.accounts.folder;
+
+        let position = folder
+            .members
+            .iter()
+            .position(|existing| *existing == member)
+            .ok_or(MessagingError::NotInFolder)?;
+        folder.members.remove(position);
+
+        emit!(FolderMemberRemoved {
+            folder: folder.key(),
+            user: folder.user,
+            member,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Removed {} from folder {}", member, folder.name);
+        msg!("Folder members: {}", folder.members.len());
+
+        Ok(())
+    }
+
+    /// Start a contact verification handshake by committing to a code and a
+    /// random salt shared with `peer` out of band, see `verification_commitment`
+    pub fn verify_contact(ctx: Context<VerifyContact>, secret_hash: [u8; 32]) -> Result<()> {
+        let initiator = ctx.accounts.initiator.key();
+        let peer = ctx.accounts.peer.key();
+
+        require!(initiator != peer, MessagingError::InvalidContact);
+        require!(
+            secret_hash != [0u8; 32],
+            MessagingError::VerificationMismatch
+        );
+
+        let contact = &mut ctx.accounts.verified_contact;
+
+        contact.initiator = initiator;
+        contact.peer = peer;
+        contact.initiator_hash = secret_hash;
+        contact.peer_hash = [0u8; 32];
+        contact.verified = false;
+        contact.initiated_at = Clock::get()?.unix_timestamp;
+        contact.verified_at = 0;
+        contact.version = ACCOUNT_VERSION;
+
+        emit!(ContactVerificationStarted {
+            verified_contact: contact.key(),
+            initiator,
+            peer,
+            timestamp: contact.initiated_at,
+        });
+
+        msg!("Contact verification started by {}", initiator);
+        msg!("Peer: {}", peer);
+
+        Ok(())
+    }
+
+    /// Complete a contact verification handshake as the peer
+    /// The peer computes its own commitment from the code and salt it received;
+    /// only the commitments are compared, so the code never appears on-chain
+    pub fn confirm_verification(
+        ctx: Context<ConfirmVerification>,
+        commitment: [u8; 32],
+    ) -> Result<()> {
+        let contact = &mut ctx.accounts.verified_contact;
+
+        require!(!contact.verified, MessagingError::AlreadyVerified);
+        require!(
+            commitment == contact.initiator_hash,
+            MessagingError::VerificationMismatch
+        );
+
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        contact.peer_hash = commitment;
+        contact.verified = true;
+        contact.verified_at = timestamp;
+
+        emit!(ContactVerified {
+            verified_contact: contact.key(),
+            initiator: contact.initiator,
+            peer: contact.peer,
+            timestamp,
+        });
+
+        msg!(
+            "Contact verified: {} <-> {}",
+            contact.initiator,
+            contact.peer
+        );
+
+        Ok(())
+    }
+
+    /// Set a personal label used to group subscriptions (max 16 bytes of UTF-8)
+    /// An empty label clears it
+    pub fn set_subscription_label(ctx: Context<SetSubscriptionLabel>, label: String) -> Result<()> {
+        require!(label.len() <= 16, MessagingError::LabelTooLong);
+
+        let subscription = &mut ctx.accounts.subscription;
+        let mut padded = [0u8; 16];
+        padded[..label.len()].copy_from_slice(label.as_bytes());
+
+        subscription.label = padded;
+
+        emit!(SubscriptionLabelSet {
+            subscription: subscription.key(),
+            subscriber: subscription.subscriber,
+            label: padded,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Subscription label set: {}", label);
+
+        Ok(())
+    }
+
+    /// Export a subscriber's on-chain subscription data as an event
+    /// Read-only: nothing is mutated
+    pub fn export_subscription_data(ctx: Context<ExportSubscriptionData>) -> Result<()> {
+        let subscription = &ctx.accounts.subscription;
+
+        emit!(SubscriptionDataExported {
+            subscription: subscription.key(),
+            subscriber: subscription.subscriber,
+            channel: subscription.channel,
+            subscribed_at: subscription.subscribed_at,
+            last_read_index: subscription.last_read_index,
+            tipped_amount: subscription.tipped_amount,
+            label: subscription.label,
+            exported_at: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Subscription data exported for {}", subscription.subscriber);
+
+        Ok(())
+    }
+
+    /// Export a channel's aggregate stats for its owner as an event
+    /// Read-only: nothing is mutated
+    pub fn export_channel_stats(ctx: Context<ExportChannelStats>) -> Result<()> {
+        let channel = &ctx.accounts.broadcast_channel;
+
+        emit!(ChannelStatsExported {
+            channel: channel.key(),
+            owner: channel.owner,
+            channel_name: channel.channel_name.clone(),
+            message_count: channel.message_count,
+            subscriber_count: channel.subscriber_count,
+            created_at: channel.created_at,
+            last_broadcast_at: channel.last_broadcast_at,
+            total_tips: channel.total_tips,
+            exported_at: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Channel stats exported for {}", channel.channel_name);
+
+        Ok(())
+    }
+
+    /// View: number of broadcasts the subscriber has not read yet
+    /// Simulate and read the return data instead of recomputing off-chain
+    pub fn get_unread_count(ctx: Context<ViewSubscription>) -> Result<u64> {
+        let unread = ctx
+            .accounts
+            .broadcast_channel
+            .message_count
+            .saturating_sub(ctx.accounts.subscription.last_read_index);
+
+        msg!("Unread broadcasts: {}", unread);
+
+        Ok(unread)
+    }
+
+    /// View: whether the subscription is active
+    pub fn get_subscription_status(ctx: Context<ViewSubscription>) -> Result<bool> {
+        let subscription = &ctx.accounts.subscription;
+
+        // A subscription that belongs to this channel (enforced by the account
+        // constraint) is active until its paid period runs out
+        let now = Clock::get()?.unix_timestamp;
+        let active = subscription.is_active(now);
+
+        msg!("Subscription {} active: {}", subscription.key(), active);
+
+        Ok(active)
+    }
+
+    /// React to a message in a thread
+    /// One reaction per (message, reactor) is enforced by the PDA seeds.
+    /// `notify_sender` (default true) tells clients whether to push a notification;
+    /// it is cleared when the other participant has muted the thread
+    pub fn add_reaction(
+        ctx: Context<AddReaction>,
+        message_index: u32,
+        emoji: [u8; 8],
+        notify_sender: Option<bool>,
+    ) -> Result<()> {
+        let thread = &ctx.accounts.message_thread;
+        let reactor = ctx.accounts.reactor.key();
+
+        require!(
+            reactor == thread.participant_a || reactor == thread.participant_b,
+            MessagingError::UnauthorizedSender
+        );
+        require!(
+            u64::from(message_index) < thread.message_count,
+            MessagingError::InvalidMessageIndex
+        );
+
+        set_reaction(
+            &mut ctx.accounts.reaction,
+            thread,
+            reactor,
+            message_index,
+            emoji,
+            notify_sender.unwrap_or(true),
+        )
+    }
+
+    /// Remove a reaction and refund its rent to the reactor
+    pub fn remove_reaction(ctx: Context<RemoveReaction>) -> Result<()> {
+        emit_reaction_removed(&ctx.accounts.reaction)
+    }
+
+    /// Create a group thread; the creator is its first member and admin
+    pub fn create_group(ctx: Context<CreateGroup>, group_id: [u8; 32], name: String) -> Result<()> {
+        require!(name.len() <= 32, MessagingError::GroupNameTooLong);
+
+        let group = &mut ctx.accounts.group_thread;
+        let creator = ctx.accounts.creator.key();
+
+        group.creator = creator;
+        group.group_id = group_id;
+        group.name = name;
+        group.members = vec![creator];
+        group.message_count = 0;
+        group.created_at = Clock::get()?.unix_timestamp;
+        group.last_message_at = 0;
+        group.version = ACCOUNT_VERSION;
+
+        emit!(GroupCreated {
+            group: group.key(),
+            creator,
+            group_id,
+            name: group.name.clone(),
+            timestamp: group.created_at,
+        });
+
+        msg!("Group thread created: {}", group.name);
+        msg!("Creator: {}", creator);
+
+        Ok(())
+    }
+
+    /// Add a member to a group (creator only)
+    pub fn add_member(ctx: Context<AddMember>, member: Pubkey) -> Result<()> {
+        let group = &mut ctx.accounts.group_thread;
+
+        require!(!group.is_member(&member), MessagingError::AlreadyMember);
+        require!(
+            group.members.len() < GroupThread::MAX_MEMBERS,
+            MessagingError::GroupFull
+        );
+
+        group.members.push(member);
+
+        emit!(GroupMemberAdded {
+            group: group.key(),
+            member,
+            added_by: group.creator,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Added {} to group {}", member, group.name);
+        msg!("Group members: {}", group.members.len());
+
+        Ok(())
+    }
+
+    /// Remove a member from a group
+    /// The creator can remove anyone else; members can remove themselves
+    pub fn remove_member(ctx: Context<RemoveMember>, member: Pubkey) -> Result<()> {
+        let group = &mut ctx.accounts.group_thread;
+        let authority = ctx.accounts.authority.key();
+
+        require!(
+            authority == group.creator || authority == member,
+            MessagingError::UnauthorizedSender
+        );
+        require!(member != group.creator, MessagingError::CannotRemoveCreator);
+
+        let position = group
+            .members
+            .iter()
+            .position(|existing| *existing == member)
+            .ok_or(MessagingError::NotAMember)?;
+        group.members.remove(position);
+
+        emit!(GroupMemberRemoved {
+            group: group.key(),
+            member,
+            removed_by: authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Removed {} from group {}", member, group.name);
+        msg!("Group members: {}", group.members.len());
+
+        Ok(())
+    }
+
+    /// Send a message to a group thread
+    /// Like direct messages, the content lives in transaction data
+    pub fn send_group_message(
+        ctx: Context<SendGroupMessage>,
+        message_index: u32,
+        encrypted_content: Vec<u8>,
+    ) -> Result<()> {
+        let group = &mut ctx.accounts.group_thread;
+        let sender = ctx.accounts.sender.key();
+
+        require!(group.is_member(&sender), MessagingError::NotAMember);
+        require!(
+            message_index == group.message_count,
+            MessagingError::InvalidMessageIndex
+        );
+
+        group.message_count = group
+            .message_count
+            .checked_add(1)
+            .ok_or(MessagingError::Overflow)?;
+        group.last_message_at = Clock::get()?.unix_timestamp;
+
+        emit!(GroupMessageSent {
+            group: group.key(),
+            sender,
+            message_index,
+            content_hash: hash(&encrypted_content).to_bytes(),
+            timestamp: group.last_message_at,
+        });
+
+        msg!("Group message {} sent by {}", message_index, sender);
+
+        msg!("Group messages: {}", group.message_count);
+
+        Ok(())
+    }
+
+    /// Unsubscribe from a broadcast channel and refund the subscription rent
+    pub fn unsubscribe_channel(ctx: Context<UnsubscribeChannel>) -> Result<()> {
+        let subscription = &ctx.accounts.subscription;
+        let channel = &mut ctx.accounts.broadcast_channel;
+
+        let now = Clock::get()?.unix_timestamp;
+
+        // A recount leaves expired subscriptions out, so only active ones are subtracted
+        if subscription.is_active(now) {
+            channel.subscriber_count = channel
+                .subscriber_count
+                .checked_sub(1)
+                .ok_or(MessagingError::Overflow)?;
+        }
+        if let Some(stats) = ctx.accounts.channel_stats.as_mut() {
+            stats.record(&channel.key(), now, 0, -1)?;
+        }
+
+        emit!(Unsubscribed {
+            channel: channel.key(),
+            subscription: subscription.key(),
+            subscriber: subscription.subscriber,
+            subscriber_count: channel.subscriber_count,
+            timestamp: now,
+        });
+
+        msg!("Unsubscribed from channel: {}", channel.channel_name);
+        msg!("Total subscribers: {}", channel.subscriber_count);
+
+        Ok(())
+    }
+
+    /// Mark broadcasts up to `read_index` as read on a subscription
+    /// `read_index` is the number of broadcasts read, so unread = message_count - read_index
+    pub fn mark_channel_read(ctx: Context<MarkChannelRead>, read_index: u64) -> Result<()> {
+        let channel = &ctx.accounts.broadcast_channel;
+        require!(
+            read_index <= channel.message_count,
+            MessagingError::InvalidMessageIndex
+        );
+
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.last_read_index = read_index;
+
+        emit!(ChannelRead {
+            channel: channel.key(),
+            subscription: subscription.key(),
+            subscriber: subscription.subscriber,
+            last_read_index: read_index,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Channel {} read up to {}", channel.channel_name, read_index);
+
+        Ok(())
+    }
+
+    /// Publish a read receipt: the signer has read the first `read_index` messages
+    /// The `ReadReceipt` event lets the other side mark exactly those messages read
+    pub fn mark_thread_read(ctx: Context<MarkThreadRead>, read_index: u64) -> Result<()> {
+        let thread = &mut ctx.accounts.message_thread;
+        let reader = ctx.accounts.reader.key();
+
+        require!(
+            read_index <= thread.message_count,
+            MessagingError::InvalidMessageIndex
+        );
+
+        if reader == thread.participant_a {
+            thread.last_read_by_a = read_index;
+        } else if reader == thread.participant_b {
+            thread.last_read_by_b = read_index;
+        } else {
+            return err!(MessagingError::UnauthorizedSender);
+        }
+
+        emit!(ReadReceipt {
+            thread: thread.key(),
+            reader,
+            read_index,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Thread read up to {} by {}", read_index, reader);
+
+        Ok(())
+    }
+
+    /// Mark the first `seen_index` messages as seen on screen, short of read
+    /// The seen pointer never moves back and never trails the signer's read receipt,
+    /// so clients order the states as delivered, seen, read
+    pub fn mark_seen(ctx: Context<MarkSeen>, seen_index: u64) -> Result<()> {
+        let thread = &ctx.accounts.message_thread;
+        let participant = ctx.accounts.participant.key();
+
+        let last_read = if participant == thread.participant_a {
+            thread.last_read_by_a
+        } else if participant == thread.participant_b {
+            thread.last_read_by_b
+        } else {
+            return err!(MessagingError::UnauthorizedSender);
+        };
+
+        let pointer = &mut ctx.accounts.seen_pointer;
+        require!(
+            seen_index <= thread.message_count
+                && seen_index >= last_read
+                && seen_index >= pointer.seen_index,
+            MessagingError::InvalidMessageIndex
+        );
+
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        pointer.thread = thread.key();
+        pointer.participant = participant;
+        pointer.seen_index = seen_index;
+        pointer.seen_at = timestamp;
+        pointer.bump = ctx.bumps.seen_pointer;
+        pointer.version = ACCOUNT_VERSION;
+
+        emit!(MessagesSeen {
+            thread: thread.key(),
+            participant,
+            seen_index,
+            timestamp,
+        });
+
+        msg!("Thread seen up to {} by {}", seen_index, participant);
+
+        Ok(())
+    }
+
+    /// Block a user: neither side can open or send in threads between the two
+    pub fn block_user(ctx: Context<BlockUser>) -> Result<()> {
+        let entry = &mut ctx.accounts.block_entry;
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        entry.blocker = ctx.accounts.blocker.key();
+        entry.blocked = ctx.accounts.blocked.key();
+        entry.blocked_at = timestamp;
+        entry.version = ACCOUNT_VERSION;
+
+        emit!(UserBlocked {
+            blocker: entry.blocker,
+            blocked: entry.blocked,
+            timestamp,
+        });
+
+        msg!("{} blocked {}", entry.blocker, entry.blocked);
+
+        Ok(())
+    }
+
+    /// Remove a block and refund the entry's rent
+    pub fn unblock_user(ctx: Context<UnblockUser>) -> Result<()> {
+        let entry = &ctx.accounts.block_entry;
+
+        emit!(UserUnblocked {
+            blocker: entry.blocker,
+            blocked: entry.blocked,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("{} unblocked {}", entry.blocker, entry.blocked);
+
+        Ok(())
+    }
+
+    /// Publish a profile with the X25519 key peers use to encrypt messages to the owner
+    pub fn register_profile(
+        ctx: Context<RegisterProfile>,
+        encryption_key: [u8; 32],
+        display_name: String,
+        avatar_uri: String,
+    ) -> Result<()> {
+        require!(display_name.len() <= 32, MessagingError::DisplayNameTooLong);
+        require!(avatar_uri.len() <= 200, MessagingError::MetadataTooLong);
+
+        let profile = &mut ctx.accounts.user_profile;
+        let now = Clock::get()?.unix_timestamp;
+
+        profile.owner = ctx.accounts.owner.key();
+        profile.encryption_key = encryption_key;
+        profile.key_version = 1;
+        profile.display_name = display_name;
+        profile.avatar_uri = avatar_uri;
+        profile.created_at = now;
+        profile.updated_at = now;
+        profile.outreach_deposit_lamports = 0;
+        profile.version = ACCOUNT_VERSION;
+
+        emit!(ProfileRegistered {
+            profile: profile.key(),
+            owner: profile.owner,
+            encryption_key,
+            display_name: profile.display_name.clone(),
+            timestamp: now,
+        });
+
+        msg!("Profile registered for {}", profile.owner);
+
+        log_thread_key(
+            ctx.accounts.message_thread.as_ref(),
+            ctx.accounts.key_log.as_mut(),
+            ctx.accounts.owner.key(),
+            encryption_key,
+            now,
+        )?;
+
+        Ok(())
+    }
+
+    /// Update the display name and avatar of a profile
+    pub fn update_profile(
+        ctx: Context<UpdateProfile>,
+        display_name: String,
+        avatar_uri: String,
+    ) -> Result<()> {
+        require!(display_name.len() <= 32, MessagingError::DisplayNameTooLong);
+        require!(avatar_uri.len() <= 200, MessagingError::MetadataTooLong);
+
+        let profile = &mut ctx.accounts.user_profile;
+        let now = Clock::get()?.unix_timestamp;
+
+        profile.display_name = display_name;
+        profile.avatar_uri = avatar_uri;
+        profile.updated_at = now;
+
+        emit!(ProfileUpdated {
+            profile: profile.key(),
+            owner: profile.owner,
+            display_name: profile.display_name.clone(),
+            avatar_uri: profile.avatar_uri.clone(),
+            timestamp: now,
+        });
+
+        msg!("Profile updated for {}", profile.owner);
+
+        Ok(())
+    }
+
+    /// Replace the profile's encryption key and bump its version
+    /// Threads snapshot the version at creation so older messages stay decryptable.
+    /// Passing a thread and its `key_log` records the new key in that thread's log
+    pub fn rotate_encryption_key(
+        ctx: Context<RotateEncryptionKey>,
+        encryption_key: [u8; 32],
+    ) -> Result<()> {
+        let profile = &mut ctx.accounts.user_profile;
+        let now = Clock::get()?.unix_timestamp;
+
+        profile.encryption_key = encryption_key;
+        profile.key_version = profile
+            .key_version
+            .checked_add(1)
+            .ok_or(MessagingError::Overflow)?;
+        profile.updated_at = now;
+
+        emit!(EncryptionKeyRotated {
+            profile: profile.key(),
+            owner: profile.owner,
+            encryption_key,
+            key_version: profile.key_version,
+            timestamp: now,
+        });
+
+        msg!("Encryption key rotated to version {}", profile.key_version);
+
+        log_thread_key(
+            ctx.accounts.message_thread.as_ref(),
+            ctx.accounts.key_log.as_mut(),
+            ctx.accounts.owner.key(),
+            encryption_key,
+            now,
+        )?;
+
+        Ok(())
+    }
+
+    /// Send a message and keep a `MessageRecord` PDA so history survives RPC pruning
+    /// The content lives off-chain at `content_uri`; only its hash is stored
+    pub fn send_message_stored(
+        ctx: Context<SendMessageStored>,
+        message_index: u32,
+        content_hash: [u8; 32],
+        content_uri: String,
+        reply_to_index: Option<u32>,
+    ) -> Result<()> {
+        require!(content_uri.len() <= 200, MessagingError::MetadataTooLong);
+        check_reply_to(reply_to_index, message_index)?;
+        ensure_not_blocked(&ctx.accounts.block_by_a)?;
+        ensure_not_blocked(&ctx.accounts.block_by_b)?;
+
+        let thread = &mut ctx.accounts.message_thread;
+        let sender = ctx.accounts.sender.key();
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        thread.record_message(sender, message_index, content_hash, timestamp)?;
+
+        let record = &mut ctx.accounts.message_record;
+        record.thread = thread.key();
+        record.message_index = message_index;
+        record.sender = sender;
+        record.content_hash = content_hash;
+        record.content_uri = content_uri;
+        record.timestamp = timestamp;
+        record.expires_at = if thread.message_ttl_seconds > 0 {
+            timestamp
+                .checked_add(thread.message_ttl_seconds)
+                .ok_or(MessagingError::Overflow)?
+        } else {
+            0
+        };
+        record.version = ACCOUNT_VERSION;
+        record.reply_to_index = reply_to_index;
+
+        emit!(MessageStored {
+            thread: record.thread,
+            record: record.key(),
+            sender,
+            message_index,
+            content_hash,
+            content_uri: record.content_uri.clone(),
+            timestamp,
+            reply_to_index,
+        });
+
+        msg!("Message {} stored by {}", message_index, sender);
+        msg!("Thread messages: {}", thread.message_count);
+
+        charge_message_fee(ctx.accounts, 1)?;
+
+        Ok(())
+    }
+
+    /// Close a message record and refund its rent to the sender
+    pub fn close_message_record(ctx: Context<CloseMessageRecord>) -> Result<()> {
+        let record = &ctx.accounts.message_record;
+
+        emit!(MessageRecordClosed {
+            thread: record.thread,
+            record: record.key(),
+            message_index: record.message_index,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Message record {} closed", record.message_index);
+
+        Ok(())
+    }
+
+    /// Set the subscription price and period (owner only)
+    /// Creates the channel's fee vault on first use
+    pub fn set_subscription_fee(
+        ctx: Context<SetSubscriptionFee>,
+        fee_lamports: u64,
+        period_seconds: i64,
+    ) -> Result<()> {
+        require!(period_seconds >= 0, MessagingError::InvalidAmount);
+
+        let channel = &mut ctx.accounts.broadcast_channel;
+        let vault = &mut ctx.accounts.channel_vault;
+
+        vault.channel = channel.key();
+        vault.version = ACCOUNT_VERSION;
+        channel.subscription_fee_lamports = fee_lamports;
+        channel.subscription_period_seconds = period_seconds;
+
+        emit!(SubscriptionFeeSet {
+            channel: channel.key(),
+            fee_lamports,
+            period_seconds,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Subscription fee for {}: {} lamports",
+            channel.channel_name,
+            fee_lamports
+        );
+        msg!("Subscription period: {} seconds", period_seconds);
+
+        Ok(())
+    }
+
+    /// Pay for another period; renewing early extends from the current expiry
+    pub fn renew_subscription(ctx: Context<RenewSubscription>) -> Result<()> {
+        let channel = &ctx.accounts.broadcast_channel;
+        require!(
+            channel.subscription_period_seconds > 0,
+            MessagingError::SubscriptionDoesNotExpire
+        );
+
+        pay_subscription_fee(
+            channel,
+            Some(&mut ctx.accounts.channel_vault),
+            &ctx.accounts.subscriber,
+            &ctx.accounts.system_program,
+        )?;
+
+        let subscription = &mut ctx.accounts.subscription;
+        let now = Clock::get()?.unix_timestamp;
+
+        subscription.expires_at = subscription
+            .expires_at
+            .max(now)
+            .checked_add(channel.subscription_period_seconds)
+            .ok_or(MessagingError::Overflow)?;
+
+        emit!(SubscriptionRenewed {
+            channel: channel.key(),
+            subscription: subscription.key(),
+            subscriber: subscription.subscriber,
+            fee_lamports: channel.subscription_fee_lamports,
+            expires_at: subscription.expires_at,
+            timestamp: now,
+        });
+
+        msg!("Subscription renewed until {}", subscription.expires_at);
+
+        Ok(())
+    }
+
+    /// Deposit lamports into the subscription's wallet so `process_renewal` can
+    /// renew it without the subscriber signing
+    pub fn fund_subscription_wallet(
+        ctx: Context<FundSubscriptionWallet>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, MessagingError::InvalidAmount);
+
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.subscriber.to_account_info(),
+                    to: ctx.accounts.subscription_wallet.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let wallet = &mut ctx.accounts.subscription_wallet;
+        // Fresh wallet from `init_if_needed`
+        if wallet.subscription == Pubkey::default() {
+            wallet.subscription = ctx.accounts.subscription.key();
+            wallet.subscriber = ctx.accounts.subscriber.key();
+            wallet.version = ACCOUNT_VERSION;
+        }
+        wallet.total_deposited = wallet
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(MessagingError::Overflow)?;
+
+        emit!(SubscriptionWalletFunded {
+            wallet: wallet.key(),
+            subscription: wallet.subscription,
+            subscriber: wallet.subscriber,
+            amount,
+            total_deposited: wallet.total_deposited,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Subscription wallet funded with {} lamports", amount);
+
+        Ok(())
+    }
+
+    /// Stop auto-renewal, returning the wallet's whole balance to the subscriber
+    pub fn close_subscription_wallet(ctx: Context<CloseSubscriptionWallet>) -> Result<()> {
+        let wallet = &ctx.accounts.subscription_wallet;
+
+        emit!(SubscriptionWalletClosed {
+            wallet: wallet.key(),
+            subscription: wallet.subscription,
+            subscriber: wallet.subscriber,
+            refunded: wallet.to_account_info().lamports(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Subscription wallet closed for {}", wallet.subscriber);
+
+        Ok(())
+    }
+
+    /// Renew a subscription from its pre-funded wallet (permissionless)
+    /// Only allowed within `RENEWAL_WINDOW_SECONDS` of expiry; a subscription that
+    /// has already expired, or whose wallet can't cover the fee, is left to lapse
+    pub fn process_renewal(ctx: Context<ProcessRenewal>) -> Result<()> {
+        let channel = &ctx.accounts.broadcast_channel;
+        require!(
+            channel.subscription_period_seconds > 0,
+            MessagingError::SubscriptionDoesNotExpire
+        );
+        require!(
+            channel.status == ChannelStatus::Active,
+            MessagingError::ChannelInactive
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let expires_at = ctx.accounts.subscription.expires_at;
+        require!(now < expires_at, MessagingError::SubscriptionLapsed);
+        require!(
+            expires_at - now <= RENEWAL_WINDOW_SECONDS,
+            MessagingError::RenewalNotDue
+        );
+
+        let fee = channel.subscription_fee_lamports;
+        let wallet_info = ctx.accounts.subscription_wallet.to_account_info();
+        let rent_minimum = Rent::get()?.minimum_balance(wallet_info.data_len());
+        let available = wallet_info.lamports().saturating_sub(rent_minimum);
+        require!(fee <= available, MessagingError::InsufficientWalletBalance);
+
+        let vault = &mut ctx.accounts.channel_vault;
+        move_lamports(&wallet_info, &vault.to_account_info(), fee)?;
+        vault.total_collected = vault
+            .total_collected
+            .checked_add(fee)
+            .ok_or(MessagingError::Overflow)?;
+
+        let wallet = &mut ctx.accounts.subscription_wallet;
+        wallet.total_spent = wallet
+            .total_spent
+            .checked_add(fee)
+            .ok_or(MessagingError::Overflow)?;
+
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.expires_at = expires_at
+            .checked_add(channel.subscription_period_seconds)
+            .ok_or(MessagingError::Overflow)?;
+
+        emit!(SubscriptionRenewed {
+            channel: channel.key(),
+            subscription: subscription.key(),
+            subscriber: subscription.subscriber,
+            fee_lamports: fee,
+            expires_at: subscription.expires_at,
+            timestamp: now,
+        });
+
+        msg!(
+            "Subscription auto-renewed until {}",
+            subscription.expires_at
+        );
+
+        Ok(())
+    }
+
+    /// Pay the subscription fee for someone else and subscribe them
+    /// The recipient must not be subscribed already, nor banned; private and
+    /// token-gated channels can't be gifted, and no welcome thread is opened
+    pub fn gift_subscription(ctx: Context<GiftSubscription>) -> Result<()> {
+        let channel = &mut ctx.accounts.broadcast_channel;
+        let gifter = ctx.accounts.gifter.key();
+        let recipient = ctx.accounts.recipient.key();
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(recipient != gifter, MessagingError::CannotGiftSelf);
+        require!(
+            channel.status == ChannelStatus::Active,
+            MessagingError::ChannelInactive
+        );
+        require!(
+            !channel.private
+                && channel.gate_mint == Pubkey::default()
+                && channel.gate_collection == Pubkey::default(),
+            MessagingError::GiftNotAllowed
+        );
+        require!(
+            ctx.accounts.ban_entry.data_is_empty(),
+            MessagingError::SubscriberBanned
+        );
+
+        pay_subscription_fee(
+            channel,
+            ctx.accounts.channel_vault.as_mut(),
+            &ctx.accounts.gifter,
+            &ctx.accounts.system_program,
+        )?;
+
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.set_inner(new_subscription(
+            recipient,
+            channel,
+            gifter,
+            ctx.bumps.subscription,
+            now,
+        )?);
+
+        channel.subscriber_count = channel
+            .subscriber_count
+            .checked_add(1)
+            .ok_or(MessagingError::Overflow)?;
+
+        emit!(SubscriptionGifted {
+            channel: channel.key(),
+            subscription: subscription.key(),
+            gifter,
+            recipient,
+            fee_lamports: channel.subscription_fee_lamports,
+            expires_at: subscription.expires_at,
+            subscriber_count: channel.subscriber_count,
+            timestamp: now,
+        });
+
+        msg!(
+            "Subscription to {} gifted to {}",
+            channel.channel_name,
+            recipient
+        );
+        msg!("Total subscribers: {}", channel.subscriber_count);
+
+        Ok(())
+    }
+
+    /// Withdraw collected subscription fees to the owner
+    /// The vault keeps its rent-exempt minimum
+    pub fn withdraw_channel_fees(ctx: Context<WithdrawChannelFees>, amount: u64) -> Result<()> {
+        require!(amount > 0, MessagingError::InvalidAmount);
+
+        let vault_info = ctx.accounts.channel_vault.to_account_info();
+        let rent_minimum = Rent::get()?.minimum_balance(vault_info.data_len());
+        let available = vault_info.lamports().saturating_sub(rent_minimum);
+        require!(
+            amount <= available,
+            MessagingError::InsufficientVaultBalance
+        );
+
+        move_lamports(&vault_info, &ctx.accounts.owner.to_account_info(), amount)?;
+
+        let vault = &mut ctx.accounts.channel_vault;
+        vault.total_withdrawn = vault
+            .total_withdrawn
+            .checked_add(amount)
+            .ok_or(MessagingError::Overflow)?;
+
+        emit!(ChannelFeesWithdrawn {
+            channel: vault.channel,
+            owner: ctx.accounts.owner.key(),
+            amount,
+            total_withdrawn: vault.total_withdrawn,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Withdrew {} lamports of channel fees", amount);
+
+        Ok(())
+    }
+
+    /// Require subscribers to hold `min_balance` of `mint` (owner only)
+    /// Passing the default pubkey as the mint removes the gate
+    pub fn set_token_gate(
+        ctx: Context<UpdateChannelSettings>,
+        mint: Pubkey,
+        min_balance: u64,
+    ) -> Result<()> {
+        let channel = &mut ctx.accounts.broadcast_channel;
+
+        channel.gate_mint = mint;
+        channel.gate_min_balance = min_balance;
+
+        emit!(TokenGateSet {
+            channel: channel.key(),
+            mint,
+            min_balance,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Token gate for {}: {} of {}",
+            channel.channel_name,
+            min_balance,
+            mint
+        );
+
+        Ok(())
+    }
+
+    /// Re-check a subscriber against the channel's token gate (callable by anyone)
+    /// A subscriber below the threshold is removed and the rent refunded to them
+    pub fn verify_subscription(ctx: Context<VerifySubscription>) -> Result<()> {
+        let channel = &mut ctx.accounts.broadcast_channel;
+        let subscriber = ctx.accounts.subscriber.key();
+
+        if channel.gate_mint == Pubkey::default() {
+            msg!("Channel {} is not token-gated", channel.channel_name);
+            return Ok(());
+        }
+
+        let gate_account = &ctx.accounts.gate_token_account;
+        let balance = gate_token_balance(&subscriber, &channel.gate_mint, gate_account)?;
+        if balance >= channel.gate_min_balance {
+            msg!(
+                "Subscription {} still meets the gate",
+                ctx.accounts.subscription.key()
+            );
+            return Ok(());
+        }
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        if ctx.accounts.subscription.is_active(timestamp) {
+            channel.subscriber_count = channel
+                .subscriber_count
+                .checked_sub(1)
+                .ok_or(MessagingError::Overflow)?;
+        }
+
+        emit!(SubscriptionPurged {
+            channel: channel.key(),
+            subscription: ctx.accounts.subscription.key(),
+            subscriber,
+            balance,
+            subscriber_count: channel.subscriber_count,
+            timestamp,
+        });
+
+        msg!(
+            "Subscriber {} purged from {}",
+            subscriber,
+            channel.channel_name
+        );
+
+        ctx.accounts
+            .subscription
+            .close(ctx.accounts.subscriber.to_account_info())
+    }
+
+    /// Grant an account an admin role on a channel (owner only)
+    pub fn add_admin(ctx: Context<AddAdmin>, permissions: u8) -> Result<()> {
+        let admin = &mut ctx.accounts.channel_admin;
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        admin.channel = ctx.accounts.broadcast_channel.key();
+        admin.admin = ctx.accounts.admin.key();
+        admin.permissions = permissions;
+        admin.added_at = timestamp;
+        admin.version = ACCOUNT_VERSION;
+
+        emit!(AdminAdded {
+            channel: admin.channel,
+            admin: admin.admin,
+            permissions,
+            timestamp,
+        });
+
+        msg!(
+            "Admin {} added with permissions {:#04b}",
+            admin.admin,
+            permissions
+        );
+
+        Ok(())
+    }
+
+    /// Revoke an admin role and refund its rent to the owner
+    pub fn remove_admin(ctx: Context<RemoveAdmin>) -> Result<()> {
+        let admin = &ctx.accounts.channel_admin;
+
+        emit!(AdminRemoved {
+            channel: admin.channel,
+            admin: admin.admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Admin {} removed", admin.admin);
+
+        Ok(())
+    }
+
+    /// Replace an admin's permission bits (owner only)
+    pub fn set_admin_permissions(ctx: Context<SetAdminPermissions>, permissions: u8) -> Result<()> {
+        let admin = &mut ctx.accounts.channel_admin;
+
+        admin.permissions = permissions;
+
+        emit!(AdminPermissionsUpdated {
+            channel: admin.channel,
+            admin: admin.admin,
+            permissions,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Admin {} permissions set to {:#04b}",
+            admin.admin,
+            permissions
+        );
+
+        Ok(())
+    }
+
+    /// Propose a new channel owner (owner only); the default pubkey cancels
+    /// The channel address stays seeded by `creator`, so subscriptions are unaffected
+    pub fn propose_channel_transfer(
+        ctx: Context<ProposeChannelTransfer>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        let channel = &mut ctx.accounts.broadcast_channel;
+
+        channel.pending_owner = new_owner;
+
+        emit!(ChannelTransferProposed {
+            channel: channel.key(),
+            owner: channel.owner,
+            pending_owner: new_owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Channel {} transfer proposed to {}",
+            channel.channel_name,
+            new_owner
+        );
+
+        Ok(())
+    }
+
+    /// Accept a proposed transfer and become the channel owner
+    pub fn accept_channel_transfer(ctx: Context<AcceptChannelTransfer>) -> Result<()> {
+        let channel = &mut ctx.accounts.broadcast_channel;
+        let previous_owner = channel.owner;
+
+        channel.owner = ctx.accounts.new_owner.key();
+        channel.pending_owner = Pubkey::default();
+        // A badge vouches for the owner, so it does not carry over. The suspicious
+        // flag stays, so a scam channel cannot shed it by changing hands.
+        channel.verified = false;
+        channel.verified_at = 0;
+
+        emit!(ChannelTransferred {
+            channel: channel.key(),
+            previous_owner,
+            new_owner: channel.owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Channel {} now owned by {}",
+            channel.channel_name,
+            channel.owner
+        );
+
+        Ok(())
+    }
+
+    /// Upgrade a channel created by an older program version to the current layout
+    /// New fields start zeroed; `creator` and `bump` are backfilled
+    pub fn migrate_channel(ctx: Context<MigrateChannel>) -> Result<()> {
+        let channel_info = ctx.accounts.broadcast_channel.to_account_info();
+        // Channels are variable-sized, so the layout is recognized by parsing it
+        let current = is_current_channel(&channel_info, ctx.program_id);
+        // v2+ layouts share the v2 prefix, which ends with the stored version
+        let legacy = if current {
+            None
+        } else {
+            let data = channel_info.try_borrow_data()?;
+            channel_v2_len(&data)
+                .map(|end| (end, data[end - 1]))
+                .filter(|&(_, version)| (2..ACCOUNT_VERSION).contains(&version))
+        };
+        let widen = !current && legacy.is_none();
+        let mut new_len = 8 + BroadcastChannel::INIT_SPACE;
+        if widen {
+            new_len = new_len.max(channel_info.data_len() + 8 + CHANNEL_TAIL_LEN);
+        } else if let Some((end, _)) = legacy {
+            new_len = new_len.max(end + CHANNEL_TAIL_LEN);
+        }
+        let old_len = grow_for_migration(
+            &channel_info,
+            BroadcastChannel::DISCRIMINATOR,
+            &[8],
+            &ctx.accounts.owner,
+            new_len,
+            &ctx.accounts.system_program,
+            MessagingError::InvalidChannelAccount,
+        )?;
+
+        if widen {
+            // v1 -> v2: message_count and subscriber_count follow the channel name
+            let name_len = {
+                let data = channel_info.try_borrow_data()?;
+                let len_bytes: [u8; 4] = data
+                    .get(40..44)
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or(MessagingError::InvalidChannelAccount)?;
+                u32::from_le_bytes(len_bytes) as usize
+            };
+            let counts_at = 44 + name_len;
+            widen_u32_fields(&channel_info, old_len, &[counts_at + 4, counts_at + 8])?;
+        }
+        if !current {
+            // Fields added after the stored version start cleared, whatever trailed them
+            let from = legacy.map_or(1, |(_, version)| version);
+            let mut data = channel_info.try_borrow_mut_data()?;
+            let end = channel_v2_len(&data).ok_or(MessagingError::InvalidChannelAccount)?;
+            data[end + channel_tail_len(from)..end + CHANNEL_TAIL_LEN].fill(0);
+        }
+
+        let mut channel =
+            BroadcastChannel::try_deserialize(&mut &channel_info.try_borrow_data()?[..])?;
+
+        if channel.creator == Pubkey::default() {
+            channel.creator = channel.owner;
+        }
+        if channel.bump == 0 {
+            let (_, bump) = Pubkey::find_program_address(
+                &[
+                    b"broadcast_channel",
+                    channel.creator.as_ref(),
+                    channel.channel_name.as_bytes(),
+                ],
+                ctx.program_id,
+            );
+            channel.bump = bump;
+        }
+        channel.version = ACCOUNT_VERSION;
+        channel.try_serialize(&mut &mut channel_info.try_borrow_mut_data()?[..])?;
+
+        emit!(ChannelMigrated {
+            channel: channel_info.key(),
+            creator: channel.creator,
+            old_len: old_len as u32,
+            new_len: new_len as u32,
+            version: ACCOUNT_VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Channel {} migrated: {} -> {} bytes",
+            channel.channel_name,
+            old_len,
+            new_len
+        );
+
+        Ok(())
+    }
+
+    /// Set the channel's discovery metadata (owner only)
+    /// The account is reallocated to fit, so channels only pay rent for what they use
+    pub fn update_channel_metadata(
+        ctx: Context<UpdateChannelMetadata>,
+        avatar_uri: String,
+        banner_uri: String,
+        category: String,
+        external_link: String,
+    ) -> Result<()> {
+        require!(
+            avatar_uri.len() <= 200 && banner_uri.len() <= 200 && external_link.len() <= 200,
+            MessagingError::MetadataTooLong
+        );
+        require!(category.len() <= 32, MessagingError::MetadataTooLong);
+
+        let channel = &mut ctx.accounts.broadcast_channel;
+
+        channel.avatar_uri = avatar_uri;
+        channel.banner_uri = banner_uri;
+        channel.category = category;
+        channel.external_link = external_link;
+
+        emit!(ChannelProfileUpdated {
+            channel: channel.key(),
+            avatar_uri: channel.avatar_uri.clone(),
+            banner_uri: channel.banner_uri.clone(),
+            category: channel.category.clone(),
+            external_link: channel.external_link.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Channel profile updated: {}", channel.channel_name);
+        msg!("Category: {}", channel.category);
+
+        Ok(())
+    }
+
+    /// Archive a thread, or close it to stop all messages until reopened
+    pub fn archive_thread(ctx: Context<UpdateThreadState>, close: bool) -> Result<()> {
+        let thread = &mut ctx.accounts.message_thread;
+        let participant = ctx.accounts.participant.key();
+
+        require!(
+            thread.is_participant(&participant),
+            MessagingError::UnauthorizedSender
+        );
+
+        thread.lifecycle = if close {
+            ThreadLifecycle::Closed
+        } else {
+            ThreadLifecycle::Archived
+        };
+
+        emit!(ThreadLifecycleChanged {
+            thread: thread.key(),
+            participant,
+            lifecycle: thread.lifecycle,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Thread {} by {}",
+            if close { "closed" } else { "archived" },
+            participant
+        );
+
+        Ok(())
+    }
+
+    /// Make an archived or closed thread active again
+    pub fn reopen_thread(ctx: Context<UpdateThreadState>) -> Result<()> {
+        let thread = &mut ctx.accounts.message_thread;
+        let participant = ctx.accounts.participant.key();
+
+        require!(
+            thread.is_participant(&participant),
+            MessagingError::UnauthorizedSender
+        );
+
+        thread.lifecycle = ThreadLifecycle::Active;
+
+        emit!(ThreadLifecycleChanged {
+            thread: thread.key(),
+            participant,
+            lifecycle: thread.lifecycle,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Thread reopened by {}", participant);
+
+        Ok(())
+    }
+
+    /// Undo a soft close, returning a `Closed` thread to `Active`
+    /// Unlike `reopen_thread` this only accepts closed threads. A hard-closed
+    /// thread no longer exists, so it fails account validation instead.
+    pub fn restore_thread(ctx: Context<UpdateThreadState>) -> Result<()> {
+        let thread = &mut ctx.accounts.message_thread;
+        let participant = ctx.accounts.participant.key();
+
+        require!(
+            thread.is_participant(&participant),
+            MessagingError::UnauthorizedSender
+        );
+        require!(
+            thread.lifecycle == ThreadLifecycle::Closed,
+            MessagingError::ThreadNotClosed
+        );
+
+        thread.lifecycle = ThreadLifecycle::Active;
+
+        emit!(ThreadRestored {
+            thread: thread.key(),
+            participant,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Thread restored by {}", participant);
+
+        Ok(())
+    }
+
+    /// Archive an active thread that has been idle longer than the participant's
+    /// auto-archive preference. Permissionless so a crank can sweep inboxes;
+    /// like `archive_thread` the archived state is shared by both sides.
+    pub fn auto_archive_thread(ctx: Context<AutoArchiveThread>) -> Result<()> {
+        let thread = &mut ctx.accounts.message_thread;
+        let participant = ctx.accounts.participant.key();
+        let preference = &ctx.accounts.archive_preference;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            thread.is_participant(&participant),
+            MessagingError::NotAParticipant
+        );
+        require!(
+            thread.lifecycle == ThreadLifecycle::Active,
+            MessagingError::AutoArchiveNotDue
+        );
+
+        // A thread that never carried a message has been idle since creation
+        let last_activity = if thread.message_count == 0 {
+            thread.created_at
+        } else {
+            thread.last_message_at
+        };
+
+        require!(
+            preference.auto_archive_after > 0
+                && now.saturating_sub(last_activity) >= preference.auto_archive_after,
+            MessagingError::AutoArchiveNotDue
+        );
+
+        thread.lifecycle = ThreadLifecycle::Archived;
+
+        emit!(AutoArchived {
+            thread: thread.key(),
+            participant,
+            last_activity,
+            auto_archive_after: preference.auto_archive_after,
+            timestamp: now,
+        });
+
+        msg!("Thread auto-archived for {}", participant);
+
+        Ok(())
+    }
+
+    /// Mute or unmute notifications for the signer's side of a thread
+    pub fn mute_thread(ctx: Context<UpdateThreadState>, muted: bool) -> Result<()> {
+        let thread = &mut ctx.accounts.message_thread;
+        let participant = ctx.accounts.participant.key();
+
+        if participant == thread.participant_a {
+            thread.a_muted = muted;
+        } else if participant == thread.participant_b {
+            thread.b_muted = muted;
+        } else {
+            return err!(MessagingError::UnauthorizedSender);
+        }
+
+        emit!(ThreadMuted {
+            thread: thread.key(),
+            participant,
+            muted,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Thread muted by {}: {}", participant, muted);
+
+        Ok(())
+    }
+
+    /// Toggle a reaction on a thread message
+    /// The same `Reaction` account as `add_reaction`: reacting with the current emoji
+    /// removes it, a different emoji replaces it
+    pub fn react_to_message(
+        ctx: Context<ReactToMessage>,
+        message_index: u32,
+        emoji: [u8; 8],
+    ) -> Result<()> {
+        let thread = &ctx.accounts.message_thread;
+        let reactor = &ctx.accounts.reactor;
+
+        require!(
+            thread.is_participant(&reactor.key()),
+            MessagingError::UnauthorizedSender
+        );
+        require!(
+            u64::from(message_index) < thread.message_count,
+            MessagingError::InvalidMessageIndex
+        );
+
+        let reaction = &mut ctx.accounts.reaction;
+        if reaction.reactor != Pubkey::default() {
+            emit_reaction_removed(reaction)?;
+            if reaction.emoji == emoji {
+                return reaction.close(reactor.to_account_info());
+            }
+        }
+
+        set_reaction(reaction, thread, reactor.key(), message_index, emoji, true)
+    }
+
+    /// Toggle a reaction on a broadcast (subscribers only)
+    /// Reacting with the current emoji removes it; a different emoji replaces it
+    pub fn react_to_broadcast(
+        ctx: Context<ReactToBroadcast>,
+        message_index: u32,
+        emoji: [u8; 8],
+    ) -> Result<()> {
+        let channel = &ctx.accounts.broadcast_channel;
+        let subscriber = &ctx.accounts.subscriber;
+        require!(
+            u64::from(message_index) < channel.message_count,
+            MessagingError::InvalidMessageIndex
+        );
+        require!(emoji != [0u8; 8], MessagingError::InvalidEmoji);
+
+        let reaction = &mut ctx.accounts.reaction;
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        if reaction.reactor != Pubkey::default() {
+            emit!(BroadcastReactionRemoved {
+                channel: reaction.channel,
+                reactor: reaction.reactor,
+                message_index,
+                emoji: reaction.emoji,
+                timestamp,
+            });
+
+            if reaction.emoji == emoji {
+                msg!("Reaction removed from broadcast {}", message_index);
+                return reaction.close(subscriber.to_account_info());
+            }
+        }
+
+        reaction.reactor = subscriber.key();
+        reaction.channel = channel.key();
+        reaction.message_index = message_index;
+        reaction.emoji = emoji;
+        reaction.timestamp = timestamp;
+        reaction.version = ACCOUNT_VERSION;
+
+        emit!(BroadcastReactionAdded {
+            channel: reaction.channel,
+            reactor: reaction.reactor,
+            message_index,
+            emoji,
+            timestamp,
+        });
+
+        msg!(
+            "Reaction added to broadcast {} by {}",
+            message_index,
+            reaction.reactor
+        );
+
+        Ok(())
+    }
+
+    /// Record a delete-for-everyone tombstone for a thread message
+    /// Content stays in transaction data; compliant clients hide tombstoned messages.
+    /// Authorship is checked on-chain for the latest message and for stored messages;
+    /// otherwise clients must only honour a tombstone whose `revoked_by` matches the
+    /// original transaction signer. Tombstones are keyed by revoker, so the other
+    /// participant cannot take the slot and block the real author.
+    pub fn revoke_message(ctx: Context<RevokeMessage>, message_index: u32) -> Result<()> {
+        let thread = &ctx.accounts.message_thread;
+        let sender = ctx.accounts.sender.key();
+
+        require!(
+            thread.is_participant(&sender),
+            MessagingError::UnauthorizedSender
+        );
+        require!(
+            u64::from(message_index) < thread.message_count,
+            MessagingError::InvalidMessageIndex
+        );
+        if u64::from(message_index) == thread.message_count - 1 {
+            require!(
+                sender == thread.last_sender,
+                MessagingError::UnauthorizedSender
+            );
+        }
+        if let Some(record) = &ctx.accounts.message_record {
+            require!(
+                record.thread == thread.key() && record.message_index == message_index,
+                MessagingError::InvalidMessageIndex
+            );
+            require!(record.sender == sender, MessagingError::UnauthorizedSender);
+        }
+
+        let tombstone = &mut ctx.accounts.tombstone;
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        tombstone.target = thread.key();
+        tombstone.message_index = message_index;
+        tombstone.revoked_by = sender;
+        tombstone.revoked_at = timestamp;
+        tombstone.version = ACCOUNT_VERSION;
+
+        emit!(MessageRevoked {
+            target: tombstone.target,
+            message_index,
+            revoked_by: sender,
+            timestamp,
+        });
+
+        msg!("Message {} revoked by {}", message_index, sender);
+
+        Ok(())
+    }
+
+    /// Record a tombstone for a broadcast (owner only)
+    pub fn revoke_broadcast(ctx: Context<RevokeBroadcast>, message_index: u32) -> Result<()> {
+        let channel = &ctx.accounts.broadcast_channel;
+        require!(
+            u64::from(message_index) < channel.message_count,
+            MessagingError::InvalidMessageIndex
+        );
+
+        let tombstone = &mut ctx.accounts.tombstone;
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        tombstone.target = channel.key();
+        tombstone.message_index = message_index;
+        tombstone.revoked_by = ctx.accounts.owner.key();
+        tombstone.revoked_at = timestamp;
+        tombstone.version = ACCOUNT_VERSION;
+
+        emit!(MessageRevoked {
+            target: tombstone.target,
+            message_index,
+            revoked_by: tombstone.revoked_by,
+            timestamp,
+        });
+
+        msg!(
+            "Broadcast {} revoked on {}",
+            message_index,
+            channel.channel_name
+        );
+
+        Ok(())
+    }
+
+    /// Propose or agree to a disappearing-message TTL for stored messages
+    /// The TTL changes only when both participants have asked for the same value
+    pub fn set_message_ttl(ctx: Context<UpdateThreadState>, ttl_seconds: i64) -> Result<()> {
+        require!(ttl_seconds >= 0, MessagingError::InvalidAmount);
+
+        let thread = &mut ctx.accounts.message_thread;
+        let participant = ctx.accounts.participant.key();
+        require!(
+            thread.is_participant(&participant),
+            MessagingError::UnauthorizedSender
+        );
+
+        let agreed = thread.ttl_proposer != Pubkey::default()
+            && thread.ttl_proposer != participant
+            && thread.pending_ttl_seconds == ttl_seconds;
+
+        if agreed {
+            thread.message_ttl_seconds = ttl_seconds;
+            thread.pending_ttl_seconds = 0;
+            thread.ttl_proposer = Pubkey::default();
+        } else {
+            thread.pending_ttl_seconds = ttl_seconds;
+            thread.ttl_proposer = participant;
+        }
+
+        emit!(MessageTtlUpdated {
+            thread: thread.key(),
+            participant,
+            ttl_seconds,
+            applied: agreed,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        if agreed {
+            msg!("Message TTL set to {} seconds", ttl_seconds);
+        } else {
+            msg!(
+                "Message TTL of {} seconds proposed by {}",
+                ttl_seconds,
+                participant
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Close an expired message record and refund its rent to the sender (permissionless)
+    pub fn prune_expired_message(ctx: Context<PruneExpiredMessage>) -> Result<()> {
+        let record = &ctx.accounts.message_record;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            record.expires_at != 0 && now >= record.expires_at,
+            MessagingError::MessageNotExpired
+        );
+
+        emit!(MessageRecordClosed {
+            thread: record.thread,
+            record: record.key(),
+            message_index: record.message_index,
+            timestamp: now,
+        });
+
+        msg!("Expired message record {} pruned", record.message_index);
+
+        Ok(())
+    }
+
+    /// Close message records left behind by a closed thread (permissionless)
+    /// Remaining accounts are (record, sender) pairs, in batches as large as a
+    /// transaction allows; each record's rent goes back to its sender. Records
+    /// older than a thread re-created at the same address count as orphaned too.
+    pub fn purge_thread_records<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PurgeThreadRecords<'info>>,
+    ) -> Result<()> {
+        let thread_info = &ctx.accounts.message_thread;
+        let reopened_at = if thread_info.data_is_empty() {
+            None
+        } else {
+            let thread = MessageThread::try_deserialize(&mut &thread_info.try_borrow_data()?[..])?;
+            Some(thread.created_at)
+        };
+
+        let pairs = ctx.remaining_accounts.chunks_exact(2);
+        require!(
+            !ctx.remaining_accounts.is_empty() && pairs.remainder().is_empty(),
+            MessagingError::InvalidRecordBatch
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        for pair in pairs {
+            let [record_info, sender] = pair else {
+                return err!(MessagingError::InvalidRecordBatch);
+            };
+            let record = Account::<MessageRecord>::try_from(record_info)?;
+            require_keys_eq!(
+                record.thread,
+                thread_info.key(),
+                MessagingError::InvalidRecordBatch
+            );
+            require!(
+                reopened_at.is_none_or(|created_at| record.timestamp < created_at),
+                MessagingError::ThreadStillOpen
+            );
+            require_keys_eq!(
+                sender.key(),
+                record.sender,
+                MessagingError::UnauthorizedSender
+            );
+
+            emit!(MessageRecordClosed {
+                thread: record.thread,
+                record: record.key(),
+                message_index: record.message_index,
+                timestamp: now,
+            });
+
+            record.close(sender.clone())?;
+        }
+
+        msg!(
+            "Purged {} message records of {}",
+            ctx.remaining_accounts.len() / 2,
+            thread_info.key()
+        );
+
+        Ok(())
+    }
+
+    /// Set the minimum seconds between messages in a thread (participant B only)
+    pub fn set_min_message_interval(
+        ctx: Context<UpdateRecipientSettings>,
+        min_message_interval: i64,
+    ) -> Result<()> {
+        require!(min_message_interval >= 0, MessagingError::InvalidAmount);
+
+        let thread = &mut ctx.accounts.message_thread;
+
+        thread.min_message_interval = min_message_interval;
+
+        emit!(RateLimitUpdated {
+            target: thread.key(),
+            min_interval: min_message_interval,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Min message interval set to {} seconds",
+            min_message_interval
+        );
+
+        Ok(())
+    }
+
+    /// Set the minimum seconds between broadcasts (owner only)
+    pub fn set_min_broadcast_interval(
+        ctx: Context<UpdateChannelSettings>,
+        min_broadcast_interval: i64,
+    ) -> Result<()> {
+        require!(min_broadcast_interval >= 0, MessagingError::InvalidAmount);
+
+        let channel = &mut ctx.accounts.broadcast_channel;
+
+        channel.min_broadcast_interval = min_broadcast_interval;
+
+        emit!(RateLimitUpdated {
+            target: channel.key(),
+            min_interval: min_broadcast_interval,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Min broadcast interval set to {} seconds",
+            min_broadcast_interval
+        );
+
+        Ok(())
+    }
+
+    /// Set the deposit non-contacts must lock to open a thread with the owner
+    pub fn set_outreach_deposit(ctx: Context<UpdateProfile>, lamports: u64) -> Result<()> {
+        let profile = &mut ctx.accounts.user_profile;
+        let now = Clock::get()?.unix_timestamp;
+
+        profile.outreach_deposit_lamports = lamports;
+        profile.updated_at = now;
+
+        emit!(OutreachDepositSet {
+            profile: profile.key(),
+            owner: profile.owner,
+            lamports,
+            timestamp: now,
+        });
+
+        msg!("Outreach deposit set to {} lamports", lamports);
+
+        Ok(())
+    }
+
+    /// Settle an outreach deposit once the recipient has decided (permissionless)
+    /// Accepted threads refund the depositor; rejected (or closed while pending)
+    /// threads forfeit the deposit to the recipient. Vault rent always goes back
+    /// to the depositor.
+    pub fn claim_thread_deposit(ctx: Context<ClaimThreadDeposit>) -> Result<()> {
+        let vault = &ctx.accounts.thread_vault;
+        let thread_info = &ctx.accounts.message_thread;
+
+        let refund = if thread_info.data_is_empty() {
+            false
+        } else {
+            let thread = MessageThread::try_deserialize(&mut &thread_info.try_borrow_data()?[..])?;
+            match thread.status {
+                ThreadStatus::Pending => return err!(MessagingError::DepositUnsettled),
+                ThreadStatus::Accepted => true,
+                ThreadStatus::Rejected => false,
+            }
+        };
+
+        let amount = vault.amount;
+        if !refund {
+            move_lamports(
+                &vault.to_account_info(),
+                &ctx.accounts.recipient.to_account_info(),
+                amount,
+            )?;
+        }
+
+        emit!(OutreachDepositSettled {
+            thread: vault.thread,
+            depositor: vault.depositor,
+            recipient: vault.recipient,
+            amount,
+            refunded: refund,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Outreach deposit of {} lamports {}",
+            amount,
+            if refund { "refunded" } else { "forfeited" }
+        );
+
+        Ok(())
+    }
+
+    /// Send a message with a SOL or SPL token tip to the other participant
+    /// SOL is tipped when no mint is passed; otherwise the tip moves between
+    /// the participants' token accounts for that mint
+    pub fn send_message_with_tip(
+        ctx: Context<SendMessageWithTip>,
+        message_index: u32,
+        encrypted_content: Vec<u8>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, MessagingError::InvalidAmount);
+        ensure_not_blocked(&ctx.accounts.block_by_a)?;
+        ensure_not_blocked(&ctx.accounts.block_by_b)?;
+
+        let accounts = &mut *ctx.accounts;
+        let sender = accounts.sender.key();
+        let recipient = accounts.recipient.key();
+        let content_hash = hash(&encrypted_content).to_bytes();
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        let thread = &mut accounts.message_thread;
+        thread.record_message(sender, message_index, content_hash, timestamp)?;
+        require_keys_eq!(
+            recipient,
+            thread.other_participant(&sender),
+            MessagingError::InvalidTipAccount
+        );
+        let thread_key = thread.key();
+
+        let mint = match &accounts.mint {
+            None => {
+                // SOL tips carry the protocol fee in basis points
+                let fee_bps = read_config(&accounts.config)?.map_or(0, |c| c.protocol_fee_bps);
+                let fee = (u128::from(amount) * u128::from(fee_bps) / 10_000) as u64;
+                pay_protocol_fee(
+                    &accounts.sender,
+                    accounts.treasury.as_ref(),
+                    Some(accounts.system_program.to_account_info()),
+                    fee,
+                )?;
+                transfer(
+                    CpiContext::new(
+                        accounts.system_program.to_account_info(),
+                        Transfer {
+                            from: accounts.sender.to_account_info(),
+                            to: accounts.recipient.to_account_info(),
+                        },
+                    ),
+                    amount - fee,
+                )?;
+                Pubkey::default()
+            }
+            Some(mint) => {
+                let (Some(from), Some(to), Some(token_program)) = (
+                    &accounts.sender_token_account,
+                    &accounts.recipient_token_account,
+                    &accounts.token_program,
+                ) else {
+                    return err!(MessagingError::InvalidTipAccount);
+                };
+                require_keys_eq!(to.owner, recipient, MessagingError::InvalidTipAccount);
+
+                transfer_checked(
+                    CpiContext::new(
+                        token_program.to_account_info(),
+                        TransferChecked {
+                            from: from.to_account_info(),
+                            mint: mint.to_account_info(),
+                            to: to.to_account_info(),
+                            authority: accounts.sender.to_account_info(),
+                        },
+                    ),
+                    amount,
+                    mint.decimals,
+                )?;
+                mint.key()
+            }
+        };
+
+        emit!(MessageSent {
+            thread: thread_key,
+            sender,
+            message_index,
+            content_hash,
+            timestamp,
+            reply_to_index: None,
+            attachment: None,
+            device: None,
+        });
+
+        emit!(MessageTipped {
+            thread: thread_key,
+            sender,
+            recipient,
+            message_index,
+            amount,
+            mint,
+            timestamp,
+        });
+
+        msg!(
+            "Message {} sent by {} with a tip of {}",
+            message_index,
+            sender,
+            amount
+        );
+
+        Ok(())
+    }
+
+    /// Ask the other participant of a thread for a payment
+    /// With `escrow` the payment is held by the request until the payer
+    /// releases it (goods-in-chat); otherwise it goes straight to the requester.
+    /// A default `mint` requests SOL.
+    pub fn request_payment(
+        ctx: Context<RequestPayment>,
+        request_id: u64,
+        amount: u64,
+        mint: Pubkey,
+        memo_hash: [u8; 32],
+        escrow: bool,
+    ) -> Result<()> {
+        require!(amount > 0, MessagingError::InvalidAmount);
+
+        let thread = &ctx.accounts.message_thread;
+        let requester = ctx.accounts.requester.key();
+        require!(
+            thread.lifecycle != ThreadLifecycle::Closed,
+            MessagingError::ThreadClosed
+        );
+        let payer = if requester == thread.participant_a {
+            thread.participant_b
+        } else if requester == thread.participant_b {
+            thread.participant_a
+        } else {
+            return err!(MessagingError::UnauthorizedSender);
+        };
+
+        let request = &mut ctx.accounts.payment_request;
+        let now = Clock::get()?.unix_timestamp;
+
+        request.thread = thread.key();
+        request.request_id = request_id;
+        request.requester = requester;
+        request.payer = payer;
+        request.amount = amount;
+        request.mint = mint;
+        request.memo_hash = memo_hash;
+        request.escrow = escrow;
+        request.status = PaymentStatus::Open;
+        request.created_at = now;
+        request.funded_at = 0;
+        request.version = ACCOUNT_VERSION;
+
+        emit!(PaymentRequested {
+            request: request.key(),
+            thread: request.thread,
+            requester,
+            payer,
+            amount,
+            mint,
+            memo_hash,
+            escrow,
+            timestamp: now,
+        });
+
+        msg!("Payment of {} requested from {}", amount, payer);
+
+        Ok(())
+    }
+
+    /// Pay an open payment request (payer only)
+    /// Direct requests pay the requester and close; escrow requests are funded
+    /// and wait for `release_payment`
+    pub fn fulfill_payment(ctx: Context<FulfillPayment>) -> Result<()> {
+        let accounts = &ctx.accounts;
+        let request = &accounts.payment_request;
+        require!(
+            request.status == PaymentStatus::Open,
+            MessagingError::InvalidPaymentState
+        );
+
+        let request_key = request.key();
+        let destination = if request.escrow {
+            request.to_account_info()
+        } else {
+            accounts.requester.to_account_info()
+        };
+
+        if request.mint == Pubkey::default() {
+            transfer(
+                CpiContext::new(
+                    accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: accounts.payer.to_account_info(),
+                        to: destination,
+                    },
+                ),
+                request.amount,
+            )?;
+        } else {
+            let (Some(mint), Some(from), Some(to), Some(token_program)) = (
+                &accounts.mint,
+                &accounts.payer_token_account,
+                &accounts.destination_token_account,
+                &accounts.token_program,
+            ) else {
+                return err!(MessagingError::InvalidPaymentAccount);
+            };
+            require_keys_eq!(
+                mint.key(),
+                request.mint,
+                MessagingError::InvalidPaymentAccount
+            );
+            if request.escrow {
+                let escrow_account = get_associated_token_address_with_program_id(
+                    &request_key,
+                    &request.mint,
+                    &token_program.key(),
+                );
+                require_keys_eq!(
+                    to.key(),
+                    escrow_account,
+                    MessagingError::InvalidPaymentAccount
+                );
+            } else {
+                require_keys_eq!(
+                    to.owner,
+                    request.requester,
+                    MessagingError::InvalidPaymentAccount
+                );
+            }
+
+            transfer_checked(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    TransferChecked {
+                        from: from.to_account_info(),
+                        mint: mint.to_account_info(),
+                        to: to.to_account_info(),
+                        authority: accounts.payer.to_account_info(),
+                    },
+                ),
+                request.amount,
+                mint.decimals,
+            )?;
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let request = &mut ctx.accounts.payment_request;
+
+        emit!(PaymentFulfilled {
+            request: request_key,
+            thread: request.thread,
+            requester: request.requester,
+            payer: request.payer,
+            amount: request.amount,
+            mint: request.mint,
+            escrowed: request.escrow,
+            timestamp: now,
+        });
+
+        msg!("Payment request {} fulfilled", request.request_id);
+
+        if request.escrow {
+            request.status = PaymentStatus::Funded;
+            request.funded_at = now;
+        } else {
+            request.close(ctx.accounts.requester.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    /// Release an escrowed payment to the requester (payer only)
+    pub fn release_payment(ctx: Context<SettlePayment>) -> Result<()> {
+        let request = &ctx.accounts.payment_request;
+        require_keys_eq!(
+            ctx.accounts.authority.key(),
+            request.payer,
+            MessagingError::UnauthorizedSender
+        );
+        require!(
+            request.status == PaymentStatus::Funded,
+            MessagingError::InvalidPaymentState
+        );
+
+        let requester = ctx.accounts.requester.to_account_info();
+        pay_out_escrow(ctx.accounts, ctx.bumps.payment_request, &requester)?;
+
+        emit!(PaymentReleased {
+            request: request.key(),
+            thread: request.thread,
+            requester: request.requester,
+            payer: request.payer,
+            amount: request.amount,
+            mint: request.mint,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Payment request {} released", request.request_id);
+
+        ctx.accounts.payment_request.close(requester)
+    }
+
+    /// Cancel a payment request
+    /// Either side can drop an open request; a funded escrow can only be
+    /// refunded to the payer by the requester
+    pub fn cancel_payment_request(ctx: Context<SettlePayment>) -> Result<()> {
+        let request = &ctx.accounts.payment_request;
+        let authority = ctx.accounts.authority.key();
+        let refunded = request.status == PaymentStatus::Funded;
+        if refunded {
+            require_keys_eq!(
+                authority,
+                request.requester,
+                MessagingError::UnauthorizedSender
+            );
+            let payer = ctx.accounts.payer.to_account_info();
+            pay_out_escrow(ctx.accounts, ctx.bumps.payment_request, &payer)?;
+        } else {
+            require!(
+                authority == request.requester || authority == request.payer,
+                MessagingError::UnauthorizedSender
+            );
+        }
+
+        emit!(PaymentRequestCancelled {
+            request: request.key(),
+            thread: request.thread,
+            cancelled_by: authority,
+            refunded,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Payment request {} cancelled", request.request_id);
+
+        ctx.accounts
+            .payment_request
+            .close(ctx.accounts.requester.to_account_info())
+    }
+
+    /// Save a user to the caller's contacts with an optional alias
+    pub fn add_contact(ctx: Context<AddContact>, alias: String) -> Result<()> {
+        require!(alias.len() <= 32, MessagingError::AliasTooLong);
+
+        let entry = &mut ctx.accounts.contact_entry;
+        let owner = ctx.accounts.owner.key();
+        let contact = ctx.accounts.contact.key();
+        require!(owner != contact, MessagingError::InvalidContact);
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        entry.owner = owner;
+        entry.contact = contact;
+        entry.alias = alias;
+        entry.added_at = timestamp;
+        entry.version = ACCOUNT_VERSION;
+
+        emit!(ContactAdded {
+            owner,
+            contact,
+            timestamp,
+        });
+
+        msg!("{} added {} to contacts", owner, contact);
+
+        Ok(())
+    }
+
+    /// Change the alias of a saved contact
+    pub fn set_contact_alias(ctx: Context<UpdateContact>, alias: String) -> Result<()> {
+        require!(alias.len() <= 32, MessagingError::AliasTooLong);
+
+        let entry = &mut ctx.accounts.contact_entry;
+        entry.alias = alias;
+
+        msg!("Alias updated for contact {}", entry.contact);
+
+        Ok(())
+    }
+
+    /// Remove a contact and refund the entry's rent
+    pub fn remove_contact(ctx: Context<RemoveContact>) -> Result<()> {
+        let entry = &ctx.accounts.contact_entry;
+
+        emit!(ContactRemoved {
+            owner: entry.owner,
+            contact: entry.contact,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("{} removed {} from contacts", entry.owner, entry.contact);
+
+        Ok(())
+    }
+
+    /// Claim a `@handle` for the caller
+    /// Handles are lowercase `a-z`, `0-9` and `_`; claiming pays a small fee
+    /// to the protocol treasury to discourage squatting
+    pub fn claim_handle(ctx: Context<ClaimHandle>, handle: String) -> Result<()> {
+        validate_handle(&handle)?;
+
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.owner.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                },
+            ),
+            HANDLE_REGISTRATION_FEE,
+        )?;
+
+        let entry = &mut ctx.accounts.handle_entry;
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        entry.owner = ctx.accounts.owner.key();
+        entry.handle = handle;
+        entry.claimed_at = timestamp;
+        entry.version = ACCOUNT_VERSION;
+
+        emit!(HandleClaimed {
+            handle: entry.handle.clone(),
+            owner: entry.owner,
+            fee: HANDLE_REGISTRATION_FEE,
+            timestamp,
+        });
+
+        msg!("@{} claimed by {}", entry.handle, entry.owner);
+
+        Ok(())
+    }
+
+    /// Give a handle to another user (owner only)
+    pub fn transfer_handle(ctx: Context<TransferHandle>) -> Result<()> {
+        let entry = &mut ctx.accounts.handle_entry;
+        let previous_owner = entry.owner;
+        entry.owner = ctx.accounts.new_owner.key();
+
+        emit!(HandleTransferred {
+            handle: entry.handle.clone(),
+            previous_owner,
+            new_owner: entry.owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("@{} transferred to {}", entry.handle, entry.owner);
+
+        Ok(())
+    }
+
+    /// Release a handle so anyone can claim it; refunds the rent, not the fee
+    pub fn release_handle(ctx: Context<ReleaseHandle>) -> Result<()> {
+        let entry = &ctx.accounts.handle_entry;
+
+        emit!(HandleReleased {
+            handle: entry.handle.clone(),
+            owner: entry.owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("@{} released", entry.handle);
+
+        Ok(())
+    }
+
+    /// Upgrade a thread created by an older program version (either participant)
+    /// New fields start zeroed; `bump` is backfilled
+    pub fn migrate_thread(ctx: Context<MigrateThread>) -> Result<()> {
+        let thread_info = ctx.accounts.message_thread.to_account_info();
+        let new_len = 8 + MessageThread::INIT_SPACE;
+        let old_len = grow_for_migration(
+            &thread_info,
+            MessageThread::DISCRIMINATOR,
+            &[8, 40],
+            &ctx.accounts.participant,
+            new_len,
+            &ctx.accounts.system_program,
+            MessagingError::InvalidMigrationAccount,
+        )?;
+
+        // v1 -> v2: message_count, observed counts and read markers become u64.
+        // Threads are fixed-size, so any shorter account predates the change.
+        if old_len < new_len {
+            widen_u32_fields(&thread_info, old_len, &[108, 233, 237, 241, 245])?;
+        }
+
+        let mut thread = MessageThread::try_deserialize(&mut &thread_info.try_borrow_data()?[..])?;
+        if thread.bump == 0 {
+            let (first, second) = sorted_participants(&thread.participant_a, &thread.participant_b);
+            let (_, bump) = Pubkey::find_program_address(
+                &[
+                    b"message_thread",
+                    first.as_ref(),
+                    second.as_ref(),
+                    thread.thread_id.as_ref(),
+                ],
+                ctx.program_id,
+            );
+            thread.bump = bump;
+        }
+        thread.version = ACCOUNT_VERSION;
+        thread.try_serialize(&mut &mut thread_info.try_borrow_mut_data()?[..])?;
+
+        emit!(ThreadMigrated {
+            thread: thread_info.key(),
+            old_len: old_len as u32,
+            new_len: new_len as u32,
+            version: ACCOUNT_VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Thread migrated: {} -> {} bytes", old_len, new_len);
+
+        Ok(())
+    }
+
+    /// Upgrade a subscription created by an older program version (subscriber only)
+    /// New fields start zeroed; `bump` is backfilled
+    pub fn migrate_subscription(ctx: Context<MigrateSubscription>) -> Result<()> {
+        let subscription_info = ctx.accounts.subscription.to_account_info();
+        let new_len = 8 + ChannelSubscription::INIT_SPACE;
+        let old_len = grow_for_migration(
+            &subscription_info,
+            ChannelSubscription::DISCRIMINATOR,
+            &[8],
+            &ctx.accounts.subscriber,
+            new_len,
+            &ctx.accounts.system_program,
+            MessagingError::InvalidMigrationAccount,
+        )?;
+
+        // v1 -> v2: last_read_index becomes u64 (subscriptions are fixed-size)
+        if old_len < new_len {
+            widen_u32_fields(&subscription_info, old_len, &[84])?;
+        }
+
+        let mut subscription =
+            ChannelSubscription::try_deserialize(&mut &subscription_info.try_borrow_data()?[..])?;
+        if subscription.bump == 0 {
+            let (_, bump) = Pubkey::find_program_address(
+                &[
+                    b"subscription",
+                    subscription.channel.as_ref(),
+                    subscription.subscriber.as_ref(),
+                ],
+                ctx.program_id,
+            );
+            subscription.bump = bump;
+        }
+        subscription.version = ACCOUNT_VERSION;
+        subscription.try_serialize(&mut &mut subscription_info.try_borrow_mut_data()?[..])?;
+
+        emit!(SubscriptionMigrated {
+            subscription: subscription_info.key(),
+            channel: subscription.channel,
+            old_len: old_len as u32,
+            new_len: new_len as u32,
+            version: ACCOUNT_VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Subscription migrated: {} -> {} bytes", old_len, new_len);
+
+        Ok(())
+    }
+
+    /// Create the program config (upgrade authority only)
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        config.authority = ctx.accounts.authority.key();
+        config.schema_version = ACCOUNT_VERSION;
+        config.version = ACCOUNT_VERSION;
+        config.verifier = config.authority;
+        config.treasury = config.authority;
+        config.protocol_fee_bps = 0;
+        config.paused = false;
+        config.default_max_consecutive = DEFAULT_MAX_CONSECUTIVE;
+        config.default_min_message_interval = 0;
+        config.default_min_broadcast_interval = 0;
+        config.pending_authority = Pubkey::default();
+        config.message_fee_lamports = 0;
+
+        emit!(SchemaVersionUpdated {
+            authority: config.authority,
+            schema_version: config.schema_version,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Config initialized at schema version {}",
+            config.schema_version
+        );
+
+        Ok(())
+    }
+
+    /// Record the schema version of the deployed program after an upgrade
+    pub fn sync_schema_version(ctx: Context<UpdateConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        config.schema_version = ACCOUNT_VERSION;
+        config.version = ACCOUNT_VERSION;
+
+        emit!(SchemaVersionUpdated {
+            authority: config.authority,
+            schema_version: config.schema_version,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Schema version set to {}", config.schema_version);
+
+        Ok(())
+    }
+
+    /// Attach a concurrent Merkle tree to a thread (either participant)
+    /// The tree account is allocated by the client, owned by spl-account-compression;
+    /// the thread's compressed log PDA becomes its authority
+    pub fn enable_thread_compression(
+        ctx: Context<EnableThreadCompression>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        let thread = &ctx.accounts.message_thread;
+        let authority = ctx.accounts.authority.key();
+        require!(
+            authority == thread.participant_a || authority == thread.participant_b,
+            MessagingError::UnauthorizedSender
+        );
+
+        open_compressed_log(
+            &mut ctx.accounts.compressed_log,
+            ctx.bumps.compressed_log,
+            thread.key(),
+            &ctx.accounts.merkle_tree,
+            &ctx.accounts.compression_program,
+            &ctx.accounts.noop_program,
+            max_depth,
+            max_buffer_size,
+        )
+    }
+
+    /// Attach a concurrent Merkle tree to a channel (owner only)
+    pub fn enable_channel_compression(
+        ctx: Context<EnableChannelCompression>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        open_compressed_log(
+            &mut ctx.accounts.compressed_log,
+            ctx.bumps.compressed_log,
+            ctx.accounts.broadcast_channel.key(),
+            &ctx.accounts.merkle_tree,
+            &ctx.accounts.compression_program,
+            &ctx.accounts.noop_program,
+            max_depth,
+            max_buffer_size,
+        )
+    }
+
+    /// Send a message whose history is kept as a leaf in the thread's Merkle tree
+    /// The leaf is `hash(sender, message_index, timestamp, content_hash)`
+    pub fn send_message_compressed(
+        ctx: Context<SendMessageCompressed>,
+        message_index: u32,
+        encrypted_content: Vec<u8>,
+    ) -> Result<()> {
+        ensure_not_blocked(&ctx.accounts.block_by_a)?;
+        ensure_not_blocked(&ctx.accounts.block_by_b)?;
+
+        let thread = &mut ctx.accounts.message_thread;
+        let sender = ctx.accounts.sender.key();
+        let content_hash = hash(&encrypted_content).to_bytes();
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        thread.record_message(sender, message_index, content_hash, timestamp)?;
+
+        emit!(MessageSent {
+            thread: thread.key(),
+            sender,
+            message_index,
+            content_hash,
+            timestamp,
+            reply_to_index: None,
+            attachment: None,
+            device: None,
+        });
+
+        append_compressed_leaf(
+            &mut ctx.accounts.compressed_log,
+            ctx.bumps.compressed_log,
+            &ctx.accounts.merkle_tree,
+            &ctx.accounts.compression_program,
+            &ctx.accounts.noop_program,
+            sender,
+            message_index,
+            content_hash,
+            timestamp,
+        )?;
+
+        msg!("Compressed message {} sent by {}", message_index, sender);
+
+        charge_message_fee(ctx.accounts, 1)?;
+
+        Ok(())
+    }
+
+    /// Broadcast whose history is kept as a leaf in the channel's Merkle tree
+    pub fn send_broadcast_compressed(
+        ctx: Context<SendBroadcastCompressed>,
+        message_index: u32,
+        encrypted_content: Vec<u8>,
+    ) -> Result<()> {
+        let channel = &mut ctx.accounts.broadcast_channel;
+        let sender = ctx.accounts.sender.key();
+        let is_admin = ctx
+            .accounts
+            .channel_admin
+            .as_ref()
+            .is_some_and(|admin| admin.has_permission(ChannelAdmin::BROADCAST));
+        let content_hash = hash(&encrypted_content).to_bytes();
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        let authorized = sender == channel.owner || is_admin;
+        channel.record_broadcast(authorized, message_index, timestamp)?;
+
+        emit!(BroadcastSent {
+            channel: channel.key(),
+            sender,
+            message_index,
+            content_hash,
+            burn_after_seen: false,
+            timestamp,
+            reply_to_index: None,
+            attachment: None,
+            device: None,
+        });
+
+        append_compressed_leaf(
+            &mut ctx.accounts.compressed_log,
+            ctx.bumps.compressed_log,
+            &ctx.accounts.merkle_tree,
+            &ctx.accounts.compression_program,
+            &ctx.accounts.noop_program,
+            sender,
+            message_index,
+            content_hash,
+            timestamp,
+        )?;
+
+        msg!("Compressed broadcast {} sent", message_index);
+
+        charge_message_fee(ctx.accounts, 1)?;
+
+        Ok(())
+    }
+
+    /// Drop a pointer from your own inbox, e.g. one left behind by a closed thread
+    pub fn remove_thread_pointer(ctx: Context<RemoveThreadPointer>) -> Result<()> {
+        let pointer = &ctx.accounts.pointer;
+
+        emit!(ThreadPointerRemoved {
+            owner: pointer.owner,
+            thread: pointer.thread,
+            pointer: pointer.key(),
+            index: pointer.index,
+        });
+
+        msg!("Thread pointer {} removed", pointer.index);
+
+        Ok(())
+    }
+
+    /// Opt a channel into the public directory (owner only)
+    /// `page_index` must be an existing page with room, or the next new page
+    pub fn list_channel_public(ctx: Context<ListChannelPublic>, page_index: u32) -> Result<()> {
+        let directory = &mut ctx.accounts.directory;
+        let page = &mut ctx.accounts.page;
+        let channel = ctx.accounts.broadcast_channel.key();
+        let now = Clock::get()?.unix_timestamp;
+
+        // Pages are appended in order so clients can walk 0..page_count
+        if page_index == directory.page_count {
+            directory.page_count = directory
+                .page_count
+                .checked_add(1)
+                .ok_or(MessagingError::Overflow)?;
+            directory.version = ACCOUNT_VERSION;
+            page.index = page_index;
+            page.version = ACCOUNT_VERSION;
+        }
+        require!(
+            page_index < directory.page_count,
+            MessagingError::InvalidDirectoryPage
+        );
+        require!(
+            page.channels.len() < DIRECTORY_PAGE_CAPACITY,
+            MessagingError::DirectoryPageFull
+        );
+
+        page.channels.push(channel);
+        directory.listed_count = directory
+            .listed_count
+            .checked_add(1)
+            .ok_or(MessagingError::Overflow)?;
+
+        let listing = &mut ctx.accounts.listing;
+        listing.channel = channel;
+        listing.owner = ctx.accounts.owner.key();
+        listing.page = page_index;
+        listing.listed_at = now;
+        listing.version = ACCOUNT_VERSION;
+
+        emit!(ChannelListed {
+            channel,
+            owner: listing.owner,
+            page: page_index,
+            timestamp: now,
+        });
+
+        msg!("Channel listed on directory page {}", page_index);
+
+        Ok(())
+    }
+
+    /// Remove a channel from the public directory (the owner who listed it)
+    /// Works after the channel itself was closed
+    pub fn delist_channel(ctx: Context<DelistChannel>) -> Result<()> {
+        let listing = &ctx.accounts.listing;
+        let page = &mut ctx.accounts.page;
+
+        let position = page
+            .channels
+            .iter()
+            .position(|channel| *channel == listing.channel)
+            .ok_or(MessagingError::InvalidDirectoryPage)?;
+        page.channels.swap_remove(position);
+
+        let directory = &mut ctx.accounts.directory;
+        directory.listed_count = directory
+            .listed_count
+            .checked_sub(1)
+            .ok_or(MessagingError::Overflow)?;
+
+        emit!(ChannelDelisted {
+            channel: listing.channel,
+            owner: listing.owner,
+            page: listing.page,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Channel delisted from directory page {}", listing.page);
+
+        Ok(())
+    }
+
+    /// Appoint the verification authority (config authority only)
+    pub fn set_verifier(ctx: Context<UpdateConfig>, verifier: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        config.verifier = verifier;
+
+        emit!(VerifierSet {
+            authority: config.authority,
+            verifier,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Channel verifier set to {}", verifier);
+
+        Ok(())
+    }
+
+    /// Grant a channel the verified badge (verifier only)
+    pub fn verify_channel(ctx: Context<SetChannelVerification>) -> Result<()> {
+        set_channel_verification(ctx, true)
+    }
+
+    /// Remove a channel's verified badge (verifier only)
+    pub fn revoke_verification(ctx: Context<SetChannelVerification>) -> Result<()> {
+        set_channel_verification(ctx, false)
+    }
+
+    /// Mark a channel as a suspected scam (config authority only)
+    /// `reason_code` is one of the `Report` reason constants; clients warn before
+    /// subscribing to a flagged channel
+    pub fn flag_suspicious_channel(
+        ctx: Context<SetChannelSuspicion>,
+        reason_code: u8,
+    ) -> Result<()> {
+        require!(
+            reason_code <= Report::OTHER,
+            MessagingError::InvalidReportReason
+        );
+        set_channel_suspicion(ctx, true, reason_code)
+    }
+
+    /// Lift a channel's suspicious flag (config authority only)
+    pub fn clear_suspicious_flag(ctx: Context<SetChannelSuspicion>) -> Result<()> {
+        set_channel_suspicion(ctx, false, 0)
+    }
+
+    /// Bring the config up to the current layout (config authority only)
+    /// New fields get their defaults
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        let config_info = ctx.accounts.config.to_account_info();
+        let new_len = 8 + ProgramConfig::INIT_SPACE;
+        let old_len = grow_for_migration(
+            &config_info,
+            ProgramConfig::DISCRIMINATOR,
+            &[8],
+            &ctx.accounts.authority,
+            new_len,
+            &ctx.accounts.system_program,
+            MessagingError::InvalidMigrationAccount,
+        )?;
+
+        let mut config = ProgramConfig::try_deserialize(&mut &config_info.try_borrow_data()?[..])?;
+        if config.verifier == Pubkey::default() {
+            config.verifier = config.authority;
+        }
+        if config.version < 4 {
+            // v3 -> v4: fee, pause and rate-limit settings
+            config.treasury = config.authority;
+            config.default_max_consecutive = DEFAULT_MAX_CONSECUTIVE;
+        }
+        config.version = ACCOUNT_VERSION;
+        config.try_serialize(&mut &mut config_info.try_borrow_mut_data()?[..])?;
+
+        msg!("Config migrated: {} -> {} bytes", old_len, new_len);
+
+        Ok(())
+    }
+
+    /// Update fee, pause and default rate-limit settings (config authority only)
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        treasury: Pubkey,
+        protocol_fee_bps: u16,
+        paused: bool,
+        default_max_consecutive: u32,
+        default_min_message_interval: i64,
+        default_min_broadcast_interval: i64,
+    ) -> Result<()> {
+        require!(protocol_fee_bps <= 10_000, MessagingError::InvalidConfig);
+        require!(
+            default_min_message_interval >= 0 && default_min_broadcast_interval >= 0,
+            MessagingError::InvalidConfig
+        );
+
+        let config = &mut ctx.accounts.config;
+
+        config.treasury = treasury;
+        config.protocol_fee_bps = protocol_fee_bps;
+        config.paused = paused;
+        config.default_max_consecutive = default_max_consecutive;
+        config.default_min_message_interval = default_min_message_interval;
+        config.default_min_broadcast_interval = default_min_broadcast_interval;
+
+        emit!(ConfigUpdated {
+            authority: config.authority,
+            treasury,
+            protocol_fee_bps,
+            paused,
+            default_max_consecutive,
+            default_min_message_interval,
+            default_min_broadcast_interval,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Config updated, paused: {}", paused);
+
+        Ok(())
+    }
+
+    /// Propose a new config authority; takes effect once they accept
+    pub fn propose_config_authority(
+        ctx: Context<UpdateConfig>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        config.pending_authority = new_authority;
+
+        emit!(ConfigAuthorityProposed {
+            authority: config.authority,
+            pending_authority: new_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Config authority handover proposed to {}", new_authority);
+
+        Ok(())
+    }
+
+    /// Accept a proposed handover and become the config authority
+    pub fn accept_config_authority(ctx: Context<AcceptConfigAuthority>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous_authority = config.authority;
+
+        config.authority = ctx.accounts.new_authority.key();
+        config.pending_authority = Pubkey::default();
+
+        emit!(ConfigAuthorityTransferred {
+            previous_authority,
+            new_authority: config.authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Config authority is now {}", config.authority);
+
+        Ok(())
+    }
+
+    /// Set the per-message protocol fee (config authority only)
+    pub fn set_message_fee(ctx: Context<UpdateConfig>, message_fee_lamports: u64) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        config.message_fee_lamports = message_fee_lamports;
+
+        emit!(MessageFeeSet {
+            authority: config.authority,
+            message_fee_lamports,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Message fee set to {} lamports", message_fee_lamports);
+
+        Ok(())
+    }
+
+    /// Move collected protocol fees from the treasury PDA to the config's treasury
+    /// The PDA keeps its rent-exempt minimum
+    pub fn withdraw_protocol_fees(ctx: Context<WithdrawProtocolFees>, amount: u64) -> Result<()> {
+        let vault = ctx.accounts.treasury_vault.to_account_info();
+        let available = vault
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(0));
+        require!(
+            amount > 0 && amount <= available,
+            MessagingError::InvalidAmount
+        );
+
+        transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: vault,
+                    to: ctx.accounts.treasury.to_account_info(),
+                },
+                &[&[b"treasury", &[ctx.bumps.treasury_vault]]],
+            ),
+            amount,
+        )?;
+
+        emit!(ProtocolFeesWithdrawn {
+            authority: ctx.accounts.authority.key(),
+            treasury: ctx.accounts.treasury.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Withdrew {} lamports of protocol fees", amount);
+
+        Ok(())
+    }
+
+    /// Send a message on behalf of `sender`, with fees paid by a relayer
+    /// The sender signs `relay_message(thread, index, content_hash)` off-chain; the
+    /// signature is checked by an Ed25519 instruction placed just before this one.
+    /// The sequential message index keeps a signature from being replayed.
+    pub fn send_message_relayed(
+        ctx: Context<SendMessageRelayed>,
+        message_index: u32,
+        encrypted_content: Vec<u8>,
+    ) -> Result<()> {
+        ensure_not_blocked(&ctx.accounts.block_by_a)?;
+        ensure_not_blocked(&ctx.accounts.block_by_b)?;
+
+        let thread = &mut ctx.accounts.message_thread;
+        let sender = ctx.accounts.sender.key();
+        let relayer = ctx.accounts.relayer.key();
+        let content_hash = hash(&encrypted_content).to_bytes();
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        let message = relay_message(&thread.key(), message_index, &content_hash);
+        let ed25519_ix = get_instruction_relative(-1, &ctx.accounts.instructions_sysvar)
+            .map_err(|_| MessagingError::InvalidSignature)?;
+        verify_ed25519_instruction(&ed25519_ix, &sender, &message)?;
+
+        thread.record_message(sender, message_index, content_hash, timestamp)?;
+
+        emit!(MessageSent {
+            thread: thread.key(),
+            sender,
+            message_index,
+            content_hash,
+            timestamp,
+            reply_to_index: None,
+            attachment: None,
+            device: None,
+        });
+        emit!(MessageRelayed {
+            thread: thread.key(),
+            sender,
+            relayer,
+            message_index,
+            timestamp,
+        });
+
+        msg!(
+            "Message {} sent by {} via {}",
+            message_index,
+            sender,
+            relayer
+        );
+        msg!("Thread messages: {}", thread.message_count);
+
+        // The relayer covers the fee
+        charge_message_fee(ctx.accounts, 1)?;
+
+        Ok(())
+    }
+
+    /// Delegate sending rights to a hot device key until `expires_at`
+    /// With `thread` set the key may only send in that thread, otherwise in any of
+    /// the owner's threads. Session keys can only send messages.
+    pub fn create_session_key(
+        ctx: Context<CreateSessionKey>,
+        thread: Option<Pubkey>,
+        expires_at: i64,
+    ) -> Result<()> {
+        let session = &mut ctx.accounts.session_key;
+        let owner = ctx.accounts.owner.key();
+        let device = ctx.accounts.device.key();
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        require!(device != owner, MessagingError::InvalidSessionKey);
+        require!(expires_at > timestamp, MessagingError::InvalidSessionKey);
+
+        session.owner = owner;
+        session.device = device;
+        session.thread = thread.unwrap_or_default();
+        session.expires_at = expires_at;
+        session.created_at = timestamp;
+        session.version = ACCOUNT_VERSION;
+
+        emit!(SessionKeyCreated {
+            owner,
+            device,
+            thread: session.thread,
+            expires_at,
+            timestamp,
+        });
+
+        msg!("Session key {} created for {}", device, owner);
+        msg!("Expires at {}", expires_at);
+
+        Ok(())
+    }
+
+    /// Revoke a session key and refund its rent to the owner
+    pub fn revoke_session_key(ctx: Context<RevokeSessionKey>) -> Result<()> {
+        let session = &ctx.accounts.session_key;
+
+        emit!(SessionKeyRevoked {
+            owner: session.owner,
+            device: session.device,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Session key {} revoked by {}",
+            session.device,
+            session.owner
+        );
+
+        Ok(())
+    }
+
+    /// Let subscribers comment on broadcasts, or stop new comments (owner only)
+    pub fn set_comments_enabled(ctx: Context<SetChannelMetadata>, enabled: bool) -> Result<()> {
+        let channel = &mut ctx.accounts.broadcast_channel;
+
+        channel.comments_enabled = enabled;
+
+        emit!(CommentsToggled {
+            channel: channel.key(),
+            enabled,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Comments on {} enabled: {}", channel.channel_name, enabled);
+
+        Ok(())
+    }
+
+    /// Comment on a broadcast as an active subscriber
+    /// The comment content is stored in transaction data; the per-broadcast
+    /// comment counter is created with the first comment
+    pub fn reply_to_broadcast(
+        ctx: Context<ReplyToBroadcast>,
+        broadcast_index: u32,
+        encrypted_content: Vec<u8>,
+    ) -> Result<()> {
+        let channel = &ctx.accounts.broadcast_channel;
+        let commenter = ctx.accounts.commenter.key();
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        require!(channel.comments_enabled, MessagingError::CommentsDisabled);
+        require!(
+            u64::from(broadcast_index) < channel.message_count,
+            MessagingError::InvalidMessageIndex
+        );
+        require!(
+            ctx.accounts.subscription.is_active(timestamp),
+            MessagingError::SubscriptionInactive
+        );
+
+        let comments = &mut ctx.accounts.broadcast_comments;
+        let comment_index = comments.comment_count;
+        let content_hash = hash(&encrypted_content).to_bytes();
+
+        comments.channel = channel.key();
+        comments.broadcast_index = broadcast_index;
+        comments.comment_count = comment_index
+            .checked_add(1)
+            .ok_or(MessagingError::Overflow)?;
+        comments.last_comment_at = timestamp;
+        comments.version = ACCOUNT_VERSION;
+
+        emit!(BroadcastCommentPosted {
+            channel: channel.key(),
+            broadcast_index,
+            commenter,
+            comment_index,
+            content_hash,
+            timestamp,
+        });
+
+        msg!(
+            "Comment {} on broadcast {} by {}",
+            comment_index,
+            broadcast_index,
+            commenter
+        );
+
+        Ok(())
+    }
+
+    /// Attach a poll to a broadcast or thread message
+    /// On a channel only the owner may create it, on a thread either participant.
+    /// `closes_at` of 0 keeps the poll open until `close_poll`.
+    pub fn create_poll(
+        ctx: Context<CreatePoll>,
+        message_index: u32,
+        options: Vec<String>,
+        closes_at: i64,
+    ) -> Result<()> {
+        let creator = ctx.accounts.creator.key();
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        require!(
+            (2..=MAX_POLL_OPTIONS).contains(&options.len())
+                && options
+                    .iter()
+                    .all(|option| !option.is_empty() && option.len() <= MAX_POLL_OPTION_LEN),
+            MessagingError::InvalidPollOptions
+        );
+        require!(
+            closes_at == 0 || closes_at > timestamp,
+            MessagingError::InvalidPollOptions
+        );
+
+        let target = read_message_host(&ctx.accounts.target, MessagingError::InvalidPollTarget)?;
+        match &target {
+            MessageHost::Channel(channel) => {
+                require_keys_eq!(channel.owner, creator, MessagingError::UnauthorizedSender);
+            }
+            MessageHost::Thread(thread) => {
+                require!(
+                    thread.is_participant(&creator),
+                    MessagingError::UnauthorizedSender
+                );
+            }
+        }
+        require!(
+            u64::from(message_index) < target.message_count(),
+            MessagingError::InvalidMessageIndex
+        );
+
+        let poll = &mut ctx.accounts.poll;
+
+        poll.target = ctx.accounts.target.key();
+        poll.kind = target.kind();
+        poll.message_index = message_index;
+        poll.creator = creator;
+        poll.tallies = vec![0; options.len()];
+        poll.options = options;
+        poll.closes_at = closes_at;
+        poll.closed = false;
+        poll.created_at = timestamp;
+        poll.version = ACCOUNT_VERSION;
+
+        emit!(PollCreated {
+            poll: poll.key(),
+            target: poll.target,
+            message_index,
+            creator,
+            option_count: poll.options.len() as u8,
+            closes_at,
+            timestamp,
+        });
+
+        msg!("Poll created on message {} by {}", message_index, creator);
+        msg!("Options: {}", poll.options.len());
+
+        Ok(())
+    }
+
+    /// Vote for one option of a poll
+    /// Channel polls take the voter's active subscription; thread polls are open
+    /// to both participants. The vote PDA allows one vote per voter.
+    pub fn cast_vote(ctx: Context<CastVote>, option: u8) -> Result<()> {
+        let voter = ctx.accounts.voter.key();
+        let timestamp = Clock::get()?.unix_timestamp;
+        let poll = &mut ctx.accounts.poll;
+
+        require!(poll.is_open(timestamp), MessagingError::PollClosed);
+        match read_message_host(&ctx.accounts.target, MessagingError::InvalidPollTarget)? {
+            MessageHost::Channel(channel) => {
+                let subscription = ctx
+                    .accounts
+                    .subscription
+                    .as_ref()
+                    .ok_or(MessagingError::SubscriptionInactive)?;
+                require!(
+                    subscription.belongs_to(&poll.target, channel.created_at)
+                        && subscription.subscriber == voter,
+                    MessagingError::SubscriptionMismatch
+                );
+                require!(
+                    subscription.is_active(timestamp),
+                    MessagingError::SubscriptionInactive
+                );
+            }
+            MessageHost::Thread(thread) => {
+                require!(
+                    thread.is_participant(&voter),
+                    MessagingError::UnauthorizedSender
+                );
+            }
+        }
+
+        let tally = poll
+            .tallies
+            .get_mut(option as usize)
+            .ok_or(MessagingError::InvalidPollOption)?;
+        *tally = tally.checked_add(1).ok_or(MessagingError::Overflow)?;
+
+        let vote = &mut ctx.accounts.poll_vote;
+
+        vote.poll = poll.key();
+        vote.voter = voter;
+        vote.option = option;
+        vote.voted_at = timestamp;
+        vote.version = ACCOUNT_VERSION;
+
+        emit!(VoteCast {
+            poll: poll.key(),
+            voter,
+            option,
+            tally: poll.tallies[option as usize],
+            timestamp,
+        });
+
+        msg!("Vote for option {} cast by {}", option, voter);
+
+        Ok(())
+    }
+
+    /// Stop accepting votes; the tallies stay on-chain (creator only)
+    pub fn close_poll(ctx: Context<ClosePoll>) -> Result<()> {
+        let poll = &mut ctx.accounts.poll;
+
+        require!(!poll.closed, MessagingError::PollClosed);
+        poll.closed = true;
+
+        emit!(PollClosed {
+            poll: poll.key(),
+            tallies: poll.tallies.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Poll {} closed", poll.key());
+
+        Ok(())
+    }
+
+    /// Upgrade a message record created by an older program version (sender only)
+    /// Records are fixed-size, so growing them clears the new `reply_to_index`
+    pub fn migrate_message_record(ctx: Context<MigrateMessageRecord>) -> Result<()> {
+        let record_info = ctx.accounts.message_record.to_account_info();
+        let new_len = 8 + MessageRecord::INIT_SPACE;
+        let old_len = grow_for_migration(
+            &record_info,
+            MessageRecord::DISCRIMINATOR,
+            &[44],
+            &ctx.accounts.sender,
+            new_len,
+            &ctx.accounts.system_program,
+            MessagingError::InvalidMigrationAccount,
+        )?;
+
+        let mut record = MessageRecord::try_deserialize(&mut &record_info.try_borrow_data()?[..])?;
+        record.version = ACCOUNT_VERSION;
+        record.try_serialize(&mut &mut record_info.try_borrow_mut_data()?[..])?;
+
+        emit!(MessageRecordMigrated {
+            record: record_info.key(),
+            thread: record.thread,
+            old_len: old_len as u32,
+            new_len: new_len as u32,
+            version: ACCOUNT_VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Message record migrated: {} -> {} bytes", old_len, new_len);
+
+        Ok(())
+    }
+
+    /// Make a channel invite-only, or open it to everyone again (owner only)
+    pub fn set_channel_private(ctx: Context<SetChannelMetadata>, private: bool) -> Result<()> {
+        let channel = &mut ctx.accounts.broadcast_channel;
+
+        channel.private = private;
+
+        emit!(ChannelPrivacySet {
+            channel: channel.key(),
+            private,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Channel {} private: {}", channel.channel_name, private);
+
+        Ok(())
+    }
+
+    /// Invite `invitee`, or anyone holding the code hashing to `code_hash`, to a channel
+    /// Pass exactly one of them. The invite admits `max_uses` subscribers (1 for a
+    /// single-use invite) until `expires_at` (0 = no expiry). The owner and admins
+    /// with the invite permission may create invites.
+    pub fn create_channel_invite(
+        ctx: Context<CreateChannelInvite>,
+        invitee: Option<Pubkey>,
+        code_hash: Option<[u8; 32]>,
+        max_uses: u32,
+        expires_at: i64,
+    ) -> Result<()> {
+        let channel = &ctx.accounts.broadcast_channel;
+        let creator = ctx.accounts.creator.key();
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        let is_admin = ctx
+            .accounts
+            .channel_admin
+            .as_ref()
+            .is_some_and(|admin| admin.has_permission(ChannelAdmin::INVITE));
+        require!(
+            creator == channel.owner || is_admin,
+            MessagingError::UnauthorizedSender
+        );
+        require!(
+            invitee.is_some() != code_hash.is_some(),
+            MessagingError::InvalidInvite
+        );
+        require!(max_uses > 0, MessagingError::InvalidInvite);
+        require!(
+            expires_at == 0 || expires_at > timestamp,
+            MessagingError::InvalidInvite
+        );
+
+        let invite = &mut ctx.accounts.channel_invite;
+
+        invite.channel = channel.key();
+        invite.invitee = invitee.unwrap_or_default();
+        invite.code_hash = code_hash.unwrap_or_default();
+        invite.max_uses = max_uses;
+        invite.uses = 0;
+        invite.expires_at = expires_at;
+        invite.created_by = creator;
+        invite.created_at = timestamp;
+        invite.version = ACCOUNT_VERSION;
+
+        emit!(ChannelInviteCreated {
+            channel: channel.key(),
+            invite: invite.key(),
+            invitee: invite.invitee,
+            max_uses,
+            expires_at,
+            timestamp,
+        });
+
+        msg!(
+            "Invite {} created for {}",
+            invite.key(),
+            channel.channel_name
+        );
+        msg!("Uses: {}", max_uses);
+
+        Ok(())
+    }
+
+    /// Revoke an invite and refund its rent to its creator
+    /// The creator or the channel owner may revoke it
+    pub fn revoke_invite(ctx: Context<RevokeInvite>) -> Result<()> {
+        let invite = &ctx.accounts.channel_invite;
+        let authority = ctx.accounts.authority.key();
+
+        require!(
+            authority == invite.created_by || authority == ctx.accounts.broadcast_channel.owner,
+            MessagingError::UnauthorizedSender
+        );
+
+        emit!(ChannelInviteRevoked {
+            channel: invite.channel,
+            invite: invite.key(),
+            uses: invite.uses,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Invite {} revoked by {}", invite.key(), authority);
+
+        Ok(())
+    }
+
+    /// Ban a wallet from a channel, closing its subscription if it has one
+    /// The owner and admins with the moderate permission may ban; the ban entry
+    /// keeps the wallet from subscribing again until `unban_subscriber`
+    pub fn ban_subscriber(ctx: Context<BanSubscriber>) -> Result<()> {
+        let channel = &mut ctx.accounts.broadcast_channel;
+        let moderator = ctx.accounts.moderator.key();
+        let subscriber = ctx.accounts.subscriber.key();
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        let is_admin = ctx
+            .accounts
+            .channel_admin
+            .as_ref()
+            .is_some_and(|admin| admin.has_permission(ChannelAdmin::MODERATE));
+        require!(
+            moderator == channel.owner || is_admin,
+            MessagingError::UnauthorizedSender
+        );
+        require!(subscriber != channel.owner, MessagingError::CannotBanOwner);
+
+        // The subscription constraint closes it; keep the count in step
+        let counted = ctx
+            .accounts
+            .subscription
+            .as_ref()
+            .is_some_and(|subscription| subscription.is_active(timestamp));
+        if counted {
+            channel.subscriber_count = channel
+                .subscriber_count
+                .checked_sub(1)
+                .ok_or(MessagingError::Overflow)?;
+        }
+
+        let ban = &mut ctx.accounts.ban_entry;
+
+        ban.channel = channel.key();
+        ban.subscriber = subscriber;
+        ban.banned_by = moderator;
+        ban.banned_at = timestamp;
+        ban.version = ACCOUNT_VERSION;
+
+        emit!(SubscriberBanned {
+            channel: channel.key(),
+            subscriber,
+            moderator,
+            subscriber_count: channel.subscriber_count,
+            timestamp,
+        });
+
+        msg!("{} banned from {}", subscriber, channel.channel_name);
+        msg!("Total subscribers: {}", channel.subscriber_count);
+
+        Ok(())
+    }
+
+    /// Lift a ban so the wallet may subscribe again
+    pub fn unban_subscriber(ctx: Context<UnbanSubscriber>) -> Result<()> {
+        let channel = &ctx.accounts.broadcast_channel;
+        let moderator = ctx.accounts.moderator.key();
+
+        let is_admin = ctx
+            .accounts
+            .channel_admin
+            .as_ref()
+            .is_some_and(|admin| admin.has_permission(ChannelAdmin::MODERATE));
+        require!(
+            moderator == channel.owner || is_admin,
+            MessagingError::UnauthorizedSender
+        );
+
+        emit!(SubscriberUnbanned {
+            channel: channel.key(),
+            subscriber: ctx.accounts.ban_entry.subscriber,
+            moderator,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "{} unbanned from {}",
+            ctx.accounts.ban_entry.subscriber,
+            channel.channel_name
+        );
+
+        Ok(())
+    }
+
+    /// Report a broadcast or thread message to the protocol moderators
+    /// The reporter locks `REPORT_DEPOSIT_LAMPORTS` in the report, returned if the
+    /// report is upheld and forfeited to the treasury if it is dismissed
+    pub fn report_content(
+        ctx: Context<ReportContent>,
+        message_index: u32,
+        reason_code: u8,
+    ) -> Result<()> {
+        require!(
+            reason_code <= Report::OTHER,
+            MessagingError::InvalidReportReason
+        );
+
+        let target = read_message_host(&ctx.accounts.target, MessagingError::InvalidReportTarget)?;
+        require!(
+            u64::from(message_index) < target.message_count(),
+            MessagingError::InvalidMessageIndex
+        );
+
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.reporter.to_account_info(),
+                    to: ctx.accounts.report.to_account_info(),
+                },
+            ),
+            REPORT_DEPOSIT_LAMPORTS,
+        )?;
+
+        let report = &mut ctx.accounts.report;
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        report.reporter = ctx.accounts.reporter.key();
+        report.target = ctx.accounts.target.key();
+        report.target_kind = target.kind();
+        report.message_index = message_index;
+        report.reason_code = reason_code;
+        report.deposit = REPORT_DEPOSIT_LAMPORTS;
+        report.created_at = timestamp;
+        report.version = ACCOUNT_VERSION;
+
+        emit!(ContentReported {
+            report: report.key(),
+            target: report.target,
+            message_index,
+            reporter: report.reporter,
+            reason_code,
+            timestamp,
+        });
+
+        msg!("Message {} reported by {}", message_index, report.reporter);
+        msg!("Reason: {}", reason_code);
+
+        Ok(())
+    }
+
+    /// Close a report (config authority only)
+    /// An upheld report refunds the deposit; a dismissed one sends it to the treasury.
+    /// The rent always goes back to the reporter.
+    pub fn resolve_report(ctx: Context<ResolveReport>, upheld: bool) -> Result<()> {
+        let report = &ctx.accounts.report;
+
+        if !upheld {
+            let deposit = report.deposit;
+            move_lamports(&report.to_account_info(), &ctx.accounts.treasury, deposit)?;
+        }
+
+        emit!(ReportResolved {
+            report: report.key(),
+            target: report.target,
+            message_index: report.message_index,
+            upheld,
+            moderator: ctx.accounts.authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Report {} resolved, upheld: {}", report.key(), upheld);
+
+        Ok(())
+    }
+
+    /// Queue a broadcast to be published at `release_at`, and again every day, week
+    /// or month after that for a recurring `recurrence` (see `ScheduledBroadcast`)
+    /// Only the SHA-256 hash of the encrypted content is stored; the protocol fee is
+    /// charged now, once, since the publish crank may be signed by anyone
+    pub fn schedule_broadcast(
+        ctx: Context<ScheduleBroadcast>,
+        release_at: i64,
+        content_hash: [u8; 32],
+        recurrence: u8,
+    ) -> Result<()> {
+        require!(
+            recurrence <= ScheduledBroadcast::MONTHLY,
+            MessagingError::InvalidRecurrence
+        );
+
+        let channel = &ctx.accounts.broadcast_channel;
+        let sender = ctx.accounts.sender.key();
+
+        let is_admin = ctx
+            .accounts
+            .channel_admin
+            .as_ref()
+            .is_some_and(|admin| admin.has_permission(ChannelAdmin::BROADCAST));
+        require!(
+            sender == channel.owner || is_admin,
+            MessagingError::NotChannelOwner
+        );
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        require!(release_at > timestamp, MessagingError::InvalidReleaseTime);
+
+        let scheduled = &mut ctx.accounts.scheduled_broadcast;
+        scheduled.channel = channel.key();
+        scheduled.author = sender;
+        scheduled.content_hash = content_hash;
+        scheduled.release_at = release_at;
+        scheduled.created_at = timestamp;
+        scheduled.recurrence = recurrence;
+        scheduled.version = ACCOUNT_VERSION;
+
+        let index = &mut ctx.accounts.schedule_index;
+        if index.version == 0 {
+            index.channel = channel.key();
+            index.version = ACCOUNT_VERSION;
+        }
+        index.upsert(scheduled.key(), release_at)?;
+
+        emit!(BroadcastScheduled {
+            channel: scheduled.channel,
+            scheduled_broadcast: scheduled.key(),
+            author: sender,
+            content_hash,
+            release_at,
+            recurrence,
+            timestamp,
+        });
+
+        msg!("Broadcast scheduled for {}", release_at);
+
+        charge_message_fee(ctx.accounts, 1)?;
+
+        Ok(())
+    }
+
+    /// Publish a scheduled broadcast once `release_at` has passed
+    /// Anyone may crank this by revealing content matching the stored hash. The
+    /// author must still be the owner or a broadcasting admin. A recurring broadcast
+    /// moves on to its next occurrence after now; otherwise the scheduled account is
+    /// closed and its rent goes back to the author
+    pub fn publish_scheduled_broadcast(
+        ctx: Context<PublishScheduledBroadcast>,
+        encrypted_content: Vec<u8>,
+    ) -> Result<()> {
+        let scheduled = &ctx.accounts.scheduled_broadcast;
+        let content_hash = hash(&encrypted_content).to_bytes();
+        require!(
+            content_hash == scheduled.content_hash,
+            MessagingError::ContentHashMismatch
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= scheduled.release_at,
+            MessagingError::BroadcastNotReleased
+        );
+
+        let channel = &mut ctx.accounts.broadcast_channel;
+        let is_admin = ctx
+            .accounts
+            .channel_admin
+            .as_ref()
+            .is_some_and(|admin| admin.has_permission(ChannelAdmin::BROADCAST));
+        let authorized = scheduled.author == channel.owner || is_admin;
+        let message_index =
+            u32::try_from(channel.message_count).map_err(|_| MessagingError::Overflow)?;
+        channel.record_broadcast(authorized, message_index, now)?;
+
+        emit!(BroadcastSent {
+            channel: channel.key(),
+            sender: scheduled.author,
+            message_index,
+            content_hash,
+            burn_after_seen: false,
+            timestamp: now,
+            reply_to_index: None,
+            attachment: None,
+            device: None,
+        });
+
+        msg!("Scheduled broadcast {} published", message_index);
+        msg!("Total broadcasts: {}", channel.message_count);
+
+        let scheduled = &mut ctx.accounts.scheduled_broadcast;
+        let index = &mut ctx.accounts.schedule_index;
+        let Some(next_release) = scheduled.next_release(now)? else {
+            index.remove(&scheduled.key());
+            return scheduled.close(ctx.accounts.author.to_account_info());
+        };
+        scheduled.release_at = next_release;
+        index.upsert(scheduled.key(), next_release)?;
+
+        emit!(BroadcastScheduled {
+            channel: scheduled.channel,
+            scheduled_broadcast: scheduled.key(),
+            author: scheduled.author,
+            content_hash,
+            release_at: next_release,
+            recurrence: scheduled.recurrence,
+            timestamp: now,
+        });
+
+        msg!("Next occurrence at {}", next_release);
+
+        Ok(())
+    }
+
+    /// Drop a scheduled broadcast before it is published (author or channel owner)
+    /// The scheduled account's rent goes back to the author; the fee is not refunded
+    pub fn cancel_scheduled_broadcast(ctx: Context<CancelScheduledBroadcast>) -> Result<()> {
+        let scheduled = &ctx.accounts.scheduled_broadcast;
+        let signer = ctx.accounts.signer.key();
+        require!(
+            signer == scheduled.author || signer == ctx.accounts.broadcast_channel.owner,
+            MessagingError::UnauthorizedSender
+        );
+
+        ctx.accounts.schedule_index.remove(&scheduled.key());
+
+        emit!(ScheduledBroadcastCancelled {
+            channel: scheduled.channel,
+            scheduled_broadcast: scheduled.key(),
+            cancelled_by: signer,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Scheduled broadcast {} cancelled", scheduled.key());
+
+        Ok(())
+    }
+
+    /// Pin a thread or channel message, with an optional note of up to 64 bytes
+    /// of UTF-8 shown on the pinned banner; an empty note leaves it out
+    /// Either participant pins in a thread; in a channel the owner or an admin with
+    /// the moderate permission does
+    pub fn pin_message(ctx: Context<PinMessage>, message_index: u32, note: String) -> Result<()> {
+        require!(note.len() <= 64, MessagingError::NoteTooLong);
+
+        let host = read_message_host(&ctx.accounts.target, MessagingError::InvalidPinTarget)?;
+        require!(
+            can_pin(
+                &host,
+                &ctx.accounts.signer.key(),
+                ctx.accounts.channel_admin.as_ref()
+            ),
+            MessagingError::UnauthorizedSender
+        );
+        require!(
+            u64::from(message_index) < host.message_count(),
+            MessagingError::InvalidMessageIndex
+        );
+
+        let pins = &mut ctx.accounts.pinned_messages;
+        if pins.version == 0 {
+            pins.target = ctx.accounts.target.key();
+            pins.target_kind = host.kind();
+            pins.version = ACCOUNT_VERSION;
+        }
+        require!(
+            !pins.indices.contains(&message_index),
+            MessagingError::AlreadyPinned
+        );
+        require!(
+            pins.indices.len() < MAX_PINNED_MESSAGES,
+            MessagingError::TooManyPins
+        );
+        let mut padded = [0u8; 64];
+        padded[..note.len()].copy_from_slice(note.as_bytes());
+        pins.indices.push(message_index);
+        pins.notes.push(padded);
+
+        emit!(MessagePinned {
+            target: pins.target,
+            message_index,
+            note: padded,
+            pinned_by: ctx.accounts.signer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Message {} pinned", message_index);
+        msg!("Pinned messages: {}", pins.indices.len());
+
+        Ok(())
+    }
+
+    /// Unpin a message, under the same rules as `pin_message`
+    pub fn unpin_message(ctx: Context<UnpinMessage>, message_index: u32) -> Result<()> {
+        let host = read_message_host(&ctx.accounts.target, MessagingError::InvalidPinTarget)?;
+        require!(
+            can_pin(
+                &host,
+                &ctx.accounts.signer.key(),
+                ctx.accounts.channel_admin.as_ref()
+            ),
+            MessagingError::UnauthorizedSender
+        );
+
+        let pins = &mut ctx.accounts.pinned_messages;
+        let position = pins
+            .indices
+            .iter()
+            .position(|&index| index == message_index)
+            .ok_or(MessagingError::NotPinned)?;
+        pins.indices.remove(position);
+        pins.notes.remove(position);
+
+        emit!(MessageUnpinned {
+            target: pins.target,
+            message_index,
+            unpinned_by: ctx.accounts.signer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Message {} unpinned", message_index);
+
+        Ok(())
+    }
+
+    /// Register a device key under the caller's profile
+    /// Senders encrypt to every registered device key, so each device can decrypt
+    pub fn register_device(ctx: Context<RegisterDevice>, device_key: [u8; 32]) -> Result<()> {
+        let device = &mut ctx.accounts.device;
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        device.owner = ctx.accounts.owner.key();
+        device.device_key = device_key;
+        device.last_sync_counter = 0;
+        device.registered_at = timestamp;
+        device.last_synced_at = timestamp;
+        device.version = ACCOUNT_VERSION;
+
+        emit!(DeviceRegistered {
+            owner: device.owner,
+            device: device.key(),
+            device_key,
+            timestamp,
+        });
+
+        msg!("Device registered for {}", device.owner);
+
+        Ok(())
+    }
+
+    /// Advance a device's sync counter after it has reconciled read state
+    /// The counter only moves forward, so a stale device cannot roll it back
+    pub fn sync_device(ctx: Context<SyncDevice>, sync_counter: u64) -> Result<()> {
+        let device = &mut ctx.accounts.device;
+        require!(
+            sync_counter > device.last_sync_counter,
+            MessagingError::StaleSyncCounter
+        );
+
+        device.last_sync_counter = sync_counter;
+        device.last_synced_at = Clock::get()?.unix_timestamp;
+
+        emit!(DeviceSynced {
+            owner: device.owner,
+            device: device.key(),
+            sync_counter,
+            timestamp: device.last_synced_at,
+        });
+
+        msg!("Device synced to {}", sync_counter);
+
+        Ok(())
+    }
+
+    /// Remove a device and refund its rent
+    pub fn revoke_device(ctx: Context<RevokeDevice>) -> Result<()> {
+        let device = &ctx.accounts.device;
+
+        emit!(DeviceRevoked {
+            owner: device.owner,
+            device: device.key(),
+            device_key: device.device_key,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Device revoked for {}", device.owner);
+
+        Ok(())
+    }
+
+    /// Post the channel key, wrapped to one subscriber's X25519 key (owner only)
+    /// The wrap is tagged with the channel's current `key_epoch`; posting again
+    /// after a rekey overwrites it
+    pub fn post_channel_key_wrap(
+        ctx: Context<PostChannelKeyWrap>,
+        wrapped_key: Vec<u8>,
+    ) -> Result<()> {
+        require!(
+            !wrapped_key.is_empty() && wrapped_key.len() <= MAX_CHANNEL_KEY_WRAP_LEN,
+            MessagingError::InvalidKeyWrap
+        );
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.subscription.is_active(timestamp),
+            MessagingError::SubscriptionInactive
+        );
+
+        let channel = &ctx.accounts.broadcast_channel;
+        let wrap = &mut ctx.accounts.key_wrap;
+        wrap.channel = channel.key();
+        wrap.subscriber = ctx.accounts.subscription.subscriber;
+        wrap.epoch = channel.key_epoch;
+        wrap.wrapped_key = wrapped_key;
+        wrap.updated_at = timestamp;
+        wrap.version = ACCOUNT_VERSION;
+
+        emit!(ChannelKeyWrapPosted {
+            channel: wrap.channel,
+            subscriber: wrap.subscriber,
+            epoch: wrap.epoch,
+            timestamp,
+        });
+
+        msg!(
+            "Channel key epoch {} wrapped for {}",
+            wrap.epoch,
+            wrap.subscriber
+        );
+
+        Ok(())
+    }
+
+    /// Start a new channel key epoch (owner only)
+    /// Existing wraps go stale; the owner re-posts wraps for the remaining
+    /// subscribers, so departed ones never receive the new key
+    pub fn rekey_channel(ctx: Context<SetChannelMetadata>) -> Result<()> {
+        let channel = &mut ctx.accounts.broadcast_channel;
+
+        channel.key_epoch = channel
+            .key_epoch
+            .checked_add(1)
+            .ok_or(MessagingError::Overflow)?;
+
+        emit!(ChannelRekeyed {
+            channel: channel.key(),
+            epoch: channel.key_epoch,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Channel rekeyed to epoch {}", channel.key_epoch);
+
+        Ok(())
+    }
+
+    /// Close a wrap from an earlier epoch and refund its rent (owner only)
+    pub fn close_channel_key_wrap(ctx: Context<CloseChannelKeyWrap>) -> Result<()> {
+        let wrap = &ctx.accounts.key_wrap;
+        require!(
+            wrap.epoch < ctx.accounts.broadcast_channel.key_epoch,
+            MessagingError::KeyWrapCurrent
+        );
+
+        msg!("Stale key wrap for {} closed", wrap.subscriber);
+
+        Ok(())
+    }
+
+    /// Set the fee non-contacts pay to open a thread with the owner
+    /// The fee is held in escrow until the owner replies or the timeout passes
+    pub fn set_dm_fee(ctx: Context<UpdateProfile>, lamports: u64) -> Result<()> {
+        let profile = &mut ctx.accounts.user_profile;
+        let now = Clock::get()?.unix_timestamp;
+
+        profile.dm_fee_lamports = lamports;
+        profile.updated_at = now;
+
+        emit!(DmFeeSet {
+            profile: profile.key(),
+            owner: profile.owner,
+            lamports,
+            timestamp: now,
+        });
+
+        msg!("DM fee set to {} lamports", lamports);
+
+        Ok(())
+    }
+
+    /// Settle a DM fee escrow (permissionless)
+    /// Accepted threads pay the fee to the recipient. Rejected threads, and threads
+    /// still pending (or closed) after `DM_FEE_TIMEOUT_SECONDS`, refund the sender.
+    /// Escrow rent always goes back to the sender.
+    pub fn claim_dm_fee(ctx: Context<ClaimDmFee>) -> Result<()> {
+        let escrow = &ctx.accounts.dm_escrow;
+        let thread_info = &ctx.accounts.message_thread;
+        let now = Clock::get()?.unix_timestamp;
+        let timed_out = now >= escrow.expires_at;
+
+        let status = if thread_info.data_is_empty() {
+            None
+        } else {
+            let thread = MessageThread::try_deserialize(&mut &thread_info.try_borrow_data()?[..])?;
+            Some(thread.status)
+        };
+        let released = match status {
+            Some(ThreadStatus::Accepted) => true,
+            Some(ThreadStatus::Rejected) => false,
+            Some(ThreadStatus::Pending) | None => {
+                require!(timed_out, MessagingError::DepositUnsettled);
+                false
+            }
+        };
+
+        let amount = escrow.amount;
+        if released {
+            move_lamports(
+                &escrow.to_account_info(),
+                &ctx.accounts.recipient.to_account_info(),
+                amount,
+            )?;
+        }
+
+        emit!(DmFeeSettled {
+            thread: escrow.thread,
+            sender: escrow.sender,
+            recipient: escrow.recipient,
+            amount,
+            released,
+            timestamp: now,
+        });
+
+        msg!(
+            "DM fee of {} lamports {}",
+            amount,
+            if released { "released" } else { "refunded" }
+        );
+
+        Ok(())
+    }
+
+    /// Grow a profile to the current layout (owner only)
+    pub fn migrate_profile(ctx: Context<MigrateProfile>) -> Result<()> {
+        let profile_info = ctx.accounts.user_profile.to_account_info();
+        let new_len = 8 + UserProfile::INIT_SPACE;
+        // Profiles are fixed-size and only gained trailing fields, which start cleared
+        let old_len = grow_for_migration(
+            &profile_info,
+            UserProfile::DISCRIMINATOR,
+            &[8],
+            &ctx.accounts.owner,
+            new_len,
+            &ctx.accounts.system_program,
+            MessagingError::InvalidMigrationAccount,
+        )?;
+
+        let mut profile = UserProfile::try_deserialize(&mut &profile_info.try_borrow_data()?[..])?;
+        profile.version = ACCOUNT_VERSION;
+        profile.try_serialize(&mut &mut profile_info.try_borrow_mut_data()?[..])?;
+
+        emit!(ProfileMigrated {
+            profile: profile_info.key(),
+            owner: profile.owner,
+            old_len: old_len as u32,
+            new_len: new_len as u32,
+            version: ACCOUNT_VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Profile migrated: {} -> {} bytes", old_len, new_len);
+
+        Ok(())
+    }
+
+    /// Lock funds for a deal with the other participant of a thread
+    /// The opener deposits `amount` right away; a default `mint` escrows SOL. With
+    /// a default `arbiter` the deal settles by mutual consent: the depositor
+    /// releases, or the beneficiary refunds. An arbiter may do either.
+    pub fn open_escrow(
+        ctx: Context<OpenEscrow>,
+        escrow_id: u64,
+        amount: u64,
+        mint: Pubkey,
+        arbiter: Pubkey,
+        terms_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(amount > 0, MessagingError::InvalidAmount);
+
+        let accounts = &ctx.accounts;
+        let thread = &accounts.message_thread;
+        let depositor = accounts.depositor.key();
+        require!(
+            thread.lifecycle != ThreadLifecycle::Closed,
+            MessagingError::ThreadClosed
+        );
+        require!(
+            depositor == thread.participant_a || depositor == thread.participant_b,
+            MessagingError::UnauthorizedSender
+        );
+        let beneficiary = thread.other_participant(&depositor);
+        require!(
+            arbiter != depositor && arbiter != beneficiary,
+            MessagingError::InvalidArbiter
+        );
+
+        let escrow_key = accounts.thread_escrow.key();
+        if mint == Pubkey::default() {
+            transfer(
+                CpiContext::new(
+                    accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: accounts.depositor.to_account_info(),
+                        to: accounts.thread_escrow.to_account_info(),
+                    },
+                ),
+                amount,
+            )?;
+        } else {
+            let (Some(mint_account), Some(from), Some(to), Some(token_program)) = (
+                &accounts.mint,
+                &accounts.depositor_token_account,
+                &accounts.escrow_token_account,
+                &accounts.token_program,
+            ) else {
+                return err!(MessagingError::InvalidPaymentAccount);
+            };
+            require_keys_eq!(
+                mint_account.key(),
+                mint,
+                MessagingError::InvalidPaymentAccount
+            );
+            require_keys_eq!(
+                to.key(),
+                get_associated_token_address_with_program_id(
+                    &escrow_key,
+                    &mint,
+                    &token_program.key()
+                ),
+                MessagingError::InvalidPaymentAccount
+            );
+
+            transfer_checked(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    TransferChecked {
+                        from: from.to_account_info(),
+                        mint: mint_account.to_account_info(),
+                        to: to.to_account_info(),
+                        authority: accounts.depositor.to_account_info(),
+                    },
+                ),
+                amount,
+                mint_account.decimals,
+            )?;
+        }
+
+        let thread_key = thread.key();
+        let escrow = &mut ctx.accounts.thread_escrow;
+        let now = Clock::get()?.unix_timestamp;
+
+        escrow.thread = thread_key;
+        escrow.escrow_id = escrow_id;
+        escrow.depositor = depositor;
+        escrow.beneficiary = beneficiary;
+        escrow.arbiter = arbiter;
+        escrow.amount = amount;
+        escrow.mint = mint;
+        escrow.terms_hash = terms_hash;
+        escrow.created_at = now;
+        escrow.version = ACCOUNT_VERSION;
+
+        emit!(EscrowOpened {
+            escrow: escrow_key,
+            thread: thread_key,
+            depositor,
+            beneficiary,
+            arbiter,
+            amount,
+            mint,
+            terms_hash,
+            timestamp: now,
+        });
+
+        msg!("Escrow {} opened for {}", escrow_id, amount);
+
+        Ok(())
+    }
+
+    /// Pay an escrow out to the beneficiary (depositor or arbiter)
+    pub fn release_escrow(ctx: Context<SettleEscrow>) -> Result<()> {
+        let escrow = &ctx.accounts.thread_escrow;
+        let authority = ctx.accounts.authority.key();
+        require!(
+            authority == escrow.depositor || escrow.is_arbiter(&authority),
+            MessagingError::UnauthorizedSender
+        );
+
+        let beneficiary = ctx.accounts.beneficiary.to_account_info();
+        pay_out_thread_escrow(ctx.accounts, ctx.bumps.thread_escrow, &beneficiary)?;
+
+        emit!(EscrowSettled {
+            escrow: escrow.key(),
+            thread: escrow.thread,
+            recipient: escrow.beneficiary,
+            amount: escrow.amount,
+            mint: escrow.mint,
+            released: true,
+            settled_by: authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Escrow {} released", escrow.escrow_id);
+
+        ctx.accounts
+            .thread_escrow
+            .close(ctx.accounts.depositor.to_account_info())
+    }
+
+    /// Return an escrow to the depositor (beneficiary or arbiter)
+    pub fn refund_escrow(ctx: Context<SettleEscrow>) -> Result<()> {
+        let escrow = &ctx.accounts.thread_escrow;
+        let authority = ctx.accounts.authority.key();
+        require!(
+            authority == escrow.beneficiary || escrow.is_arbiter(&authority),
+            MessagingError::UnauthorizedSender
+        );
+
+        let depositor = ctx.accounts.depositor.to_account_info();
+        pay_out_thread_escrow(ctx.accounts, ctx.bumps.thread_escrow, &depositor)?;
+
+        emit!(EscrowSettled {
+            escrow: escrow.key(),
+            thread: escrow.thread,
+            recipient: escrow.depositor,
+            amount: escrow.amount,
+            mint: escrow.mint,
+            released: false,
+            settled_by: authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Escrow {} refunded", escrow.escrow_id);
+
+        ctx.accounts.thread_escrow.close(depositor)
+    }
+
+    /// Create the stats account of a channel for UTC day `day` (days since the epoch)
+    /// Anyone may pay for it, typically the owner ahead of time; sends and
+    /// (un)subscribes passing it that day are then counted
+    pub fn open_channel_stats(ctx: Context<OpenChannelStats>, day: u32) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(day >= stats_day(now)?, MessagingError::InvalidStatsDay);
+
+        let stats = &mut ctx.accounts.channel_stats;
+        stats.channel = ctx.accounts.broadcast_channel.key();
+        stats.day = day;
+        stats.broadcast_count = 0;
+        stats.subscriber_delta = 0;
+        stats.payer = ctx.accounts.payer.key();
+        stats.version = ACCOUNT_VERSION;
+
+        msg!("Stats opened for day {}", day);
+
+        Ok(())
+    }
+
+    /// Close the stats account of a past day and refund whoever paid for it
+    pub fn close_stale_stats(ctx: Context<CloseStaleStats>) -> Result<()> {
+        let stats = &ctx.accounts.channel_stats;
+        let now = Clock::get()?.unix_timestamp;
+        require!(stats.day < stats_day(now)?, MessagingError::InvalidStatsDay);
+
+        emit!(ChannelStatsClosed {
+            channel: stats.channel,
+            day: stats.day,
+            broadcast_count: stats.broadcast_count,
+            subscriber_delta: stats.subscriber_delta,
+            timestamp: now,
+        });
+
+        msg!("Stats for day {} closed", stats.day);
+
+        Ok(())
+    }
+
+    /// Create the caller's notification inbox
+    pub fn create_notification_inbox(ctx: Context<CreateNotificationInbox>) -> Result<()> {
+        let inbox = &mut ctx.accounts.notification_inbox;
+
+        inbox.owner = ctx.accounts.owner.key();
+        inbox.delivered_count = 0;
+        inbox.records = Vec::new();
+        inbox.version = ACCOUNT_VERSION;
+
+        msg!("Notification inbox created for {}", inbox.owner);
+
+        Ok(())
+    }
+
+    /// Let a program deliver notifications for free (config authority only)
+    pub fn approve_notifier(ctx: Context<ApproveNotifier>, program: Pubkey) -> Result<()> {
+        let notifier = &mut ctx.accounts.notifier;
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        notifier.program = program;
+        notifier.approved_by = ctx.accounts.authority.key();
+        notifier.approved_at = timestamp;
+        notifier.version = ACCOUNT_VERSION;
+
+        emit!(NotifierApproved {
+            program,
+            approved_by: notifier.approved_by,
+            timestamp,
+        });
+
+        msg!("Notifier {} approved", program);
+
+        Ok(())
+    }
+
+    /// Remove a program from the notifier whitelist (config authority only)
+    pub fn revoke_notifier(ctx: Context<RevokeNotifier>) -> Result<()> {
+        emit!(NotifierRevoked {
+            program: ctx.accounts.notifier.program,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Notifier {} revoked", ctx.accounts.notifier.program);
+
+        Ok(())
+    }
+
+    /// Append a notification to a user's inbox
+    /// A whitelisted program calls this through CPI, signing as its
+    /// `["notifier"]` PDA, and is recorded as the source. Anyone else pays
+    /// `NOTIFICATION_FEE_LAMPORTS` to the treasury and is recorded by its own key.
+    /// The inbox keeps the latest `MAX_NOTIFICATIONS` records.
+    pub fn deliver_notification(
+        ctx: Context<DeliverNotification>,
+        kind: u16,
+        payload_hash: [u8; 32],
+    ) -> Result<()> {
+        let sender = ctx.accounts.sender.key();
+        let (source, verified) = match &ctx.accounts.notifier {
+            Some(notifier) => {
+                let (expected, _) = Pubkey::find_program_address(&[b"notifier"], &notifier.program);
+                require_keys_eq!(sender, expected, MessagingError::NotifierMismatch);
+                (notifier.program, true)
+            }
+            None => {
+                pay_protocol_fee(
+                    &ctx.accounts.sender,
+                    ctx.accounts.treasury.as_ref(),
+                    ctx.accounts
+                        .system_program
+                        .as_ref()
+                        .map(|p| p.to_account_info()),
+                    NOTIFICATION_FEE_LAMPORTS,
+                )?;
+                (sender, false)
+            }
+        };
+
+        let inbox = &mut ctx.accounts.notification_inbox;
+        let timestamp = Clock::get()?.unix_timestamp;
+        let record = NotificationRecord {
+            source,
+            verified,
+            kind,
+            payload_hash,
+            delivered_at: timestamp,
+        };
+        let index = inbox.delivered_count;
+        let slot = (index % MAX_NOTIFICATIONS as u64) as usize;
+        if slot < inbox.records.len() {
+            inbox.records[slot] = record;
+        } else {
+            inbox.records.push(record);
+        }
+        inbox.delivered_count = index.checked_add(1).ok_or(MessagingError::Overflow)?;
+
+        emit!(NotificationDelivered {
+            owner: inbox.owner,
+            index,
+            source,
+            verified,
+            kind,
+            payload_hash,
+            timestamp,
+        });
+
+        msg!("Notification {} delivered to {}", index, inbox.owner);
+
+        Ok(())
+    }
+
+    /// Commit a Merkle root of the channel's current subscribers (owner only)
+    /// Leaves are `snapshot_leaf(subscriber, subscribed_at)` over the channel's
+    /// subscription PDAs, so anyone can rebuild the tree to check the root
+    pub fn snapshot_subscribers(
+        ctx: Context<SnapshotSubscribers>,
+        snapshot_id: u64,
+        merkle_root: [u8; 32],
+    ) -> Result<()> {
+        let channel = &ctx.accounts.broadcast_channel;
+        let snapshot = &mut ctx.accounts.snapshot;
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        snapshot.channel = channel.key();
+        snapshot.snapshot_id = snapshot_id;
+        snapshot.merkle_root = merkle_root;
+        snapshot.subscriber_count = channel.subscriber_count;
+        snapshot.created_at = timestamp;
+        snapshot.version = ACCOUNT_VERSION;
+
+        emit!(SubscribersSnapshotted {
+            channel: snapshot.channel,
+            snapshot: snapshot.key(),
+            snapshot_id,
+            merkle_root,
+            subscriber_count: snapshot.subscriber_count,
+            timestamp,
+        });
+
+        msg!(
+            "Snapshot {} of {} subscribers",
+            snapshot_id,
+            snapshot.subscriber_count
+        );
+
+        Ok(())
+    }
+
+    /// Check that a subscriber is in a snapshot, failing otherwise
+    /// Airdrop programs call this through CPI before paying out a claim
+    pub fn verify_snapshot_claim(
+        ctx: Context<VerifySnapshotClaim>,
+        subscriber: Pubkey,
+        subscribed_at: i64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(
+            proof.len() <= MAX_SNAPSHOT_PROOF_LEN,
+            MessagingError::InvalidMerkleProof
+        );
+        let snapshot = &ctx.accounts.snapshot;
+        let leaf = snapshot_leaf(&subscriber, subscribed_at);
+        require!(
+            merkle_root_from_proof(leaf, &proof) == snapshot.merkle_root,
+            MessagingError::InvalidMerkleProof
+        );
+
+        msg!("{} is in snapshot {}", subscriber, snapshot.snapshot_id);
+
+        Ok(())
+    }
+
+    /// Close a subscription whose channel has been closed, refunding its rent
+    /// This includes subscriptions older than a channel re-created under the same
+    /// name, which no instruction accepts for the new channel.
+    pub fn close_orphaned_subscription(ctx: Context<CloseOrphanedSubscription>) -> Result<()> {
+        let subscription = &ctx.accounts.subscription;
+
+        // A channel re-created under the same name does not adopt old subscriptions
+        let channel_info = &ctx.accounts.broadcast_channel;
+        if !channel_info.data_is_empty() {
+            let channel =
+                BroadcastChannel::try_deserialize(&mut &channel_info.try_borrow_data()?[..])?;
+            require!(
+                !subscription.belongs_to(&channel_info.key(), channel.created_at),
+                MessagingError::ChannelStillOpen
+            );
+        }
+
+        emit!(Unsubscribed {
+            channel: subscription.channel,
+            subscription: subscription.key(),
+            subscriber: subscription.subscriber,
+            subscriber_count: 0,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Orphaned subscription {} closed", subscription.key());
+
+        Ok(())
+    }
+
+    /// Propose closing a thread without the other participant's signature
+    /// The close becomes executable by the proposer after
+    /// `THREAD_CLOSE_TIMELOCK_SECONDS` unless it is vetoed first.
+    pub fn request_thread_close(ctx: Context<RequestThreadClose>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let executable_after = now
+            .checked_add(THREAD_CLOSE_TIMELOCK_SECONDS)
+            .ok_or(MessagingError::Overflow)?;
+
+        let request = &mut ctx.accounts.close_request;
+        request.thread = ctx.accounts.message_thread.key();
+        request.proposer = ctx.accounts.proposer.key();
+        request.requested_at = now;
+        request.executable_after = executable_after;
+        request.version = ACCOUNT_VERSION;
+
+        emit!(ThreadCloseRequested {
+            thread: request.thread,
+            proposer: request.proposer,
+            executable_after,
+            timestamp: now,
+        });
+
+        msg!("Thread close requested by {}", request.proposer);
+        msg!("Executable after: {}", executable_after);
+
+        Ok(())
+    }
+
+    /// Veto a pending thread close request, refunding its rent to the proposer
+    /// Either participant may veto while the thread exists; once it is gone
+    /// the proposer can still reclaim a leftover request.
+    pub fn veto_thread_close(ctx: Context<VetoThreadClose>) -> Result<()> {
+        let request = &ctx.accounts.close_request;
+        let signer = ctx.accounts.participant.key();
+
+        let thread_info = &ctx.accounts.message_thread;
+        let allowed = signer == request.proposer
+            || (!thread_info.data_is_empty() && {
+                let thread =
+                    MessageThread::try_deserialize(&mut &thread_info.try_borrow_data()?[..])?;
+                thread.is_participant(&signer)
+            });
+        require!(allowed, MessagingError::NotAParticipant);
+
+        emit!(ThreadCloseVetoed {
+            thread: request.thread,
+            proposer: request.proposer,
+            vetoed_by: signer,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Thread close request vetoed by {}", signer);
+
+        Ok(())
+    }
+
+    /// Send several messages in a thread in one transaction
+    /// Messages take the indices `first_message_index..` in order, each checked and
+    /// rate limited exactly as `send_message` would, and each emits its own
+    /// `MessageSent`. The protocol fee is charged per message.
+    pub fn send_message_batch(
+        ctx: Context<SendMessage>,
+        first_message_index: u32,
+        messages: Vec<MessageDescriptor>,
+    ) -> Result<()> {
+        require!(!messages.is_empty(), MessagingError::EmptyBatch);
+        require!(
+            messages.len() <= MAX_BATCH_MESSAGES,
+            MessagingError::BatchTooLarge
+        );
+        ensure_not_blocked(&ctx.accounts.block_by_a)?;
+        ensure_not_blocked(&ctx.accounts.block_by_b)?;
+
+        let thread = &mut ctx.accounts.message_thread;
+        let timestamp = Clock::get()?.unix_timestamp;
+
+        // A session key sends as its owner
+        let sender = match &ctx.accounts.session_key {
+            Some(session) => session.authorize(&thread.key(), timestamp)?,
+            None => ctx.accounts.sender.key(),
+        };
+        let device = ctx
+            .accounts
+            .sender_device
+            .as_ref()
+            .map(|device| device.key());
+
+        let mut message_index = first_message_index;
+        for message in &messages {
+            check_reply_to(message.reply_to_index, message_index)?;
+            if let Some(attachment) = &message.attachment {
+                attachment.validate()?;
+            }
+
+            let content_hash = hash(&message.encrypted_content).to_bytes();
+            thread.record_message(sender, message_index, content_hash, timestamp)?;
+
+            emit!(MessageSent {
+                thread: thread.key(),
+                sender,
+                message_index,
+                content_hash,
+                timestamp,
+                reply_to_index: message.reply_to_index,
+                attachment: message.attachment.clone(),
+                device,
+            });
+
+            message_index = message_index
+                .checked_add(1)
+                .ok_or(MessagingError::Overflow)?;
+        }
+
+        msg!("{} messages sent by {}", messages.len(), sender);
+        msg!("Thread messages: {}", thread.message_count);
+
+        charge_message_fee(ctx.accounts, messages.len() as u64)?;
+
+        Ok(())
+    }
+
+    /// Send several broadcasts to a channel in one transaction
+    /// Broadcasts take the indices `first_message_index..` in order, each checked and
+    /// rate limited exactly as `send_broadcast` would, and each emits its own
+    /// `BroadcastSent`. The protocol fee is charged per broadcast.
+    pub fn send_broadcast_batch(
+        ctx: Context<SendBroadcast>,
+        first_message_index: u32,
+        broadcasts: Vec<BroadcastDescriptor>,
+    ) -> Result<()> {
+        require!(!broadcasts.is_empty(), MessagingError::EmptyBatch);
+        require!(
+            broadcasts.len() <= MAX_BATCH_MESSAGES,
+            MessagingError::BatchTooLarge
+        );
+        let channel = &mut ctx.accounts.broadcast_channel;
+
+        // The owner or an admin with broadcast permission may send
+        let is_admin = ctx
+            .accounts
+            .channel_admin
+            .as_ref()
+            .is_some_and(|admin| admin.has_permission(ChannelAdmin::BROADCAST));
+        let authorized = ctx.accounts.sender.key() == channel.owner || is_admin;
+        let now = Clock::get()?.unix_timestamp;
+        let device = ctx
+            .accounts
+            .sender_device
+            .as_ref()
+            .map(|device| device.key());
+
+        let mut message_index = first_message_index;
+        for broadcast in &broadcasts {
+            check_reply_to(broadcast.reply_to_index, message_index)?;
+            if let Some(attachment) = &broadcast.attachment {
+                attachment.validate()?;
+            }
+
+            channel.record_broadcast(authorized, message_index, now)?;
+
+            emit!(BroadcastSent {
+                channel: channel.key(),
+                sender: ctx.accounts.sender.key(),
+                message_index,
+                content_hash: hash(&broadcast.encrypted_content).to_bytes(),
+                burn_after_seen: broadcast.burn_after_seen,
+                timestamp: now,
+                reply_to_index: broadcast.reply_to_index,
+                attachment: broadcast.attachment.clone(),
+                device,
+            });
+
+            message_index = message_index
+                .checked_add(1)
+                .ok_or(MessagingError::Overflow)?;
+        }
+        if let Some(stats) = ctx.accounts.channel_stats.as_mut() {
+            stats.record(&channel.key(), now, broadcasts.len() as u32, 0)?;
+        }
+
+        msg!("{} broadcasts sent", broadcasts.len());
+        msg!("Total broadcasts: {}", channel.message_count);
+
+        charge_message_fee(ctx.accounts, broadcasts.len() as u64)?;
+
+        Ok(())
+    }
+
+    /// Post an ephemeral status update under the poster's profile
+    /// The content lives off-chain at `uri`; `content_hash` pins it and keys the PDA.
+    /// It can be viewed until `expires_at`, at most `MAX_STATUS_LIFETIME_SECONDS`
+    /// away, after which anyone can close it with `expire_status`.
+    pub fn post_status(
+        ctx: Context<PostStatus>,
+        content_hash: [u8; 32],
+        uri: String,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(
+            uri.len() <= MAX_STATUS_URI_LEN,
+            MessagingError::StatusUriTooLong
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            expires_at > now && expires_at - now <= MAX_STATUS_LIFETIME_SECONDS,
+            MessagingError::InvalidStatusExpiry
+        );
+
+        let status = &mut ctx.accounts.status;
+        status.owner = ctx.accounts.owner.key();
+        status.content_hash = content_hash;
+        status.uri = uri;
+        status.posted_at = now;
+        status.expires_at = expires_at;
+        status.view_count = 0;
+        status.version = ACCOUNT_VERSION;
+
+        emit!(StatusPosted {
+            status: status.key(),
+            owner: status.owner,
+            content_hash,
+            expires_at,
+            timestamp: now,
+        });
+
+        msg!("Status posted by {}", status.owner);
+        msg!("Expires at: {}", expires_at);
+
+        Ok(())
+    }
+
+    /// Record that one of the poster's contacts viewed a status
+    /// Receipts are one per viewer; the viewer pays their rent and gets it back
+    /// through `close_status_view` once the status is gone.
+    pub fn view_status(ctx: Context<ViewStatus>) -> Result<()> {
+        let status = &mut ctx.accounts.status;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < status.expires_at, MessagingError::StatusExpired);
+
+        status.view_count = status
+            .view_count
+            .checked_add(1)
+            .ok_or(MessagingError::Overflow)?;
+
+        let receipt = &mut ctx.accounts.status_view;
+        receipt.status = status.key();
+        receipt.viewer = ctx.accounts.viewer.key();
+        receipt.viewed_at = now;
+        receipt.version = ACCOUNT_VERSION;
+
+        emit!(StatusViewed {
+            status: status.key(),
+            owner: status.owner,
+            viewer: receipt.viewer,
+            view_count: status.view_count,
+            timestamp: now,
+        });
+
+        msg!("Status viewed by {}", receipt.viewer);
+
+        Ok(())
+    }
+
+    /// Close an expired status, refunding its rent to the poster (permissionless)
+    pub fn expire_status(ctx: Context<ExpireStatus>) -> Result<()> {
+        let status = &ctx.accounts.status;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= status.expires_at, MessagingError::StatusNotExpired);
+
+        emit!(StatusExpired {
+            status: status.key(),
+            owner: status.owner,
+            view_count: status.view_count,
+            timestamp: now,
+        });
+
+        msg!("Status {} expired", status.key());
+
+        Ok(())
+    }
+
+    /// Close a view receipt once its status has expired or been closed,
+    /// refunding its rent to the viewer (permissionless)
+    pub fn close_status_view(ctx: Context<CloseStatusView>) -> Result<()> {
+        let status_info = &ctx.accounts.status;
+        if !status_info.data_is_empty() {
+            let status = Status::try_deserialize(&mut &status_info.try_borrow_data()?[..])?;
+            require!(
+                Clock::get()?.unix_timestamp >= status.expires_at,
+                MessagingError::StatusNotExpired
+            );
+        }
+
+        msg!(
+            "Status view receipt closed for {}",
+            ctx.accounts.viewer.key()
+        );
+
+        Ok(())
+    }
+
+    /// Start a voice/video call in an accepted thread
+    /// The `call_session` PDA is the signaling rendezvous: it carries the hash of the
+    /// caller's SDP offer, which the callee checks against the offer sent
+    /// off-chain before answering.
+    pub fn initiate_call(ctx: Context<InitiateCall>, sdp_offer_hash: [u8; 32]) -> Result<()> {
+        let thread = &ctx.accounts.message_thread;
+        require!(
+            thread.status == ThreadStatus::Accepted,
+            MessagingError::ThreadNotAccepted
+        );
+
+        let caller = ctx.accounts.caller.key();
+        let now = Clock::get()?.unix_timestamp;
+        let call = &mut ctx.accounts.call_session;
+        call.thread = thread.key();
+        call.caller = caller;
+        call.callee = thread.other_participant(&caller);
+        call.sdp_offer_hash = sdp_offer_hash;
+        call.sdp_answer_hash = [0; 32];
+        call.state = CallState::Ringing;
+        call.started_at = now;
+        call.answered_at = 0;
+        call.version = ACCOUNT_VERSION;
+
+        emit!(CallInitiated {
+            thread: call.thread,
+            call_session: call.key(),
+            caller,
+            callee: call.callee,
+            sdp_offer_hash,
+            timestamp: now,
+        });
+
+        msg!("Call from {} to {}", caller, call.callee);
+
+        Ok(())
+    }
+
+    /// Answer a ringing call with the hash of the callee's SDP answer
+    pub fn answer_call(ctx: Context<AnswerCall>, sdp_answer_hash: [u8; 32]) -> Result<()> {
+        let call = &mut ctx.accounts.call_session;
+        require!(
+            call.state == CallState::Ringing,
+            MessagingError::CallNotRinging
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        call.sdp_answer_hash = sdp_answer_hash;
+        call.state = CallState::Active;
+        call.answered_at = now;
+
+        emit!(CallAnswered {
+            thread: call.thread,
+            call_session: call.key(),
+            callee: call.callee,
+            sdp_answer_hash,
+            timestamp: now,
+        });
+
+        msg!("Call answered by {}", call.callee);
+
+        Ok(())
+    }
+
+    /// Hang up, decline or cancel a call, refunding the session rent to the caller
+    /// Either participant may end a call; anyone may clear one left ringing for
+    /// `CALL_RING_TIMEOUT_SECONDS`.
+    pub fn end_call(ctx: Context<EndCall>) -> Result<()> {
+        let call = &ctx.accounts.call_session;
+        let ended_by = ctx.accounts.ended_by.key();
+        let now = Clock::get()?.unix_timestamp;
+
+        let is_participant = ended_by == call.caller || ended_by == call.callee;
+        let ring_timed_out =
+            call.state == CallState::Ringing && now >= call.started_at + CALL_RING_TIMEOUT_SECONDS;
+        require!(
+            is_participant || ring_timed_out,
+            MessagingError::NotAParticipant
+        );
+
+        let duration = if call.state == CallState::Active {
+            now - call.answered_at
+        } else {
+            0
+        };
+
+        emit!(CallEnded {
+            thread: call.thread,
+            call_session: call.key(),
+            ended_by,
+            answered: call.state == CallState::Active,
+            duration,
+            timestamp: now,
+        });
+
+        msg!("Call ended by {}", ended_by);
+        msg!("Duration: {}s", duration);
+
+        Ok(())
+    }
+
+    /// Open a token reward pool for a channel's subscribers (owner only)
+    /// The pool's ATA for `mint` is its vault: the owner funds it with a plain token
+    /// transfer. Each full epoch of `epoch_seconds` earns an eligible subscriber
+    /// `reward_per_epoch` tokens, counted from when their subscription is
+    /// `min_subscription_age` seconds old.
+    pub fn create_reward_pool(
+        ctx: Context<CreateRewardPool>,
+        reward_per_epoch: u64,
+        epoch_seconds: i64,
+        min_subscription_age: i64,
+        max_unread: u64,
+    ) -> Result<()> {
+        require!(
+            epoch_seconds > 0 && min_subscription_age >= 0,
+            MessagingError::InvalidRewardConfig
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let pool = &mut ctx.accounts.reward_pool;
+        pool.channel = ctx.accounts.broadcast_channel.key();
+        pool.mint = ctx.accounts.mint.key();
+        pool.reward_per_epoch = reward_per_epoch;
+        pool.epoch_seconds = epoch_seconds;
+        pool.min_subscription_age = min_subscription_age;
+        pool.max_unread = max_unread;
+        pool.start_at = now;
+        pool.total_claimed = 0;
+        pool.version = ACCOUNT_VERSION;
+
+        emit!(RewardPoolUpdated {
+            reward_pool: pool.key(),
+            channel: pool.channel,
+            mint: pool.mint,
+            reward_per_epoch,
+            epoch_seconds,
+            timestamp: now,
+        });
+
+        msg!(
+            "Reward pool opened for {}",
+            ctx.accounts.broadcast_channel.channel_name
+        );
+        msg!("{} tokens every {}s", reward_per_epoch, epoch_seconds);
+
+        Ok(())
+    }
+
+    /// Change the per-epoch reward (owner only)
+    /// Accrued rewards are not snapshotted: the new rate is applied retroactively
+    /// to every epoch a subscriber has not claimed yet, including past ones.
+    pub fn set_reward_rate(ctx: Context<UpdateRewardPool>, reward_per_epoch: u64) -> Result<()> {
+        let pool = &mut ctx.accounts.reward_pool;
+        pool.reward_per_epoch = reward_per_epoch;
+
+        emit!(RewardPoolUpdated {
+            reward_pool: pool.key(),
+            channel: pool.channel,
+            mint: pool.mint,
+            reward_per_epoch,
+            epoch_seconds: pool.epoch_seconds,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Reward rate set to {}", reward_per_epoch);
+
+        Ok(())
+    }
+
+    /// Claim the tokens earned since the last claim
+    /// The subscription must be active, old enough, and have at most `max_unread`
+    /// unread broadcasts. Full epochs since the later of the last claim and the
+    /// subscription becoming eligible are paid out from the vault; the partial
+    /// epoch in which it becomes eligible earns nothing.
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        let pool = &ctx.accounts.reward_pool;
+        let subscription = &ctx.accounts.subscription;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            subscription.is_active(now),
+            MessagingError::SubscriptionInactive
+        );
+        let unread = ctx
+            .accounts
+            .broadcast_channel
+            .message_count
+            .saturating_sub(subscription.last_read_index);
+        require!(unread <= pool.max_unread, MessagingError::RewardsNotEarned);
+
+        let eligible_at = subscription
+            .subscribed_at
+            .checked_add(pool.min_subscription_age)
+            .ok_or(MessagingError::Overflow)?;
+        require!(now >= eligible_at, MessagingError::RewardsNotEarned);
+
+        // Fresh claim record from `init_if_needed`
+        let claim = &mut ctx.accounts.reward_claim;
+        if claim.reward_pool == Pubkey::default() {
+            claim.reward_pool = pool.key();
+            claim.subscriber = subscription.subscriber;
+            claim.version = ACCOUNT_VERSION;
+        }
+
+        let current_epoch = pool.epoch_at(now);
+        let first_epoch = claim
+            .last_claimed_epoch
+            .max(pool.first_full_epoch_from(eligible_at));
+        let epochs = current_epoch.saturating_sub(first_epoch);
+        require!(epochs > 0, MessagingError::RewardsNotEarned);
+        let amount = epochs
+            .checked_mul(pool.reward_per_epoch)
+            .ok_or(MessagingError::Overflow)?;
+        require!(
+            ctx.accounts.vault.amount >= amount,
+            MessagingError::RewardPoolDepleted
+        );
+
+        let channel_key = pool.channel;
+        let seeds: &[&[u8]] = &[
+            b"reward_pool",
+            channel_key.as_ref(),
+            &[ctx.bumps.reward_pool],
+        ];
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.destination_token_account.to_account_info(),
+                    authority: ctx.accounts.reward_pool.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        claim.last_claimed_epoch = current_epoch;
+        claim.total_claimed = claim
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(MessagingError::Overflow)?;
+        let pool = &mut ctx.accounts.reward_pool;
+        pool.total_claimed = pool
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(MessagingError::Overflow)?;
+
+        emit!(RewardsClaimed {
+            reward_pool: pool.key(),
+            channel: pool.channel,
+            subscriber: claim.subscriber,
+            epochs,
+            amount,
+            timestamp: now,
+        });
+
+        msg!("{} reward tokens claimed for {} epochs", amount, epochs);
+
+        Ok(())
+    }
+
+    /// Withdraw unclaimed tokens from a reward pool's vault (owner only)
+    pub fn withdraw_rewards(ctx: Context<WithdrawRewards>, amount: u64) -> Result<()> {
+        require!(amount > 0, MessagingError::InvalidAmount);
+
+        let channel_key = ctx.accounts.reward_pool.channel;
+        let seeds: &[&[u8]] = &[
+            b"reward_pool",
+            channel_key.as_ref(),
+            &[ctx.bumps.reward_pool],
+        ];
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.destination_token_account.to_account_info(),
+                    authority: ctx.accounts.reward_pool.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        emit!(RewardsWithdrawn {
+            reward_pool: ctx.accounts.reward_pool.key(),
+            channel: channel_key,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("{} reward tokens withdrawn", amount);
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(thread_id: [u8; 32])]
+pub struct InitializeThread<'info> {
+    #[account(
+        init,
+        payer = participant_a,
+        space = 8 + MessageThread::INIT_SPACE,
+        seeds = [
+            b"message_thread",
+            sorted_participants(&participant_a.key(), &participant_b.key()).0.as_ref(),
+            sorted_participants(&participant_a.key(), &participant_b.key()).1.as_ref(),
+            thread_id.as_ref()
+        ],
+        bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+    
+    #[account(mut)]
+    pub participant_a: Signer<'info>,
+    
+    /// CHECK: Participant B doesn't need to sign for initialization
+    pub participant_b: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Block entry of participant A blocking B; must not exist
+    #[account(
+        seeds = [
+            b"block_entry",
             participant_a.key().as_ref(),
+            participant_b.key().as_ref()
+        ],
+        bump
+    )]
+    pub block_by_a: UncheckedAccount<'info>,
+
+    /// CHECK: Block entry of participant B blocking A; must not exist
+    #[account(
+        seeds = [
+            b"block_entry",
             participant_b.key().as_ref(),
-            thread_id.as_ref()
+            participant_a.key().as_ref()
+        ],
+        bump
+    )]
+    pub block_by_b: UncheckedAccount<'info>,
+
+    /// Participant A's profile, if registered, to snapshot its key version
+    #[account(
+        seeds = [b"user_profile", participant_a.key().as_ref()],
+        bump
+    )]
+    pub profile_a: Option<Account<'info, UserProfile>>,
+
+    /// Participant B's profile, if registered, to snapshot its key version
+    #[account(
+        seeds = [b"user_profile", participant_b.key().as_ref()],
+        bump
+    )]
+    pub profile_b: Option<Account<'info, UserProfile>>,
+
+    /// Verified contact handshake between the participants; skips the deposit
+    pub contact_proof: Option<Account<'info, VerifiedContact>>,
+
+    /// CHECK: Deposit vault PDA, validated and created in the handler when
+    /// participant B requires an outreach deposit
+    #[account(mut)]
+    pub thread_vault: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Participant B's profile PDA, always passed so the deposit
+    /// requirement cannot be skipped; may be uninitialized
+    #[account(
+        seeds = [b"user_profile", participant_b.key().as_ref()],
+        bump
+    )]
+    pub recipient_profile: UncheckedAccount<'info>,
+
+    /// Participant B's contact entry for A; pre-accepts and skips the deposit
+    #[account(
+        seeds = [b"contact", participant_b.key().as_ref(), participant_a.key().as_ref()],
+        bump
+    )]
+    pub recipient_contact: Option<Account<'info, Contact>>,
+
+    /// Participant A's inbox; indexing is skipped for a participant without one passed
+    #[account(
+        init_if_needed,
+        payer = participant_a,
+        space = 8 + UserInbox::INIT_SPACE,
+        seeds = [b"user_inbox", participant_a.key().as_ref()],
+        bump
+    )]
+    pub inbox_a: Option<Box<Account<'info, UserInbox>>>,
+
+    /// CHECK: Next thread pointer PDA of `inbox_a`, created in the handler
+    #[account(mut)]
+    pub pointer_a: Option<UncheckedAccount<'info>>,
+
+    /// Participant B's inbox, created and paid for by A if missing
+    #[account(
+        init_if_needed,
+        payer = participant_a,
+        space = 8 + UserInbox::INIT_SPACE,
+        seeds = [b"user_inbox", participant_b.key().as_ref()],
+        bump
+    )]
+    pub inbox_b: Option<Box<Account<'info, UserInbox>>>,
+
+    /// CHECK: Next thread pointer PDA of `inbox_b`, created in the handler
+    #[account(mut)]
+    pub pointer_b: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: DM fee escrow PDA, validated and created in the handler when
+    /// participant B charges a DM fee
+    #[account(mut)]
+    pub dm_escrow: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_index: u32)]
+pub struct SendMessage<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+    
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    /// CHECK: Block entry of participant A blocking B; must not exist
+    #[account(
+        seeds = [
+            b"block_entry",
+            message_thread.participant_a.as_ref(),
+            message_thread.participant_b.as_ref()
+        ],
+        bump
+    )]
+    pub block_by_a: UncheckedAccount<'info>,
+
+    /// CHECK: Block entry of participant B blocking A; must not exist
+    #[account(
+        seeds = [
+            b"block_entry",
+            message_thread.participant_b.as_ref(),
+            message_thread.participant_a.as_ref()
+        ],
+        bump
+    )]
+    pub block_by_b: UncheckedAccount<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol treasury PDA, required while a fee is charged
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Option<Program<'info, System>>,
+
+    /// Recipient's contact entry for the sender; contacts message fee-free
+    #[account(
+        seeds = [
+            b"contact",
+            message_thread
+                .other_participant(&sending_as(&sender.key(), &session_key))
+                .as_ref(),
+            sending_as(&sender.key(), &session_key).as_ref()
+        ],
+        bump
+    )]
+    pub sender_contact: Option<Account<'info, Contact>>,
+
+    /// Session key delegating the signer to send as its owner
+    #[account(
+        seeds = [b"session_key", session_key.owner.as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub session_key: Option<Account<'info, SessionKey>>,
+
+    /// Sender's device, referenced in the event for auditing
+    #[account(
+        seeds = [
+            b"device",
+            sending_as(&sender.key(), &session_key).as_ref(),
+            sender_device.device_key.as_ref()
+        ],
+        bump
+    )]
+    pub sender_device: Option<Account<'info, Device>>,
+}
+
+#[derive(Accounts)]
+pub struct ReconcileCount<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    pub participant: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_index: u32)]
+pub struct SignAcknowledgment<'info> {
+    #[account(
+        init,
+        payer = recipient,
+        space = 8 + AckReceipt::INIT_SPACE,
+        seeds = [
+            b"ack_receipt",
+            message_thread.key().as_ref(),
+            &message_index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub ack_receipt: Account<'info, AckReceipt>,
+
+    #[account(
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 instruction
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRecipientSettings<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump,
+        has_one = participant_b @ MessagingError::UnauthorizedSender
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    pub participant_b: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SendMultiBroadcast<'info> {
+    pub sender: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol treasury PDA, required while a fee is charged
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Option<Program<'info, System>>,
+
+    /// Pays the protocol fee in place of the sender; required when the sender is a
+    /// PDA that holds data
+    #[account(mut)]
+    pub fee_payer: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(source_index: u32, message_index: u32)]
+pub struct ForwardMessage<'info> {
+    /// CHECK: Source message thread or broadcast channel, parsed by the handler
+    pub source: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"message_thread",
+            sorted_participants(
+                &destination_thread.participant_a,
+                &destination_thread.participant_b
+            )
+            .0
+            .as_ref(),
+            sorted_participants(
+                &destination_thread.participant_a,
+                &destination_thread.participant_b
+            )
+            .1
+            .as_ref(),
+            destination_thread.thread_id.as_ref()
+        ],
+        bump = destination_thread.bump
+    )]
+    pub destination_thread: Account<'info, MessageThread>,
+
+    #[account(mut)]
+    pub forwarder: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = forwarder,
+        space = 8 + ForwardRecord::INIT_SPACE,
+        seeds = [
+            b"forward_record",
+            destination_thread.key().as_ref(),
+            &message_index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub forward_record: Account<'info, ForwardRecord>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Source message's record, naming its sender; optional for the latest message
+    pub source_record: Option<Account<'info, MessageRecord>>,
+
+    /// Forwarder's subscription, required to forward a broadcast unless they own the channel
+    pub source_subscription: Option<Account<'info, ChannelSubscription>>,
+
+    /// CHECK: Protocol treasury PDA, required while a fee is charged
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    /// Recipient's contact entry for the sender; contacts message fee-free
+    #[account(
+        seeds = [
+            b"contact",
+            destination_thread.other_participant(&forwarder.key()).as_ref(),
+            forwarder.key().as_ref()
+        ],
+        bump
+    )]
+    pub sender_contact: Option<Account<'info, Contact>>,
+}
+
+#[derive(Accounts)]
+pub struct EditLatestMessage<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    pub sender: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RespondToThread<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump,
+        has_one = participant_b @ MessagingError::UnauthorizedSender
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    pub participant_b: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(channel_name: String)]
+pub struct InitializeChannel<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + BroadcastChannel::INIT_SPACE,
+        seeds = [
+            b"broadcast_channel",
+            owner.key().as_ref(),
+            channel_name.as_bytes()
+        ],
+        bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+    
+    /// Channel owner; may be a PDA signing through CPI
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Pays the rent, so the owner may be a program-signed PDA holding data
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetChannelMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetWelcomeThread<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SubscribeChannel<'info> {
+    #[account(
+        init,
+        payer = subscriber,
+        space = 8 + ChannelSubscription::INIT_SPACE,
+        seeds = [
+            b"subscription",
+            broadcast_channel.key().as_ref(),
+            subscriber.key().as_ref()
+        ],
+        bump
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+    
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+    
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Welcome thread PDA, validated and created in the handler when
+    /// the channel has a welcome thread enabled
+    #[account(mut)]
+    pub welcome_thread: Option<UncheckedAccount<'info>>,
+
+    /// Fee vault, required when the channel charges a subscription fee
+    #[account(
+        mut,
+        seeds = [b"channel_vault", broadcast_channel.key().as_ref()],
+        bump
+    )]
+    pub channel_vault: Option<Account<'info, ChannelVault>>,
+
+    /// CHECK: Subscriber's associated token account for the gate mint, validated
+    /// in the handler when the channel is token-gated
+    pub gate_token_account: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Invite consumed when the channel is private
+    #[account(mut)]
+    pub channel_invite: Option<Account<'info, ChannelInvite>>,
+
+    /// CHECK: Ban entry of the subscriber on this channel; must not exist
+    #[account(
+        seeds = [
+            b"channel_ban",
+            broadcast_channel.key().as_ref(),
+            subscriber.key().as_ref()
+        ],
+        bump
+    )]
+    pub ban_entry: UncheckedAccount<'info>,
+
+    /// Stats account for the current UTC day, counted when passed
+    #[account(mut)]
+    pub channel_stats: Option<Account<'info, ChannelStats>>,
+
+    /// Channel owner's profile, if registered, to snapshot its key version
+    #[account(
+        seeds = [b"user_profile", broadcast_channel.owner.as_ref()],
+        bump
+    )]
+    pub owner_profile: Option<Account<'info, UserProfile>>,
+
+    /// Subscriber's profile, if registered, to snapshot its key version
+    #[account(
+        seeds = [b"user_profile", subscriber.key().as_ref()],
+        bump
+    )]
+    pub subscriber_profile: Option<Account<'info, UserProfile>>,
+
+    /// Channel owner's inbox, created and paid for by the subscriber if missing;
+    /// indexing the welcome thread is skipped for an inbox not passed
+    #[account(
+        init_if_needed,
+        payer = subscriber,
+        space = 8 + UserInbox::INIT_SPACE,
+        seeds = [b"user_inbox", broadcast_channel.owner.as_ref()],
+        bump
+    )]
+    pub owner_inbox: Option<Box<Account<'info, UserInbox>>>,
+
+    /// CHECK: Next thread pointer PDA of `owner_inbox`, created in the handler
+    #[account(mut)]
+    pub owner_pointer: Option<UncheckedAccount<'info>>,
+
+    /// Subscriber's inbox, indexed with the welcome thread when passed
+    #[account(
+        init_if_needed,
+        payer = subscriber,
+        space = 8 + UserInbox::INIT_SPACE,
+        seeds = [b"user_inbox", subscriber.key().as_ref()],
+        bump
+    )]
+    pub subscriber_inbox: Option<Box<Account<'info, UserInbox>>>,
+
+    /// CHECK: Next thread pointer PDA of `subscriber_inbox`, created in the handler
+    #[account(mut)]
+    pub subscriber_pointer: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_index: u32)]
+pub struct SendBroadcast<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+    
+    /// Channel owner or admin; may be a PDA signing through CPI
+    pub sender: Signer<'info>,
+
+    /// Sender's admin role, when the sender is not the owner
+    #[account(
+        seeds = [
+            b"channel_admin",
+            broadcast_channel.key().as_ref(),
+            sender.key().as_ref()
+        ],
+        bump
+    )]
+    pub channel_admin: Option<Account<'info, ChannelAdmin>>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol treasury PDA, required while a fee is charged
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Option<Program<'info, System>>,
+
+    /// Pays the protocol fee in place of the sender; required when the sender is a
+    /// PDA that holds data
+    #[account(mut)]
+    pub fee_payer: Option<Signer<'info>>,
+
+    /// Sender's device, referenced in the event for auditing
+    #[account(
+        seeds = [b"device", sender.key().as_ref(), sender_device.device_key.as_ref()],
+        bump
+    )]
+    pub sender_device: Option<Account<'info, Device>>,
+
+    /// Stats account for the current UTC day, counted when passed
+    #[account(mut)]
+    pub channel_stats: Option<Account<'info, ChannelStats>>,
+}
+
+#[derive(Accounts)]
+pub struct RecountSubscribers<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TipChannel<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    /// CHECK: Channel owner receiving the tip, tied by `has_one`; may be a PDA
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = subscriber @ MessagingError::UnauthorizedSender,
+        constraint = subscription
+            .belongs_to(&broadcast_channel.key(), broadcast_channel.created_at)
+            @ MessagingError::SubscriptionMismatch
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_index: u32)]
+pub struct ConfirmBroadcastDelivery<'info> {
+    #[account(
+        init_if_needed,
+        payer = subscriber,
+        space = 8 + BroadcastDelivery::INIT_SPACE,
+        seeds = [
+            b"broadcast_delivery",
+            broadcast_channel.key().as_ref(),
+            &message_index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub broadcast_delivery: Account<'info, BroadcastDelivery>,
+
+    #[account(
+        init,
+        payer = subscriber,
+        space = 8 + DeliveryReceipt::INIT_SPACE,
+        seeds = [
+            b"delivery_receipt",
+            broadcast_delivery.key().as_ref(),
+            subscriber.key().as_ref()
+        ],
+        bump
+    )]
+    pub delivery_receipt: Account<'info, DeliveryReceipt>,
+
+    #[account(
+        has_one = subscriber @ MessagingError::UnauthorizedSender,
+        constraint = subscription
+            .belongs_to(&broadcast_channel.key(), broadcast_channel.created_at)
+            @ MessagingError::SubscriptionMismatch
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_index: u32)]
+pub struct BurnBroadcastForSelf<'info> {
+    #[account(
+        init,
+        payer = subscriber,
+        space = 8 + BroadcastBurn::INIT_SPACE,
+        seeds = [
+            b"broadcast_burn",
+            broadcast_channel.key().as_ref(),
+            &message_index.to_le_bytes(),
+            subscriber.key().as_ref()
+        ],
+        bump
+    )]
+    pub broadcast_burn: Account<'info, BroadcastBurn>,
+
+    #[account(
+        has_one = subscriber @ MessagingError::UnauthorizedSender,
+        constraint = subscription
+            .belongs_to(&broadcast_channel.key(), broadcast_channel.created_at)
+            @ MessagingError::SubscriptionMismatch
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseThread<'info> {
+    #[account(
+        mut,
+        has_one = participant_a @ MessagingError::NotAParticipant,
+        constraint = message_thread.is_participant(&closer.key())
+            @ MessagingError::NotAParticipant,
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump,
+        close = participant_a
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+    
+    /// Either participant
+    #[account(mut)]
+    pub closer: Signer<'info>,
+
+    /// CHECK: Thread initiator, who paid the thread and pointer rent and gets it back
+    #[account(mut)]
+    pub participant_a: UncheckedAccount<'info>,
+
+    /// Participant A's pointer to this thread, closed along with it
+    #[account(
+        mut,
+        constraint = pointer_a.thread == message_thread.key()
+            @ MessagingError::InvalidThreadPointer,
+        close = participant_a
+    )]
+    pub pointer_a: Option<Account<'info, ThreadPointer>>,
+
+    /// Participant B's pointer to this thread, closed along with it
+    #[account(
+        mut,
+        constraint = pointer_b.thread == message_thread.key()
+            @ MessagingError::InvalidThreadPointer,
+        close = participant_a
+    )]
+    pub pointer_b: Option<Account<'info, ThreadPointer>>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Outreach deposit vault; must be empty unless the thread is pending
+    #[account(seeds = [b"thread_vault", message_thread.key().as_ref()], bump)]
+    pub thread_vault: UncheckedAccount<'info>,
+
+    /// CHECK: DM fee escrow; must be empty unless the thread is pending
+    #[account(seeds = [b"dm_escrow", message_thread.key().as_ref()], bump)]
+    pub dm_escrow: UncheckedAccount<'info>,
+
+    /// The other participant, co-signing for an immediate close
+    pub counterparty: Option<Signer<'info>>,
+
+    /// The closer's own close request, once its timelock has passed
+    #[account(
+        mut,
+        seeds = [b"thread_close_request", message_thread.key().as_ref()],
+        bump,
+        constraint = close_request.proposer == closer.key()
+            @ MessagingError::ThreadCloseNotApproved,
+        close = closer
+    )]
+    pub close_request: Option<Account<'info, ThreadCloseRequest>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseChannel<'info> {
+    #[account(
+        mut,
+        has_one = owner @ MessagingError::NotChannelOwner,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+    
+    pub owner: Signer<'info>,
+    
+    /// CHECK: Receives the rent on the final close; can be any account
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetQuietHours<'info> {
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + QuietHours::INIT_SPACE,
+        seeds = [b"quiet_hours", user.key().as_ref()],
+        bump
+    )]
+    pub quiet_hours: Account<'info, QuietHours>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAutoArchive<'info> {
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + ArchivePreference::INIT_SPACE,
+        seeds = [b"archive_preference", user.key().as_ref()],
+        bump
+    )]
+    pub archive_preference: Account<'info, ArchivePreference>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MuteSenderGlobally<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + GlobalSenderMute::INIT_SPACE,
+        seeds = [
+            b"global_mute",
+            user.key().as_ref(),
+            muted_sender.key().as_ref()
+        ],
+        bump
+    )]
+    pub global_sender_mute: Account<'info, GlobalSenderMute>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Any account can be muted
+    pub muted_sender: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnmuteSenderGlobally<'info> {
+    #[account(
+        mut,
+        has_one = user @ MessagingError::UnauthorizedSender,
+        close = user
+    )]
+    pub global_sender_mute: Account<'info, GlobalSenderMute>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(folder_id: u32)]
+pub struct CreateFolder<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Folder::INIT_SPACE,
+        seeds = [
+            b"folder",
+            user.key().as_ref(),
+            &folder_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub folder: Account<'info, Folder>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFolder<'info> {
+    #[account(
+        mut,
+        has_one = user @ MessagingError::UnauthorizedSender
+    )]
+    pub folder: Account<'info, Folder>,
+
+    pub user: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyContact<'info> {
+    #[account(
+        init,
+        payer = initiator,
+        space = 8 + VerifiedContact::INIT_SPACE,
+        seeds = [
+            b"verified_contact",
+            initiator.key().as_ref(),
+            peer.key().as_ref()
+        ],
+        bump
+    )]
+    pub verified_contact: Account<'info, VerifiedContact>,
+
+    #[account(mut)]
+    pub initiator: Signer<'info>,
+
+    /// CHECK: The peer confirms separately with confirm_verification
+    pub peer: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmVerification<'info> {
+    #[account(
+        mut,
+        has_one = peer @ MessagingError::UnauthorizedSender
+    )]
+    pub verified_contact: Account<'info, VerifiedContact>,
+
+    pub peer: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSubscriptionLabel<'info> {
+    #[account(
+        mut,
+        has_one = subscriber @ MessagingError::UnauthorizedSender
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    pub subscriber: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExportSubscriptionData<'info> {
+    #[account(has_one = subscriber @ MessagingError::UnauthorizedSender)]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    pub subscriber: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExportChannelStats<'info> {
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ViewSubscription<'info> {
+    #[account(
+        constraint = subscription
+            .belongs_to(&broadcast_channel.key(), broadcast_channel.created_at)
+            @ MessagingError::SubscriptionMismatch
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_index: u32)]
+pub struct AddReaction<'info> {
+    #[account(
+        init,
+        payer = reactor,
+        space = 8 + Reaction::INIT_SPACE,
+        seeds = [
+            b"reaction",
+            message_thread.key().as_ref(),
+            &message_index.to_le_bytes(),
+            reactor.key().as_ref()
+        ],
+        bump
+    )]
+    pub reaction: Account<'info, Reaction>,
+
+    #[account(
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    #[account(mut)]
+    pub reactor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveReaction<'info> {
+    #[account(
+        mut,
+        has_one = reactor @ MessagingError::UnauthorizedSender,
+        close = reactor
+    )]
+    pub reaction: Account<'info, Reaction>,
+
+    #[account(mut)]
+    pub reactor: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(group_id: [u8; 32])]
+pub struct CreateGroup<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + GroupThread::INIT_SPACE,
+        seeds = [
+            b"group_thread",
+            creator.key().as_ref(),
+            group_id.as_ref()
+        ],
+        bump
+    )]
+    pub group_thread: Account<'info, GroupThread>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddMember<'info> {
+    #[account(
+        mut,
+        has_one = creator @ MessagingError::UnauthorizedSender
+    )]
+    pub group_thread: Account<'info, GroupThread>,
+
+    pub creator: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveMember<'info> {
+    #[account(mut)]
+    pub group_thread: Account<'info, GroupThread>,
+
+    pub authority: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_index: u32)]
+pub struct SendGroupMessage<'info> {
+    #[account(mut)]
+    pub group_thread: Account<'info, GroupThread>,
+
+    pub sender: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnsubscribeChannel<'info> {
+    #[account(
+        mut,
+        has_one = subscriber @ MessagingError::UnauthorizedSender,
+        constraint = subscription
+            .belongs_to(&broadcast_channel.key(), broadcast_channel.created_at)
+            @ MessagingError::SubscriptionMismatch,
+        seeds = [
+            b"subscription",
+            broadcast_channel.key().as_ref(),
+            subscriber.key().as_ref()
+        ],
+        bump = subscription.bump,
+        close = subscriber
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Stats account for the current UTC day, counted when passed
+    #[account(mut)]
+    pub channel_stats: Option<Account<'info, ChannelStats>>,
+}
+
+#[derive(Accounts)]
+pub struct MarkChannelRead<'info> {
+    #[account(
+        mut,
+        has_one = subscriber @ MessagingError::UnauthorizedSender,
+        constraint = subscription
+            .belongs_to(&broadcast_channel.key(), broadcast_channel.created_at)
+            @ MessagingError::SubscriptionMismatch
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub subscriber: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MarkThreadRead<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    pub reader: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MarkSeen<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + SeenPointer::INIT_SPACE,
+        seeds = [
+            b"seen_pointer",
+            message_thread.key().as_ref(),
+            participant.key().as_ref()
+        ],
+        bump
+    )]
+    pub seen_pointer: Account<'info, SeenPointer>,
+
+    #[account(
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    pub participant: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Pays the rent, so the owner may be a program-signed PDA holding data
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BlockUser<'info> {
+    #[account(
+        init,
+        payer = blocker,
+        space = 8 + BlockEntry::INIT_SPACE,
+        seeds = [
+            b"block_entry",
+            blocker.key().as_ref(),
+            blocked.key().as_ref()
+        ],
+        bump
+    )]
+    pub block_entry: Account<'info, BlockEntry>,
+
+    #[account(mut)]
+    pub blocker: Signer<'info>,
+
+    /// CHECK: Any account can be blocked
+    pub blocked: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnblockUser<'info> {
+    #[account(
+        mut,
+        has_one = blocker @ MessagingError::UnauthorizedSender,
+        close = blocker
+    )]
+    pub block_entry: Account<'info, BlockEntry>,
+
+    #[account(mut)]
+    pub blocker: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterProfile<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + UserProfile::INIT_SPACE,
+        seeds = [b"user_profile", owner.key().as_ref()],
+        bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Pays the key log's rent, so the owner may be a program-signed PDA holding data
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Thread whose key log records the key, when `key_log` is passed
+    #[account(
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Option<Account<'info, MessageThread>>,
+
+    /// Key transparency log of `message_thread`, created on first use
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + KeyTransparencyLog::INIT_SPACE,
+        seeds = [
+            b"key_log",
+            message_thread.as_ref().map(|thread| thread.key()).unwrap_or_default().as_ref()
+        ],
+        bump
+    )]
+    pub key_log: Option<Box<Account<'info, KeyTransparencyLog>>>,
+}
+
+#[derive(Accounts)]
+pub struct RotateEncryptionKey<'info> {
+    #[account(
+        mut,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Pays the key log's rent, so the owner may be a program-signed PDA holding data
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Thread whose key log records the key, when `key_log` is passed
+    #[account(
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Option<Account<'info, MessageThread>>,
+
+    /// Key transparency log of `message_thread`, created on first use
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + KeyTransparencyLog::INIT_SPACE,
+        seeds = [
+            b"key_log",
+            message_thread.as_ref().map(|thread| thread.key()).unwrap_or_default().as_ref()
+        ],
+        bump
+    )]
+    pub key_log: Option<Box<Account<'info, KeyTransparencyLog>>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProfile<'info> {
+    #[account(
+        mut,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_index: u32)]
+pub struct SendMessageStored<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + MessageRecord::INIT_SPACE,
+        seeds = [
+            b"message_record",
+            message_thread.key().as_ref(),
+            message_index.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub message_record: Account<'info, MessageRecord>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Block entry of participant A blocking B; must not exist
+    #[account(
+        seeds = [
+            b"block_entry",
+            message_thread.participant_a.as_ref(),
+            message_thread.participant_b.as_ref()
+        ],
+        bump
+    )]
+    pub block_by_a: UncheckedAccount<'info>,
+
+    /// CHECK: Block entry of participant B blocking A; must not exist
+    #[account(
+        seeds = [
+            b"block_entry",
+            message_thread.participant_b.as_ref(),
+            message_thread.participant_a.as_ref()
+        ],
+        bump
+    )]
+    pub block_by_b: UncheckedAccount<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol treasury PDA, required while a fee is charged
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    /// Recipient's contact entry for the sender; contacts message fee-free
+    #[account(
+        seeds = [
+            b"contact",
+            message_thread.other_participant(&sender.key()).as_ref(),
+            sender.key().as_ref()
+        ],
+        bump
+    )]
+    pub sender_contact: Option<Account<'info, Contact>>,
+}
+
+#[derive(Accounts)]
+pub struct CloseMessageRecord<'info> {
+    #[account(
+        mut,
+        has_one = sender @ MessagingError::UnauthorizedSender,
+        close = sender
+    )]
+    pub message_record: Account<'info, MessageRecord>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetSubscriptionFee<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ChannelVault::INIT_SPACE,
+        seeds = [b"channel_vault", broadcast_channel.key().as_ref()],
+        bump
+    )]
+    pub channel_vault: Account<'info, ChannelVault>,
+
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Pays the rent, so the owner may be a program-signed PDA holding data
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RenewSubscription<'info> {
+    #[account(
+        mut,
+        has_one = subscriber @ MessagingError::UnauthorizedSender,
+        constraint = subscription
+            .belongs_to(&broadcast_channel.key(), broadcast_channel.created_at)
+            @ MessagingError::SubscriptionMismatch
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        mut,
+        seeds = [b"channel_vault", broadcast_channel.key().as_ref()],
+        bump
+    )]
+    pub channel_vault: Account<'info, ChannelVault>,
+
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FundSubscriptionWallet<'info> {
+    #[account(
+        init_if_needed,
+        payer = subscriber,
+        space = 8 + SubscriptionWallet::INIT_SPACE,
+        seeds = [b"subscription_wallet", subscription.key().as_ref()],
+        bump
+    )]
+    pub subscription_wallet: Account<'info, SubscriptionWallet>,
+
+    #[account(has_one = subscriber @ MessagingError::UnauthorizedSender)]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseSubscriptionWallet<'info> {
+    #[account(
+        mut,
+        close = subscriber,
+        has_one = subscriber @ MessagingError::UnauthorizedSender
+    )]
+    pub subscription_wallet: Account<'info, SubscriptionWallet>,
+
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProcessRenewal<'info> {
+    #[account(
+        mut,
+        constraint = subscription
+            .belongs_to(&broadcast_channel.key(), broadcast_channel.created_at)
+            @ MessagingError::SubscriptionMismatch
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    #[account(
+        mut,
+        seeds = [b"subscription_wallet", subscription.key().as_ref()],
+        bump
+    )]
+    pub subscription_wallet: Account<'info, SubscriptionWallet>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        mut,
+        seeds = [b"channel_vault", broadcast_channel.key().as_ref()],
+        bump
+    )]
+    pub channel_vault: Account<'info, ChannelVault>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GiftSubscription<'info> {
+    #[account(
+        init,
+        payer = gifter,
+        space = 8 + ChannelSubscription::INIT_SPACE,
+        seeds = [
+            b"subscription",
+            broadcast_channel.key().as_ref(),
+            recipient.key().as_ref()
+        ],
+        bump
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    /// Pays the subscription fee and the subscription's rent
+    #[account(mut)]
+    pub gifter: Signer<'info>,
+
+    /// CHECK: Receives the subscription; doesn't need to sign
+    pub recipient: UncheckedAccount<'info>,
+
+    /// Fee vault, required when the channel charges a subscription fee
+    #[account(
+        mut,
+        seeds = [b"channel_vault", broadcast_channel.key().as_ref()],
+        bump
+    )]
+    pub channel_vault: Option<Account<'info, ChannelVault>>,
+
+    /// CHECK: Ban entry of the recipient on this channel; must not exist
+    #[account(
+        seeds = [
+            b"channel_ban",
+            broadcast_channel.key().as_ref(),
+            recipient.key().as_ref()
+        ],
+        bump
+    )]
+    pub ban_entry: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawChannelFees<'info> {
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        mut,
+        seeds = [b"channel_vault", broadcast_channel.key().as_ref()],
+        bump
+    )]
+    pub channel_vault: Account<'info, ChannelVault>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateChannelSettings<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifySubscription<'info> {
+    #[account(
+        mut,
+        has_one = subscriber @ MessagingError::UnauthorizedSender,
+        constraint = subscription
+            .belongs_to(&broadcast_channel.key(), broadcast_channel.created_at)
+            @ MessagingError::SubscriptionMismatch
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    /// CHECK: Receives the subscription rent if it is purged
+    #[account(mut)]
+    pub subscriber: UncheckedAccount<'info>,
+
+    /// CHECK: Subscriber's associated token account for the gate mint,
+    /// validated in the handler (may be closed)
+    pub gate_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddAdmin<'info> {
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ChannelAdmin::INIT_SPACE,
+        seeds = [
+            b"channel_admin",
+            broadcast_channel.key().as_ref(),
+            admin.key().as_ref()
+        ],
+        bump
+    )]
+    pub channel_admin: Account<'info, ChannelAdmin>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Any account can be made an admin
+    pub admin: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Pays the rent, so the owner may be a program-signed PDA holding data
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAdmin<'info> {
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        mut,
+        constraint = channel_admin.channel == broadcast_channel.key()
+            @ MessagingError::UnauthorizedSender,
+        close = owner
+    )]
+    pub channel_admin: Account<'info, ChannelAdmin>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetAdminPermissions<'info> {
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        mut,
+        constraint = channel_admin.channel == broadcast_channel.key()
+            @ MessagingError::UnauthorizedSender
+    )]
+    pub channel_admin: Account<'info, ChannelAdmin>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeChannelTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptChannelTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        constraint = broadcast_channel.pending_owner == new_owner.key()
+            @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub new_owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateChannel<'info> {
+    /// CHECK: May be shorter than the current layout; validated in the handler
+    #[account(mut)]
+    pub broadcast_channel: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    avatar_uri: String,
+    banner_uri: String,
+    category: String,
+    external_link: String
+)]
+pub struct UpdateChannelMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender,
+        realloc = 8 + BroadcastChannel::INIT_SPACE
+            + avatar_uri.len()
+            + banner_uri.len()
+            + category.len()
+            + external_link.len(),
+        realloc::payer = payer,
+        realloc::zero = false
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Pays the rent, so the owner may be a program-signed PDA holding data
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateThreadState<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    pub participant: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AutoArchiveThread<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    /// CHECK: Participant whose preference is applied; checked against the thread
+    pub participant: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"archive_preference", participant.key().as_ref()],
+        bump
+    )]
+    pub archive_preference: Account<'info, ArchivePreference>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_index: u32)]
+pub struct ReactToMessage<'info> {
+    #[account(
+        init_if_needed,
+        payer = reactor,
+        space = 8 + Reaction::INIT_SPACE,
+        seeds = [
+            b"reaction",
+            message_thread.key().as_ref(),
+            &message_index.to_le_bytes(),
+            reactor.key().as_ref()
+        ],
+        bump
+    )]
+    pub reaction: Account<'info, Reaction>,
+
+    #[account(
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    #[account(mut)]
+    pub reactor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_index: u32)]
+pub struct ReactToBroadcast<'info> {
+    #[account(
+        init_if_needed,
+        payer = subscriber,
+        space = 8 + BroadcastReaction::INIT_SPACE,
+        seeds = [
+            b"broadcast_reaction",
+            broadcast_channel.key().as_ref(),
+            &message_index.to_le_bytes(),
+            subscriber.key().as_ref()
+        ],
+        bump
+    )]
+    pub reaction: Account<'info, BroadcastReaction>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        has_one = subscriber @ MessagingError::UnauthorizedSender,
+        constraint = subscription
+            .belongs_to(&broadcast_channel.key(), broadcast_channel.created_at)
+            @ MessagingError::SubscriptionMismatch
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_index: u32)]
+pub struct RevokeMessage<'info> {
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + MessageTombstone::INIT_SPACE,
+        seeds = [
+            b"tombstone",
+            message_thread.key().as_ref(),
+            &message_index.to_le_bytes(),
+            sender.key().as_ref()
+        ],
+        bump
+    )]
+    pub tombstone: Account<'info, MessageTombstone>,
+
+    #[account(
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Stored record of the message, if it was sent with `send_message_stored`
+    pub message_record: Option<Account<'info, MessageRecord>>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_index: u32)]
+pub struct RevokeBroadcast<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MessageTombstone::INIT_SPACE,
+        seeds = [
+            b"tombstone",
+            broadcast_channel.key().as_ref(),
+            &message_index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub tombstone: Account<'info, MessageTombstone>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Pays the rent, so the owner may be a program-signed PDA holding data
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PruneExpiredMessage<'info> {
+    #[account(
+        mut,
+        has_one = sender @ MessagingError::UnauthorizedSender,
+        close = sender
+    )]
+    pub message_record: Account<'info, MessageRecord>,
+
+    /// CHECK: Original sender, receives the rent refund
+    #[account(mut)]
+    pub sender: UncheckedAccount<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PurgeThreadRecords<'info> {
+    /// CHECK: The records' thread; must be closed or re-created since
+    pub message_thread: UncheckedAccount<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimThreadDeposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"thread_vault", message_thread.key().as_ref()],
+        bump,
+        has_one = depositor @ MessagingError::UnauthorizedSender,
+        has_one = recipient @ MessagingError::UnauthorizedSender,
+        close = depositor
+    )]
+    pub thread_vault: Account<'info, ThreadVault>,
+
+    /// CHECK: Thread the deposit was locked for; may already be closed
+    pub message_thread: UncheckedAccount<'info>,
+
+    /// CHECK: Thread initiator, receives refunds and the vault rent
+    #[account(mut)]
+    pub depositor: UncheckedAccount<'info>,
+
+    /// CHECK: Thread recipient, receives forfeited deposits
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SendMessageWithTip<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    /// CHECK: Other participant receiving the tip, checked in the handler
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: Block entry of participant A blocking B; must not exist
+    #[account(
+        seeds = [
+            b"block_entry",
+            message_thread.participant_a.as_ref(),
+            message_thread.participant_b.as_ref()
+        ],
+        bump
+    )]
+    pub block_by_a: UncheckedAccount<'info>,
+
+    /// CHECK: Block entry of participant B blocking A; must not exist
+    #[account(
+        seeds = [
+            b"block_entry",
+            message_thread.participant_b.as_ref(),
+            message_thread.participant_a.as_ref()
+        ],
+        bump
+    )]
+    pub block_by_b: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Tip mint; omit to tip SOL
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub sender_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub recipient_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol treasury PDA, required while a fee is charged
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(request_id: u64)]
+pub struct RequestPayment<'info> {
+    #[account(
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    #[account(
+        init,
+        payer = requester,
+        space = 8 + PaymentRequest::INIT_SPACE,
+        seeds = [
+            b"payment_request",
+            message_thread.key().as_ref(),
+            request_id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub payment_request: Account<'info, PaymentRequest>,
+
+    #[account(mut)]
+    pub requester: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FulfillPayment<'info> {
+    #[account(
+        mut,
+        has_one = payer @ MessagingError::UnauthorizedSender,
+        has_one = requester @ MessagingError::InvalidPaymentAccount
+    )]
+    pub payment_request: Account<'info, PaymentRequest>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Requester, paid directly or refunded the request's rent
+    #[account(mut)]
+    pub requester: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Requested mint; omit for SOL requests
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub payer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Requester's token account, or the request's escrow ATA for escrow requests
+    #[account(mut)]
+    pub destination_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettlePayment<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"payment_request",
+            payment_request.thread.as_ref(),
+            payment_request.request_id.to_le_bytes().as_ref()
+        ],
+        bump,
+        has_one = payer @ MessagingError::InvalidPaymentAccount,
+        has_one = requester @ MessagingError::InvalidPaymentAccount
+    )]
+    pub payment_request: Account<'info, PaymentRequest>,
+
+    pub authority: Signer<'info>,
+
+    /// CHECK: Requester, receives released funds and the request's rent
+    #[account(mut)]
+    pub requester: UncheckedAccount<'info>,
+
+    /// CHECK: Payer, receives refunds and the escrow token account's rent
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+
+    /// Requested mint; omit for SOL requests
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// The request's escrow ATA
+    #[account(mut)]
+    pub escrow_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token account of whoever receives the escrowed tokens
+    #[account(mut)]
+    pub destination_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddContact<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Contact::INIT_SPACE,
+        seeds = [b"contact", owner.key().as_ref(), contact.key().as_ref()],
+        bump
+    )]
+    pub contact_entry: Account<'info, Contact>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Any account can be saved as a contact
+    pub contact: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateContact<'info> {
+    #[account(mut, has_one = owner @ MessagingError::UnauthorizedSender)]
+    pub contact_entry: Account<'info, Contact>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveContact<'info> {
+    #[account(
+        mut,
+        has_one = owner @ MessagingError::UnauthorizedSender,
+        close = owner
+    )]
+    pub contact_entry: Account<'info, Contact>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(handle: String)]
+pub struct ClaimHandle<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Handle::INIT_SPACE,
+        seeds = [b"handle", handle.as_bytes()],
+        bump
+    )]
+    pub handle_entry: Account<'info, Handle>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Protocol treasury PDA, only receives lamports
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct TransferHandle<'info> {
+    #[account(mut, has_one = owner @ MessagingError::UnauthorizedSender)]
+    pub handle_entry: Account<'info, Handle>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Any account can receive a handle
+    pub new_owner: UncheckedAccount<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseHandle<'info> {
+    #[account(
+        mut,
+        has_one = owner @ MessagingError::UnauthorizedSender,
+        close = owner
+    )]
+    pub handle_entry: Account<'info, Handle>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateThread<'info> {
+    /// CHECK: May be shorter than the current layout; validated in the handler
+    #[account(mut)]
+    pub message_thread: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub participant: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateSubscription<'info> {
+    /// CHECK: May be shorter than the current layout; validated in the handler
+    #[account(mut)]
+    pub subscription: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ProgramConfig::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key())
+            @ MessagingError::UnauthorizedSender
+    )]
+    pub program: Program<'info, crate::program::WhatsappSol>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key())
+            @ MessagingError::UnauthorizedSender
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority @ MessagingError::UnauthorizedSender
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EnableThreadCompression<'info> {
+    #[account(
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + CompressedLog::INIT_SPACE,
+        seeds = [b"compressed_log", message_thread.key().as_ref()],
+        bump
+    )]
+    pub compressed_log: Account<'info, CompressedLog>,
+
+    /// CHECK: Pre-allocated concurrent Merkle tree, initialized through CPI
+    #[account(mut, owner = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: spl-account-compression
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: spl-noop
+    #[account(address = SPL_NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EnableChannelCompression<'info> {
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + CompressedLog::INIT_SPACE,
+        seeds = [b"compressed_log", broadcast_channel.key().as_ref()],
+        bump
+    )]
+    pub compressed_log: Account<'info, CompressedLog>,
+
+    /// CHECK: Pre-allocated concurrent Merkle tree, initialized through CPI
+    #[account(mut, owner = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: spl-account-compression
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: spl-noop
+    #[account(address = SPL_NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Pays the rent, so the owner may be a program-signed PDA holding data
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SendMessageCompressed<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    /// CHECK: Block entry of participant A blocking B; must not exist
+    #[account(
+        seeds = [
+            b"block_entry",
+            message_thread.participant_a.as_ref(),
+            message_thread.participant_b.as_ref()
+        ],
+        bump
+    )]
+    pub block_by_a: UncheckedAccount<'info>,
+
+    /// CHECK: Block entry of participant B blocking A; must not exist
+    #[account(
+        seeds = [
+            b"block_entry",
+            message_thread.participant_b.as_ref(),
+            message_thread.participant_a.as_ref()
+        ],
+        bump
+    )]
+    pub block_by_b: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"compressed_log", message_thread.key().as_ref()],
+        bump,
+        has_one = merkle_tree @ MessagingError::InvalidMerkleTree
+    )]
+    pub compressed_log: Account<'info, CompressedLog>,
+
+    /// CHECK: The log's Merkle tree, checked via `has_one`
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: spl-account-compression
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: spl-noop
+    #[account(address = SPL_NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol treasury PDA, required while a fee is charged
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Option<Program<'info, System>>,
+
+    /// Recipient's contact entry for the sender; contacts message fee-free
+    #[account(
+        seeds = [
+            b"contact",
+            message_thread.other_participant(&sender.key()).as_ref(),
+            sender.key().as_ref()
+        ],
+        bump
+    )]
+    pub sender_contact: Option<Account<'info, Contact>>,
+}
+
+#[derive(Accounts)]
+pub struct SendBroadcastCompressed<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub sender: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"compressed_log", broadcast_channel.key().as_ref()],
+        bump,
+        has_one = merkle_tree @ MessagingError::InvalidMerkleTree
+    )]
+    pub compressed_log: Account<'info, CompressedLog>,
+
+    /// CHECK: The log's Merkle tree, checked via `has_one`
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: spl-account-compression
+    #[account(address = SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: spl-noop
+    #[account(address = SPL_NOOP_ID)]
+    pub noop_program: UncheckedAccount<'info>,
+
+    /// Sender's admin role, when the sender is not the owner
+    #[account(
+        seeds = [
+            b"channel_admin",
+            broadcast_channel.key().as_ref(),
+            sender.key().as_ref()
+        ],
+        bump
+    )]
+    pub channel_admin: Option<Account<'info, ChannelAdmin>>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol treasury PDA, required while a fee is charged
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Option<Program<'info, System>>,
+
+    /// Pays the protocol fee in place of the sender; required when the sender is a
+    /// PDA that holds data
+    #[account(mut)]
+    pub fee_payer: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveThreadPointer<'info> {
+    #[account(
+        mut,
+        has_one = owner @ MessagingError::UnauthorizedSender,
+        close = owner
+    )]
+    pub pointer: Account<'info, ThreadPointer>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(page_index: u32)]
+pub struct ListChannelPublic<'info> {
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + ChannelDirectory::INIT_SPACE,
+        seeds = [b"channel_directory"],
+        bump
+    )]
+    pub directory: Account<'info, ChannelDirectory>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + DirectoryPage::INIT_SPACE,
+        seeds = [b"directory_page", page_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub page: Box<Account<'info, DirectoryPage>>,
+
+    /// One listing per channel, so a channel cannot be listed twice
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + DirectoryListing::INIT_SPACE,
+        seeds = [b"directory_listing", broadcast_channel.key().as_ref()],
+        bump
+    )]
+    pub listing: Account<'info, DirectoryListing>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DelistChannel<'info> {
+    #[account(
+        mut,
+        has_one = owner @ MessagingError::UnauthorizedSender,
+        close = owner
+    )]
+    pub listing: Account<'info, DirectoryListing>,
+
+    #[account(mut, seeds = [b"channel_directory"], bump)]
+    pub directory: Account<'info, ChannelDirectory>,
+
+    #[account(
+        mut,
+        seeds = [b"directory_page", listing.page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub page: Box<Account<'info, DirectoryPage>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: May be shorter than the current layout; validated in the handler
+    #[account(mut, seeds = [b"config"], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetChannelVerification<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = verifier @ MessagingError::UnauthorizedSender
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub verifier: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetChannelSuspicion<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = authority @ MessagingError::UnauthorizedSender
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptConfigAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.pending_authority == new_authority.key()
+            @ MessagingError::UnauthorizedSender
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawProtocolFees<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = authority @ MessagingError::UnauthorizedSender,
+        has_one = treasury @ MessagingError::UnauthorizedSender
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    /// CHECK: Protocol treasury PDA holding collected fees
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Fee destination recorded in the config
+    #[account(mut)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SendMessageRelayed<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    /// CHECK: True sender, authorized by the Ed25519 signature instead of a transaction signature
+    pub sender: UncheckedAccount<'info>,
+
+    /// Pays the transaction and protocol fees
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    /// CHECK: Block entry of participant A blocking B; must not exist
+    #[account(
+        seeds = [
+            b"block_entry",
+            message_thread.participant_a.as_ref(),
+            message_thread.participant_b.as_ref()
+        ],
+        bump
+    )]
+    pub block_by_a: UncheckedAccount<'info>,
+
+    /// CHECK: Block entry of participant B blocking A; must not exist
+    #[account(
+        seeds = [
+            b"block_entry",
+            message_thread.participant_b.as_ref(),
+            message_thread.participant_a.as_ref()
+        ],
+        bump
+    )]
+    pub block_by_b: UncheckedAccount<'info>,
+
+    /// CHECK: Instructions sysvar, used to inspect the Ed25519 instruction
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol treasury PDA, required while a fee is charged
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Option<Program<'info, System>>,
+
+    /// Recipient's contact entry for the sender; contacts message fee-free
+    #[account(
+        seeds = [
+            b"contact",
+            message_thread.other_participant(&sender.key()).as_ref(),
+            sender.key().as_ref()
+        ],
+        bump
+    )]
+    pub sender_contact: Option<Account<'info, Contact>>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSessionKey<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + SessionKey::INIT_SPACE,
+        seeds = [b"session_key", owner.key().as_ref(), device.key().as_ref()],
+        bump
+    )]
+    pub session_key: Account<'info, SessionKey>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Device key receiving the sending rights
+    pub device: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSessionKey<'info> {
+    #[account(
+        mut,
+        has_one = owner @ MessagingError::UnauthorizedSender,
+        close = owner
+    )]
+    pub session_key: Account<'info, SessionKey>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(broadcast_index: u32)]
+pub struct ReplyToBroadcast<'info> {
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        constraint = subscription.subscriber == commenter.key()
+            @ MessagingError::UnauthorizedSender,
+        constraint = subscription
+            .belongs_to(&broadcast_channel.key(), broadcast_channel.created_at)
+            @ MessagingError::SubscriptionMismatch
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    #[account(
+        init_if_needed,
+        payer = commenter,
+        space = 8 + BroadcastComments::INIT_SPACE,
+        seeds = [
+            b"broadcast_comments",
+            broadcast_channel.key().as_ref(),
+            &broadcast_index.to_le_bytes()
+        ],
+        bump
+    )]
+    pub broadcast_comments: Account<'info, BroadcastComments>,
+
+    #[account(mut)]
+    pub commenter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_index: u32, options: Vec<String>)]
+pub struct CreatePoll<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Poll::INIT_SPACE,
+        seeds = [b"poll", target.key().as_ref(), &message_index.to_le_bytes()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    /// CHECK: Broadcast channel or message thread holding the message, parsed by the handler
+    pub target: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(
+        mut,
+        seeds = [b"poll", poll.target.as_ref(), &poll.message_index.to_le_bytes()],
+        bump
+    )]
+    pub poll: Account<'info, Poll>,
+
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + PollVote::INIT_SPACE,
+        seeds = [b"poll_vote", poll.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub poll_vote: Account<'info, PollVote>,
+
+    /// CHECK: The poll's channel or thread, parsed by the handler
+    #[account(address = poll.target @ MessagingError::InvalidPollTarget)]
+    pub target: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Voter's subscription, required for channel polls
+    pub subscription: Option<Account<'info, ChannelSubscription>>,
+}
+
+#[derive(Accounts)]
+pub struct ClosePoll<'info> {
+    #[account(mut, has_one = creator @ MessagingError::UnauthorizedSender)]
+    pub poll: Account<'info, Poll>,
+
+    pub creator: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateMessageRecord<'info> {
+    /// CHECK: May be shorter than the current layout; validated in the handler
+    #[account(mut)]
+    pub message_record: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(invitee: Option<Pubkey>, code_hash: Option<[u8; 32]>)]
+pub struct CreateChannelInvite<'info> {
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ChannelInvite::INIT_SPACE,
+        seeds = [
+            b"channel_invite",
+            broadcast_channel.key().as_ref(),
+            invite_seed(&invitee, &code_hash).as_ref()
+        ],
+        bump
+    )]
+    pub channel_invite: Account<'info, ChannelInvite>,
+
+    pub creator: Signer<'info>,
+
+    /// Creator's admin role, when the creator is not the owner
+    #[account(
+        seeds = [
+            b"channel_admin",
+            broadcast_channel.key().as_ref(),
+            creator.key().as_ref()
+        ],
+        bump
+    )]
+    pub channel_admin: Option<Account<'info, ChannelAdmin>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Pays the rent, so the owner or admin may be a program-signed PDA
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeInvite<'info> {
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        mut,
+        constraint = channel_invite.channel == broadcast_channel.key()
+            @ MessagingError::InvalidInvite,
+        close = invite_creator
+    )]
+    pub channel_invite: Account<'info, ChannelInvite>,
+
+    pub authority: Signer<'info>,
+
+    /// CHECK: Receives the rent; must be the invite's creator
+    #[account(mut, address = channel_invite.created_by @ MessagingError::UnauthorizedSender)]
+    pub invite_creator: UncheckedAccount<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BanSubscriber<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        init,
+        payer = moderator,
+        space = 8 + BanEntry::INIT_SPACE,
+        seeds = [
+            b"channel_ban",
+            broadcast_channel.key().as_ref(),
+            subscriber.key().as_ref()
+        ],
+        bump
+    )]
+    pub ban_entry: Account<'info, BanEntry>,
+
+    /// CHECK: Wallet being banned; receives its subscription's rent
+    #[account(mut)]
+    pub subscriber: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub moderator: Signer<'info>,
+
+    /// Moderator's admin role, when the moderator is not the owner
+    #[account(
+        seeds = [
+            b"channel_admin",
+            broadcast_channel.key().as_ref(),
+            moderator.key().as_ref()
+        ],
+        bump
+    )]
+    pub channel_admin: Option<Account<'info, ChannelAdmin>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// The banned wallet's subscription, closed by the ban
+    #[account(
+        mut,
+        seeds = [
+            b"subscription",
+            broadcast_channel.key().as_ref(),
+            subscriber.key().as_ref()
+        ],
+        bump = subscription.bump,
+        constraint = subscription
+            .belongs_to(&broadcast_channel.key(), broadcast_channel.created_at)
+            @ MessagingError::SubscriptionMismatch,
+        close = subscriber
+    )]
+    pub subscription: Option<Account<'info, ChannelSubscription>>,
+}
+
+#[derive(Accounts)]
+pub struct UnbanSubscriber<'info> {
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        mut,
+        constraint = ban_entry.channel == broadcast_channel.key()
+            @ MessagingError::UnauthorizedSender,
+        close = moderator
+    )]
+    pub ban_entry: Account<'info, BanEntry>,
+
+    #[account(mut)]
+    pub moderator: Signer<'info>,
+
+    /// Moderator's admin role, when the moderator is not the owner
+    #[account(
+        seeds = [
+            b"channel_admin",
+            broadcast_channel.key().as_ref(),
+            moderator.key().as_ref()
+        ],
+        bump
+    )]
+    pub channel_admin: Option<Account<'info, ChannelAdmin>>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(message_index: u32)]
+pub struct ReportContent<'info> {
+    #[account(
+        init,
+        payer = reporter,
+        space = 8 + Report::INIT_SPACE,
+        seeds = [
+            b"report",
+            target.key().as_ref(),
+            &message_index.to_le_bytes(),
+            reporter.key().as_ref()
+        ],
+        bump
+    )]
+    pub report: Account<'info, Report>,
+
+    /// CHECK: Broadcast channel or message thread holding the message, parsed by the handler
+    pub target: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveReport<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = authority @ MessagingError::UnauthorizedSender
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(mut, close = reporter)]
+    pub report: Account<'info, Report>,
+
+    /// CHECK: Receives the report's rent; must be its reporter
+    #[account(mut, address = report.reporter @ MessagingError::UnauthorizedSender)]
+    pub reporter: UncheckedAccount<'info>,
+
+    /// CHECK: Protocol treasury PDA, only receives lamports
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(release_at: i64, content_hash: [u8; 32])]
+pub struct ScheduleBroadcast<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ScheduledBroadcast::INIT_SPACE,
+        seeds = [
+            b"scheduled_broadcast",
+            broadcast_channel.key().as_ref(),
+            content_hash.as_ref()
+        ],
+        bump
+    )]
+    pub scheduled_broadcast: Account<'info, ScheduledBroadcast>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    /// Channel owner or admin
+    pub sender: Signer<'info>,
+
+    /// Sender's admin role, when the sender is not the owner
+    #[account(
+        seeds = [
+            b"channel_admin",
+            broadcast_channel.key().as_ref(),
+            sender.key().as_ref()
+        ],
+        bump
+    )]
+    pub channel_admin: Option<Account<'info, ChannelAdmin>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Pays the rent and the protocol fee, so the sender may be a program-signed PDA
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Protocol treasury PDA, required while a fee is charged
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    /// Channel's index of pending scheduled broadcasts, created by the first one
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ScheduleIndex::INIT_SPACE,
+        seeds = [b"schedule_index", broadcast_channel.key().as_ref()],
+        bump
+    )]
+    pub schedule_index: Account<'info, ScheduleIndex>,
+}
+
+#[derive(Accounts)]
+pub struct PublishScheduledBroadcast<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    /// Closed by the handler unless it recurs
+    #[account(
+        mut,
+        constraint = scheduled_broadcast.channel == broadcast_channel.key()
+            @ MessagingError::InvalidChannelAccount
+    )]
+    pub scheduled_broadcast: Account<'info, ScheduledBroadcast>,
+
+    /// CHECK: Receives the scheduled account's rent; must be its author
+    #[account(mut, address = scheduled_broadcast.author @ MessagingError::UnauthorizedSender)]
+    pub author: UncheckedAccount<'info>,
+
+    /// Author's admin role, when the author is not the owner
+    #[account(
+        seeds = [
+            b"channel_admin",
+            broadcast_channel.key().as_ref(),
+            author.key().as_ref()
+        ],
+        bump
+    )]
+    pub channel_admin: Option<Account<'info, ChannelAdmin>>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"schedule_index", broadcast_channel.key().as_ref()],
+        bump
+    )]
+    pub schedule_index: Account<'info, ScheduleIndex>,
+}
+
+#[derive(Accounts)]
+pub struct CancelScheduledBroadcast<'info> {
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        mut,
+        close = author,
+        constraint = scheduled_broadcast.channel == broadcast_channel.key()
+            @ MessagingError::InvalidChannelAccount
+    )]
+    pub scheduled_broadcast: Account<'info, ScheduledBroadcast>,
+
+    /// CHECK: Receives the scheduled account's rent; must be its author
+    #[account(mut, address = scheduled_broadcast.author @ MessagingError::UnauthorizedSender)]
+    pub author: UncheckedAccount<'info>,
+
+    /// Author or channel owner
+    pub signer: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"schedule_index", broadcast_channel.key().as_ref()],
+        bump
+    )]
+    pub schedule_index: Account<'info, ScheduleIndex>,
+}
+
+#[derive(Accounts)]
+pub struct PinMessage<'info> {
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + PinnedMessages::INIT_SPACE,
+        seeds = [b"pinned_messages", target.key().as_ref()],
+        bump
+    )]
+    pub pinned_messages: Account<'info, PinnedMessages>,
+
+    /// CHECK: Broadcast channel or message thread, parsed by the handler
+    pub target: UncheckedAccount<'info>,
+
+    /// Thread participant, or channel owner or admin
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    /// Signer's admin role on a channel target, when the signer is not the owner
+    #[account(
+        seeds = [b"channel_admin", target.key().as_ref(), signer.key().as_ref()],
+        bump
+    )]
+    pub channel_admin: Option<Account<'info, ChannelAdmin>>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnpinMessage<'info> {
+    #[account(
+        mut,
+        seeds = [b"pinned_messages", target.key().as_ref()],
+        bump
+    )]
+    pub pinned_messages: Account<'info, PinnedMessages>,
+
+    /// CHECK: Broadcast channel or message thread, parsed by the handler
+    pub target: UncheckedAccount<'info>,
+
+    pub signer: Signer<'info>,
+
+    /// Signer's admin role on a channel target, when the signer is not the owner
+    #[account(
+        seeds = [b"channel_admin", target.key().as_ref(), signer.key().as_ref()],
+        bump
+    )]
+    pub channel_admin: Option<Account<'info, ChannelAdmin>>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(device_key: [u8; 32])]
+pub struct RegisterDevice<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Device::INIT_SPACE,
+        seeds = [b"device", owner.key().as_ref(), device_key.as_ref()],
+        bump
+    )]
+    pub device: Account<'info, Device>,
+
+    #[account(
+        seeds = [b"user_profile", owner.key().as_ref()],
+        bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SyncDevice<'info> {
+    #[account(
+        mut,
+        seeds = [b"device", owner.key().as_ref(), device.device_key.as_ref()],
+        bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub device: Account<'info, Device>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDevice<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"device", owner.key().as_ref(), device.device_key.as_ref()],
+        bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub device: Account<'info, Device>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PostChannelKeyWrap<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ChannelKeyWrap::INIT_SPACE,
+        seeds = [
+            b"channel_key_wrap",
+            broadcast_channel.key().as_ref(),
+            subscription.subscriber.as_ref()
+        ],
+        bump
+    )]
+    pub key_wrap: Account<'info, ChannelKeyWrap>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        seeds = [
+            b"subscription",
+            broadcast_channel.key().as_ref(),
+            subscription.subscriber.as_ref()
+        ],
+        bump = subscription.bump,
+        constraint = subscription
+            .belongs_to(&broadcast_channel.key(), broadcast_channel.created_at)
+            @ MessagingError::SubscriptionMismatch
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Pays the rent, so the owner may be a program-signed PDA holding data
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseChannelKeyWrap<'info> {
+    #[account(
+        mut,
+        close = owner,
+        constraint = key_wrap.channel == broadcast_channel.key()
+            @ MessagingError::InvalidChannelAccount
+    )]
+    pub key_wrap: Account<'info, ChannelKeyWrap>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimDmFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"dm_escrow", message_thread.key().as_ref()],
+        bump,
+        has_one = sender @ MessagingError::UnauthorizedSender,
+        has_one = recipient @ MessagingError::UnauthorizedSender,
+        close = sender
+    )]
+    pub dm_escrow: Account<'info, DmEscrow>,
+
+    /// CHECK: Thread the fee was paid for; may already be closed
+    pub message_thread: UncheckedAccount<'info>,
+
+    /// CHECK: Thread initiator, receives refunds and the escrow rent
+    #[account(mut)]
+    pub sender: UncheckedAccount<'info>,
+
+    /// CHECK: Thread recipient, receives released fees
+    #[account(mut)]
+    pub recipient: UncheckedAccount<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateProfile<'info> {
+    /// CHECK: May be shorter than the current layout; validated in the handler
+    #[account(mut)]
+    pub user_profile: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(escrow_id: u64)]
+pub struct OpenEscrow<'info> {
+    #[account(
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    #[account(
+        init,
+        payer = depositor,
+        space = 8 + ThreadEscrow::INIT_SPACE,
+        seeds = [
+            b"thread_escrow",
+            message_thread.key().as_ref(),
+            escrow_id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub thread_escrow: Account<'info, ThreadEscrow>,
+
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Escrowed mint; omit for SOL
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub depositor_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The escrow's ATA, created beforehand
+    #[account(mut)]
+    pub escrow_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"thread_escrow",
+            thread_escrow.thread.as_ref(),
+            thread_escrow.escrow_id.to_le_bytes().as_ref()
+        ],
+        bump,
+        has_one = depositor @ MessagingError::InvalidPaymentAccount,
+        has_one = beneficiary @ MessagingError::InvalidPaymentAccount
+    )]
+    pub thread_escrow: Account<'info, ThreadEscrow>,
+
+    pub authority: Signer<'info>,
+
+    /// CHECK: Depositor, receives refunds and the escrow's rent
+    #[account(mut)]
+    pub depositor: UncheckedAccount<'info>,
+
+    /// CHECK: Beneficiary, receives released funds
+    #[account(mut)]
+    pub beneficiary: UncheckedAccount<'info>,
+
+    /// Escrowed mint; omit for SOL
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// The escrow's ATA
+    #[account(mut)]
+    pub escrow_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Token account of whoever receives the escrowed tokens
+    #[account(mut)]
+    pub destination_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(day: u32)]
+pub struct OpenChannelStats<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ChannelStats::INIT_SPACE,
+        seeds = [b"channel_stats", broadcast_channel.key().as_ref(), &day.to_le_bytes()],
+        bump
+    )]
+    pub channel_stats: Account<'info, ChannelStats>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseStaleStats<'info> {
+    #[account(
+        mut,
+        close = payer,
+        has_one = payer @ MessagingError::UnauthorizedSender
+    )]
+    pub channel_stats: Account<'info, ChannelStats>,
+
+    /// CHECK: Paid for the stats account and receives its rent
+    #[account(mut)]
+    pub payer: UncheckedAccount<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateNotificationInbox<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + NotificationInbox::INIT_SPACE,
+        seeds = [b"notification_inbox", owner.key().as_ref()],
+        bump
+    )]
+    pub notification_inbox: Account<'info, NotificationInbox>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(program: Pubkey)]
+pub struct ApproveNotifier<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = authority @ MessagingError::UnauthorizedSender
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Notifier::INIT_SPACE,
+        seeds = [b"notifier", program.as_ref()],
+        bump
+    )]
+    pub notifier: Account<'info, Notifier>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeNotifier<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = authority @ MessagingError::UnauthorizedSender
+    )]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"notifier", notifier.program.as_ref()],
+        bump
+    )]
+    pub notifier: Account<'info, Notifier>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DeliverNotification<'info> {
+    #[account(
+        mut,
+        seeds = [b"notification_inbox", notification_inbox.owner.as_ref()],
+        bump
+    )]
+    pub notification_inbox: Account<'info, NotificationInbox>,
+
+    /// Whitelisted program's `["notifier"]` PDA, or any fee-paying wallet
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Whitelist entry of the calling program; omit to pay the fee instead
+    #[account(
+        seeds = [b"notifier", notifier.program.as_ref()],
+        bump
+    )]
+    pub notifier: Option<Account<'info, Notifier>>,
+
+    /// CHECK: Protocol treasury PDA, required when the fee is paid
+    #[account(mut, seeds = [b"treasury"], bump)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Option<Program<'info, System>>,
+}
+
+#[derive(Accounts)]
+#[instruction(snapshot_id: u64)]
+pub struct SnapshotSubscribers<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + SubscriberSnapshot::INIT_SPACE,
+        seeds = [
+            b"subscriber_snapshot",
+            broadcast_channel.key().as_ref(),
+            snapshot_id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub snapshot: Account<'info, SubscriberSnapshot>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Pays the rent, so the owner may be a program-signed PDA holding data
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifySnapshotClaim<'info> {
+    pub snapshot: Account<'info, SubscriberSnapshot>,
+}
+
+#[derive(Accounts)]
+pub struct CloseOrphanedSubscription<'info> {
+    #[account(
+        mut,
+        has_one = subscriber @ MessagingError::UnauthorizedSender,
+        constraint = subscription.channel == broadcast_channel.key()
+            @ MessagingError::SubscriptionMismatch,
+        close = subscriber
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    /// CHECK: The subscription's channel; must be closed or re-created since
+    pub broadcast_channel: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RequestThreadClose<'info> {
+    #[account(
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
         ],
+        bump = message_thread.bump,
+        constraint = message_thread.is_participant(&proposer.key())
+            @ MessagingError::NotAParticipant
+    )]
+    pub message_thread: Account<'info, MessageThread>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + ThreadCloseRequest::INIT_SPACE,
+        seeds = [b"thread_close_request", message_thread.key().as_ref()],
+        bump
+    )]
+    pub close_request: Account<'info, ThreadCloseRequest>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VetoThreadClose<'info> {
+    /// CHECK: Thread the request targets; may already be closed
+    pub message_thread: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"thread_close_request", message_thread.key().as_ref()],
+        bump,
+        has_one = proposer @ MessagingError::UnauthorizedSender,
+        close = proposer
+    )]
+    pub close_request: Account<'info, ThreadCloseRequest>,
+
+    /// CHECK: Participant who requested the close, receives the request rent
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+
+    pub participant: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(content_hash: [u8; 32])]
+pub struct PostStatus<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Status::INIT_SPACE,
+        seeds = [b"status", user_profile.key().as_ref(), content_hash.as_ref()],
+        bump
+    )]
+    pub status: Account<'info, Status>,
+
+    #[account(
+        seeds = [b"user_profile", owner.key().as_ref()],
+        bump,
+        has_one = owner @ MessagingError::UnauthorizedSender
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ViewStatus<'info> {
+    #[account(mut)]
+    pub status: Account<'info, Status>,
+
+    #[account(
+        init,
+        payer = viewer,
+        space = 8 + StatusView::INIT_SPACE,
+        seeds = [b"status_view", status.key().as_ref(), viewer.key().as_ref()],
+        bump
+    )]
+    pub status_view: Account<'info, StatusView>,
+
+    /// The poster's contact entry for the viewer; only contacts leave receipts
+    #[account(
+        seeds = [b"contact", status.owner.as_ref(), viewer.key().as_ref()],
         bump
     )]
+    pub contact_entry: Account<'info, Contact>,
+
+    #[account(mut)]
+    pub viewer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExpireStatus<'info> {
+    #[account(
+        mut,
+        has_one = owner @ MessagingError::UnauthorizedSender,
+        close = owner
+    )]
+    pub status: Account<'info, Status>,
+
+    /// CHECK: Poster, receives the status rent
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseStatusView<'info> {
+    #[account(
+        mut,
+        seeds = [b"status_view", status.key().as_ref(), viewer.key().as_ref()],
+        bump,
+        has_one = viewer @ MessagingError::UnauthorizedSender,
+        close = viewer
+    )]
+    pub status_view: Account<'info, StatusView>,
+
+    /// CHECK: Status the receipt was left on; may already be closed
+    pub status: UncheckedAccount<'info>,
+
+    /// CHECK: Viewer who left the receipt, receives its rent
+    #[account(mut)]
+    pub viewer: UncheckedAccount<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitiateCall<'info> {
+    #[account(
+        seeds = [
+            b"message_thread",
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .0
+                .as_ref(),
+            sorted_participants(&message_thread.participant_a, &message_thread.participant_b)
+                .1
+                .as_ref(),
+            message_thread.thread_id.as_ref()
+        ],
+        bump = message_thread.bump,
+        constraint = message_thread.is_participant(&caller.key())
+            @ MessagingError::NotAParticipant
+    )]
     pub message_thread: Account<'info, MessageThread>,
+
+    /// One call per thread at a time
+    #[account(
+        init,
+        payer = caller,
+        space = 8 + CallSession::INIT_SPACE,
+        seeds = [b"call_session", message_thread.key().as_ref()],
+        bump
+    )]
+    pub call_session: Account<'info, CallSession>,
+
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AnswerCall<'info> {
+    #[account(
+        mut,
+        seeds = [b"call_session", call_session.thread.as_ref()],
+        bump,
+        has_one = callee @ MessagingError::UnauthorizedSender
+    )]
+    pub call_session: Account<'info, CallSession>,
+
+    pub callee: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EndCall<'info> {
+    #[account(
+        mut,
+        seeds = [b"call_session", call_session.thread.as_ref()],
+        bump,
+        has_one = caller @ MessagingError::UnauthorizedSender,
+        close = caller
+    )]
+    pub call_session: Account<'info, CallSession>,
+
+    /// CHECK: Participant who started the call and paid the session rent
+    #[account(mut)]
+    pub caller: UncheckedAccount<'info>,
+
+    pub ended_by: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRewardPool<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RewardPool::INIT_SPACE,
+        seeds = [b"reward_pool", broadcast_channel.key().as_ref()],
+        bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::NotChannelOwner
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub owner: Signer<'info>,
+
+    /// Reward token
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+
+    /// Pays the rent, so the owner may be a program-signed PDA holding data
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRewardPool<'info> {
+    #[account(
+        mut,
+        seeds = [b"reward_pool", broadcast_channel.key().as_ref()],
+        bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::NotChannelOwner
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"reward_pool", broadcast_channel.key().as_ref()],
+        bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    #[account(
+        seeds = [
+            b"subscription",
+            broadcast_channel.key().as_ref(),
+            subscriber.key().as_ref()
+        ],
+        bump = subscription.bump,
+        has_one = subscriber @ MessagingError::UnauthorizedSender,
+        constraint = subscription
+            .belongs_to(&broadcast_channel.key(), broadcast_channel.created_at)
+            @ MessagingError::SubscriptionMismatch
+    )]
+    pub subscription: Account<'info, ChannelSubscription>,
+
+    #[account(
+        init_if_needed,
+        payer = subscriber,
+        space = 8 + RewardClaim::INIT_SPACE,
+        seeds = [b"reward_claim", reward_pool.key().as_ref(), subscriber.key().as_ref()],
+        bump
+    )]
+    pub reward_claim: Account<'info, RewardClaim>,
+
+    #[account(mut)]
+    pub subscriber: Signer<'info>,
+
+    #[account(address = reward_pool.mint @ MessagingError::InvalidPaymentAccount)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// The pool's ATA
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &reward_pool.key(),
+            &reward_pool.mint,
+            &token_program.key()
+        ) @ MessagingError::InvalidPaymentAccount
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Subscriber's token account for the reward mint
+    #[account(
+        mut,
+        constraint = destination_token_account.owner == subscriber.key()
+            @ MessagingError::InvalidPaymentAccount
+    )]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawRewards<'info> {
+    #[account(
+        seeds = [b"reward_pool", broadcast_channel.key().as_ref()],
+        bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(
+        seeds = [
+            b"broadcast_channel",
+            broadcast_channel.creator.as_ref(),
+            broadcast_channel.channel_name.as_bytes()
+        ],
+        bump = broadcast_channel.bump,
+        has_one = owner @ MessagingError::NotChannelOwner
+    )]
+    pub broadcast_channel: Account<'info, BroadcastChannel>,
+
+    pub owner: Signer<'info>,
+
+    #[account(address = reward_pool.mint @ MessagingError::InvalidPaymentAccount)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// The pool's ATA
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &reward_pool.key(),
+            &reward_pool.mint,
+            &token_program.key()
+        ) @ MessagingError::InvalidPaymentAccount
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: Program config PDA, read for the pause switch; may not exist yet
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = !is_paused(&config)? @ MessagingError::ProgramPaused
+    )]
+    pub config: UncheckedAccount<'info>,
+}
+
+// ============================================================================
+// Account Structures
+// ============================================================================
+
+#[account]
+#[derive(InitSpace)]
+pub struct MessageThread {
+    /// First participant (thread initiator)
+    pub participant_a: Pubkey,
+    
+    /// Second participant
+    pub participant_b: Pubkey,
+    
+    /// Unique thread identifier
+    pub thread_id: [u8; 32],
+    
+    /// Total number of messages sent
+    pub message_count: u64,
+    
+    /// Timestamp of thread creation
+    pub created_at: i64,
+    
+    /// Timestamp of last message
+    pub last_message_at: i64,
+
+    /// Consent state of the thread, decided by participant B
+    pub status: ThreadStatus,
+
+    /// Sender of the latest message
+    pub last_sender: Pubkey,
+
+    /// Hash of the latest message's encrypted content (updated on edit)
+    pub last_content_hash: [u8; 32],
+
+    /// Participant currently on a sending streak
+    pub consecutive_sender: Pubkey,
+
+    /// Messages sent in a row by `consecutive_sender`
+    pub consecutive_count: u32,
+
+    /// Streak length after which a reply is required (0 = unlimited)
+    pub max_consecutive: u32,
+
+    /// Message count last observed by participant A's client
+    pub a_observed_count: u64,
+
+    /// Message count last observed by participant B's client
+    pub b_observed_count: u64,
+
+    /// Messages participant A has read (read receipt)
+    pub last_read_by_a: u64,
+
+    /// Messages participant B has read (read receipt)
+    pub last_read_by_b: u64,
+
+    /// Participant A's profile key version at thread creation (0 = no profile)
+    pub a_key_version: u32,
+
+    /// Participant B's profile key version at thread creation (0 = no profile)
+    pub b_key_version: u32,
+
+    /// Whether the thread is active, archived or closed
+    pub lifecycle: ThreadLifecycle,
+
+    /// Participant A has muted notifications for this thread
+    pub a_muted: bool,
+
+    /// Participant B has muted notifications for this thread
+    pub b_muted: bool,
+
+    /// Lifetime of stored message records in seconds (0 = keep forever)
+    pub message_ttl_seconds: i64,
+
+    /// TTL proposed by `ttl_proposer`, applied once the other participant agrees
+    pub pending_ttl_seconds: i64,
+
+    /// Participant with an open TTL proposal (default pubkey = none)
+    pub ttl_proposer: Pubkey,
+
+    /// Minimum seconds between consecutive messages (0 = unlimited)
+    pub min_message_interval: i64,
+
+    /// PDA bump, validated by the send and close paths
+    pub bump: u8,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Message-request lifecycle of a thread
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ThreadStatus {
+    /// Opened by participant A; only the initial request message is allowed
+    Pending,
+    /// Participant B accepted (explicitly or by replying)
+    Accepted,
+    /// Participant B declined; nobody can send
+    Rejected,
+}
+
+/// Visibility lifecycle of a thread, controlled by either participant
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ThreadLifecycle {
+    /// Normal thread
+    Active,
+    /// Hidden from the inbox; a new message makes it active again
+    Archived,
+    /// No messages can be sent until it is reopened
+    Closed,
+}
+
+impl MessageThread {
+    pub fn is_participant(&self, key: &Pubkey) -> bool {
+        *key == self.participant_a || *key == self.participant_b
+    }
+
+    /// Whether `key` has muted notifications for this thread
+    pub fn is_muted_by(&self, key: &Pubkey) -> bool {
+        if *key == self.participant_a {
+            self.a_muted
+        } else {
+            *key == self.participant_b && self.b_muted
+        }
+    }
+
+    /// The participant on the other side from `key`
+    pub fn other_participant(&self, key: &Pubkey) -> Pubkey {
+        if *key == self.participant_a {
+            self.participant_b
+        } else {
+            self.participant_a
+        }
+    }
+
+    /// Apply the send rules for a new message and advance the thread
+    pub fn record_message(
+        &mut self,
+        sender: Pubkey,
+        message_index: u32,
+        content_hash: [u8; 32],
+        timestamp: i64,
+    ) -> Result<()> {
+        // Verify sender is a participant
+        require!(
+            self.is_participant(&sender),
+            MessagingError::UnauthorizedSender
+        );
+
+        require!(
+            self.lifecycle != ThreadLifecycle::Closed,
+            MessagingError::ThreadClosed
+        );
+        self.lifecycle = ThreadLifecycle::Active;
+
+        // The initiator gets a single request message, then must wait for
+        // participant B to accept; participant B replying counts as accepting
+        match self.status {
+            ThreadStatus::Accepted => {}
+            ThreadStatus::Rejected => return err!(MessagingError::ThreadRejected),
+            ThreadStatus::Pending => {
+                if sender == self.participant_b {
+                    self.status = ThreadStatus::Accepted;
+                } else {
+                    require!(self.message_count == 0, MessagingError::ThreadNotAccepted);
+                }
+            }
+        }
+
+        // Verify message index is sequential
+        require!(
+            u64::from(message_index) == self.message_count,
+            MessagingError::InvalidMessageIndex
+        );
+
+        // Enforce the minimum spacing between messages
+        if self.min_message_interval > 0 && self.message_count > 0 {
+            require!(
+                timestamp - self.last_message_at >= self.min_message_interval,
+                MessagingError::RateLimited
+            );
+        }
+
+        // Limit one-sided flooding: the streak resets when the other party replies
+        if sender == self.consecutive_sender {
+            require!(
+                self.max_consecutive == 0 || self.consecutive_count < self.max_consecutive,
+                MessagingError::AwaitingReply
+            );
+            self.consecutive_count = self
+                .consecutive_count
+                .checked_add(1)
+                .ok_or(MessagingError::Overflow)?;
+        } else {
+            self.consecutive_sender = sender;
+            self.consecutive_count = 1;
+        }
+
+        self.message_count = self
+            .message_count
+            .checked_add(1)
+            .ok_or(MessagingError::Overflow)?;
+        self.last_message_at = timestamp;
+        self.last_sender = sender;
+        self.last_content_hash = content_hash;
+
+        // Sending implies having read everything up to and including this message
+        if sender == self.participant_a {
+            self.last_read_by_a = self.message_count;
+        } else {
+            self.last_read_by_b = self.message_count;
+        }
+
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct BroadcastChannel {
+    /// Channel owner (broadcaster)
+    pub owner: Pubkey,
     
-    #[account(mut)]
-    pub participant_a: Signer<'info>,
+    /// Channel name (max 32 chars)
+    #[max_len(32)]
+    pub channel_name: String,
     
-    /// CHECK: Participant B doesn't need to sign for initialization
-    pub participant_b: UncheckedAccount<'info>,
+    /// Total messages broadcast
+    pub message_count: u64,
     
-    pub system_program: Program<'info, System>,
+    /// Number of subscribers
+    pub subscriber_count: u64,
+    
+    /// Timestamp of channel creation
+    pub created_at: i64,
+    
+    /// Timestamp of last broadcast
+    pub last_broadcast_at: i64,
+
+    /// Channel description (max 200 chars)
+    #[max_len(200)]
+    pub description: String,
+
+    /// Off-chain metadata such as avatar and links (max 200 chars)
+    #[max_len(200)]
+    pub metadata_uri: String,
+
+    /// Lamports tipped to the owner by subscribers
+    pub total_tips: u64,
+
+    /// Whether new subscribers get a welcome thread from the owner
+    pub auto_welcome_thread: bool,
+
+    /// Hash of the welcome message content seeded into welcome threads
+    pub welcome_content_hash: [u8; 32],
+
+    /// Lamports charged per subscription period (0 = free)
+    pub subscription_fee_lamports: u64,
+
+    /// Seconds a paid subscription lasts before renewal (0 = never expires)
+    pub subscription_period_seconds: i64,
+
+    /// SPL mint subscribers must hold (default pubkey = not gated)
+    pub gate_mint: Pubkey,
+
+    /// Minimum `gate_mint` balance, in base units, required to stay subscribed
+    pub gate_min_balance: u64,
+
+    /// Verified NFT collection subscribers must hold (default pubkey = not gated)
+    pub gate_collection: Pubkey,
+
+    /// Original owner; the PDA is seeded by this key, so it never changes
+    pub creator: Pubkey,
+
+    /// Proposed new owner awaiting acceptance (default pubkey = none)
+    pub pending_owner: Pubkey,
+
+    /// Avatar image URI (max 200 chars; space is reallocated on update)
+    #[max_len(0)]
+    pub avatar_uri: String,
+
+    /// Banner image URI (max 200 chars; space is reallocated on update)
+    #[max_len(0)]
+    pub banner_uri: String,
+
+    /// Discovery category tag (max 32 chars; space is reallocated on update)
+    #[max_len(0)]
+    pub category: String,
+
+    /// External website link (max 200 chars; space is reallocated on update)
+    #[max_len(0)]
+    pub external_link: String,
+
+    /// Minimum seconds between broadcasts (0 = unlimited)
+    pub min_broadcast_interval: i64,
+
+    /// PDA bump, validated by the send, subscribe and close paths
+    pub bump: u8,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+
+    /// Verified badge granted by the config's verifier
+    pub verified: bool,
+
+    /// When the badge was granted (0 = not verified)
+    pub verified_at: i64,
+
+    /// Whether subscribers may comment on broadcasts
+    pub comments_enabled: bool,
+
+    /// Invite-only: subscribing consumes a `ChannelInvite`
+    pub private: bool,
+
+    /// Current channel key generation, bumped by `rekey_channel`
+    pub key_epoch: u32,
+
+    /// Active, or closing after the first `close_channel`
+    pub status: ChannelStatus,
+
+    /// When the channel started closing (0 = active)
+    pub closing_at: i64,
+
+    /// Flagged by the config authority as a suspected scam
+    pub suspicious: bool,
+
+    /// `Report` reason constant behind the flag (0 when not flagged)
+    pub reason_code: u8,
+}
+
+impl BroadcastChannel {
+    /// Shared broadcast path: authorization, sequential index, rate limit and counters
+    pub fn record_broadcast(
+        &mut self,
+        authorized: bool,
+        message_index: u32,
+        now: i64,
+    ) -> Result<()> {
+        require!(authorized, MessagingError::NotChannelOwner);
+        require!(
+            self.status == ChannelStatus::Active,
+            MessagingError::ChannelInactive
+        );
+        require!(
+            u64::from(message_index) == self.message_count,
+            MessagingError::InvalidMessageIndex
+        );
+        self.check_broadcast_interval(now)?;
+
+        self.message_count = self
+            .message_count
+            .checked_add(1)
+            .ok_or(MessagingError::Overflow)?;
+        self.last_broadcast_at = now;
+        Ok(())
+    }
+
+    /// Enforce the minimum spacing between broadcasts
+    pub fn check_broadcast_interval(&self, now: i64) -> Result<()> {
+        if self.min_broadcast_interval > 0 && self.message_count > 0 {
+            require!(
+                now - self.last_broadcast_at >= self.min_broadcast_interval,
+                MessagingError::RateLimited
+            );
+        }
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ChannelSubscription {
+    /// Subscriber's public key
+    pub subscriber: Pubkey,
+    
+    /// Channel being subscribed to
+    pub channel: Pubkey,
+    
+    /// When subscription was created
+    pub subscribed_at: i64,
+    
+    /// Last message index read by subscriber
+    pub last_read_index: u64,
+
+    /// Lamports this subscriber has tipped the channel owner
+    pub tipped_amount: u64,
+
+    /// Personal grouping label as zero-padded UTF-8
+    pub label: [u8; 16],
+
+    /// When the paid period ends (0 = never expires)
+    pub expires_at: i64,
+
+    /// User who paid for this subscription (default pubkey = the subscriber)
+    pub gifted_by: Pubkey,
+
+    /// PDA bump, validated on unsubscribe
+    pub bump: u8,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+impl ChannelSubscription {
+    /// Active until its paid period runs out (0 = never expires)
+    pub fn is_active(&self, now: i64) -> bool {
+        self.expires_at == 0 || now < self.expires_at
+    }
+
+    /// Whether this was taken out on `channel` as it exists now, and not on an
+    /// earlier channel closed and re-created at the same address
+    pub fn belongs_to(&self, channel: &Pubkey, channel_created_at: i64) -> bool {
+        self.channel == *channel && self.subscribed_at >= channel_created_at
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct BroadcastDelivery {
+    /// Channel the broadcast was sent on
+    pub channel: Pubkey,
+
+    /// Index of the broadcast
+    pub message_index: u32,
+
+    /// Number of subscribers that confirmed delivery
+    pub delivery_count: u32,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DeliveryReceipt {
+    /// Subscriber that confirmed delivery
+    pub subscriber: Pubkey,
+
+    /// Delivery aggregate this receipt counted towards
+    pub delivery: Pubkey,
+
+    /// When delivery was confirmed
+    pub confirmed_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct BroadcastBurn {
+    /// Channel the broadcast was sent on
+    pub channel: Pubkey,
+
+    /// Index of the burned broadcast
+    pub message_index: u32,
+
+    /// Subscriber that consumed the broadcast
+    pub subscriber: Pubkey,
+
+    /// When the subscriber marked it seen
+    pub burned_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct QuietHours {
+    /// User the schedule belongs to
+    pub user: Pubkey,
+
+    /// Start of the quiet window (minutes from midnight UTC)
+    pub start_minute: u16,
+
+    /// End of the quiet window, exclusive (minutes from midnight UTC)
+    pub end_minute: u16,
+
+    /// Timestamp of the last schedule change
+    pub updated_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+impl QuietHours {
+    /// Whether the given minute of the UTC day falls inside the window.
+    /// Clients must use the same rule so every device agrees.
+    pub fn is_quiet_at(&self, minute_of_day: u16) -> bool {
+        if self.start_minute <= self.end_minute {
+            minute_of_day >= self.start_minute && minute_of_day < self.end_minute
+        } else {
+            // Window spans midnight, e.g. 22:00 -> 07:00
+            minute_of_day >= self.start_minute || minute_of_day < self.end_minute
+        }
+    }
+
+    /// Whether the given unix timestamp falls inside the window
+    pub fn is_quiet_at_timestamp(&self, unix_timestamp: i64) -> bool {
+        let minute_of_day = unix_timestamp.rem_euclid(86_400) / 60;
+        self.is_quiet_at(minute_of_day as u16)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ArchivePreference {
+    /// User the preference belongs to
+    pub user: Pubkey,
+
+    /// Seconds of inactivity before a thread may be auto-archived (0 = never)
+    pub auto_archive_after: i64,
+
+    /// Timestamp of the last preference change
+    pub updated_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Reaction {
+    /// Participant or subscriber who reacted
+    pub reactor: Pubkey,
+
+    /// Thread containing the message
+    pub thread: Pubkey,
+
+    /// Index of the message reacted to
+    pub message_index: u32,
+
+    /// Emoji as zero-padded UTF-8 bytes
+    pub emoji: [u8; 8],
+
+    /// When the reaction was added
+    pub timestamp: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct BroadcastReaction {
+    /// Subscriber who reacted
+    pub reactor: Pubkey,
+
+    /// Channel containing the broadcast
+    pub channel: Pubkey,
+
+    /// Index of the broadcast reacted to
+    pub message_index: u32,
+
+    /// Emoji as zero-padded UTF-8 bytes
+    pub emoji: [u8; 8],
+
+    /// When the reaction was added or last changed
+    pub timestamp: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalSenderMute {
+    /// User who muted the sender
+    pub user: Pubkey,
+
+    /// Sender muted across all threads
+    pub muted_sender: Pubkey,
+
+    /// When the mute was created
+    pub muted_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Folder {
+    /// Folder owner
+    pub user: Pubkey,
+
+    /// Owner-chosen folder identifier
+    pub folder_id: u32,
+
+    /// Folder name (max 32 chars)
+    #[max_len(32)]
+    pub name: String,
+
+    /// Threads and channels in the folder
+    #[max_len(64)]
+    pub members: Vec<Pubkey>,
+
+    /// When the folder was created
+    pub created_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+impl Folder {
+    pub const MAX_MEMBERS: usize = 64;
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct VerifiedContact {
+    /// User who started the handshake
+    pub initiator: Pubkey,
+
+    /// User asked to confirm the handshake
+    pub peer: Pubkey,
+
+    /// Salted commitment to the shared code, submitted by the initiator
+    pub initiator_hash: [u8; 32],
+
+    /// Salted commitment computed independently by the peer
+    pub peer_hash: [u8; 32],
+
+    /// Whether both commitments matched
+    pub verified: bool,
+
+    /// When the handshake was started
+    pub initiated_at: i64,
+
+    /// When the peer confirmed
+    pub verified_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct AckReceipt {
+    /// Thread containing the acknowledged message
+    pub thread: Pubkey,
+
+    /// Index of the acknowledged message
+    pub message_index: u32,
+
+    /// Participant who signed the acknowledgment
+    pub recipient: Pubkey,
+
+    /// Content hash of the message at the time of signing
+    pub content_hash: [u8; 32],
+
+    /// Ed25519 signature over `ack_message(thread, index, content_hash)`
+    pub signature: [u8; 64],
+
+    /// When the acknowledgment was recorded
+    pub acknowledged_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct GroupThread {
+    /// Group creator and admin
+    pub creator: Pubkey,
+
+    /// Unique group identifier
+    pub group_id: [u8; 32],
+
+    /// Group name (max 32 chars)
+    #[max_len(32)]
+    pub name: String,
+
+    /// Current members, including the creator
+    #[max_len(32)]
+    pub members: Vec<Pubkey>,
+
+    /// Total number of messages sent
+    pub message_count: u32,
+
+    /// Timestamp of group creation
+    pub created_at: i64,
+
+    /// Timestamp of last message
+    pub last_message_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+impl GroupThread {
+    pub const MAX_MEMBERS: usize = 32;
+
+    pub fn is_member(&self, key: &Pubkey) -> bool {
+        self.members.contains(key)
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct BlockEntry {
+    /// User who created the block
+    pub blocker: Pubkey,
+
+    /// User who is blocked
+    pub blocked: Pubkey,
+
+    /// When the block was created
+    pub blocked_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Append-only history of the encryption keys used in one thread, so clients can
+/// spot an unexpected key change. Lives at `[b"key_log", thread]`.
+#[account]
+#[derive(InitSpace)]
+pub struct KeyTransparencyLog {
+    pub thread: Pubkey,
+
+    /// Oldest first
+    #[max_len(MAX_KEY_LOG_ENTRIES)]
+    pub entries: Vec<KeyLogEntry>,
+
+    /// Layout version, see `ACCOUNT_VERSION`; 0 until the first entry
+    pub version: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct KeyLogEntry {
+    pub participant: Pubkey,
+
+    /// X25519 key the participant published
+    pub encryption_key: [u8; 32],
+
+    pub timestamp: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct UserProfile {
+    /// Wallet the profile belongs to
+    pub owner: Pubkey,
+
+    /// X25519 public key peers encrypt messages to
+    pub encryption_key: [u8; 32],
+
+    /// Incremented on every key rotation (starts at 1)
+    pub key_version: u32,
+
+    /// Display name (max 32 chars)
+    #[max_len(32)]
+    pub display_name: String,
+
+    /// Avatar URI (max 200 chars)
+    #[max_len(200)]
+    pub avatar_uri: String,
+
+    /// When the profile was registered
+    pub created_at: i64,
+
+    /// When the profile was last changed
+    pub updated_at: i64,
+
+    /// Lamports a non-contact must lock to open a thread with the owner (0 = none)
+    pub outreach_deposit_lamports: u64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+
+    /// Lamports a non-contact pays the owner, via escrow, to open a thread (0 = none)
+    pub dm_fee_lamports: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct MessageRecord {
+    /// Thread the message belongs to
+    pub thread: Pubkey,
+
+    /// Index of the message in the thread
+    pub message_index: u32,
+
+    /// Message sender (receives the rent back on close)
+    pub sender: Pubkey,
+
+    /// Hash of the encrypted content
+    pub content_hash: [u8; 32],
+
+    /// Off-chain location of the encrypted content, e.g. an IPFS/Arweave CID (max 200 chars)
+    #[max_len(200)]
+    pub content_uri: String,
+
+    /// When the message was sent
+    pub timestamp: i64,
+
+    /// When the record may be pruned (0 = never)
+    pub expires_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+
+    /// Earlier message this one replies to
+    pub reply_to_index: Option<u32>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ChannelVault {
+    /// Channel whose subscription fees are held here
+    pub channel: Pubkey,
+
+    /// Lamports paid in by subscribers
+    pub total_collected: u64,
+
+    /// Lamports withdrawn by the owner
+    pub total_withdrawn: u64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Lamports a subscriber set aside for `process_renewal`
+#[account]
+#[derive(InitSpace)]
+pub struct SubscriptionWallet {
+    /// Subscription the balance renews
+    pub subscription: Pubkey,
+
+    /// Subscriber who funded it and gets the balance back on close
+    pub subscriber: Pubkey,
+
+    /// Lamports deposited by the subscriber
+    pub total_deposited: u64,
+
+    /// Lamports paid out as renewal fees
+    pub total_spent: u64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ChannelAdmin {
+    /// Channel the role applies to
+    pub channel: Pubkey,
+
+    /// Account holding the role
+    pub admin: Pubkey,
+
+    /// Permission bits (see the associated constants)
+    pub permissions: u8,
+
+    /// When the role was granted
+    pub added_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+impl ChannelAdmin {
+    /// May send broadcasts on the channel
+    pub const BROADCAST: u8 = 1 << 0;
+    /// May create invites to a private channel
+    pub const INVITE: u8 = 1 << 1;
+    /// May ban and unban subscribers
+    pub const MODERATE: u8 = 1 << 2;
+
+    pub fn has_permission(&self, permission: u8) -> bool {
+        self.permissions & permission == permission
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct MessageTombstone {
+    /// Thread or channel containing the revoked message
+    pub target: Pubkey,
+
+    /// Index of the revoked message
+    pub message_index: u32,
+
+    /// Original sender who revoked it; thread tombstones also carry it in their
+    /// seeds, and clients ignore one whose revoker did not send the message
+    pub revoked_by: Pubkey,
+
+    /// When the message was revoked
+    pub revoked_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ThreadVault {
+    /// Thread the deposit was locked for
+    pub thread: Pubkey,
+
+    /// Initiator who locked the deposit
+    pub depositor: Pubkey,
+
+    /// Recipient who decides whether it is refunded
+    pub recipient: Pubkey,
+
+    /// Deposit in lamports, on top of the vault's rent
+    pub amount: u64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PaymentRequest {
+    /// Thread the request was made in
+    pub thread: Pubkey,
+
+    /// Client-chosen id, unique per thread
+    pub request_id: u64,
+
+    /// Participant asking to be paid
+    pub requester: Pubkey,
+
+    /// Other participant, asked to pay
+    pub payer: Pubkey,
+
+    /// Amount in lamports or token base units
+    pub amount: u64,
+
+    /// Requested mint (default pubkey = SOL)
+    pub mint: Pubkey,
+
+    /// Hash of the off-chain memo describing the payment
+    pub memo_hash: [u8; 32],
+
+    /// Whether the payment is held until the payer releases it
+    pub escrow: bool,
+
+    pub status: PaymentStatus,
+
+    pub created_at: i64,
+
+    /// When an escrow request was funded (0 = not funded)
+    pub funded_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PaymentStatus {
+    /// Waiting for the payer
+    Open,
+    /// Escrow funded, waiting for release or refund
+    Funded,
+}
+
+/// Address-book entry; its existence is what other instructions check
+#[account]
+#[derive(InitSpace)]
+pub struct Contact {
+    /// User whose contacts list this belongs to
+    pub owner: Pubkey,
+
+    /// Saved user
+    pub contact: Pubkey,
+
+    /// Owner's nickname for the contact (may be empty)
+    #[max_len(32)]
+    pub alias: String,
+
+    pub added_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Global `@handle` → wallet mapping, seeded by the normalized handle
+#[account]
+#[derive(InitSpace)]
+pub struct Handle {
+    /// Wallet the handle resolves to
+    pub owner: Pubkey,
+
+    /// Normalized handle, without the `@`
+    #[max_len(20)]
+    pub handle: String,
+
+    pub claimed_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Program-wide settings, a singleton at `[b"config"]`
+#[account]
+#[derive(InitSpace)]
+pub struct ProgramConfig {
+    /// Admin allowed to change the config (the upgrade authority at creation)
+    pub authority: Pubkey,
+
+    /// Account layout version of the deployed program
+    pub schema_version: u8,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+
+    /// Authority that grants and revokes channel verification badges
+    pub verifier: Pubkey,
+
+    /// Destination of protocol fees withdrawn from the treasury PDA
+    pub treasury: Pubkey,
+
+    /// Protocol fee in basis points
+    pub protocol_fee_bps: u16,
+
+    /// Emergency switch; every user instruction fails while set
+    pub paused: bool,
+
+    /// `max_consecutive` given to new threads (0 = unlimited)
+    pub default_max_consecutive: u32,
+
+    /// `min_message_interval` given to new threads
+    pub default_min_message_interval: i64,
+
+    /// `min_broadcast_interval` given to new channels
+    pub default_min_broadcast_interval: i64,
+
+    /// Proposed new authority awaiting acceptance (default pubkey = none)
+    pub pending_authority: Pubkey,
+
+    /// Lamports charged per `send_message` / `send_broadcast` (0 = free)
+    pub message_fee_lamports: u64,
+}
+
+/// Binds a thread or channel to the concurrent Merkle tree holding its history
+/// This PDA is the tree's authority, so only this program can append to it
+#[account]
+#[derive(InitSpace)]
+pub struct CompressedLog {
+    /// Thread or channel whose messages are logged
+    pub target: Pubkey,
+
+    /// spl-account-compression tree
+    pub merkle_tree: Pubkey,
+
+    /// Leaves appended so far (the next leaf's index)
+    pub leaf_count: u64,
+
+    pub created_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Per-user index of threads, so wallets can list conversations without a program scan
+/// Pointers live at `[b"thread_pointer", owner, index]` for `index < next_index`;
+/// missing ones belong to closed threads
+#[account]
+#[derive(InitSpace)]
+pub struct UserInbox {
+    pub owner: Pubkey,
+
+    /// Index of the next pointer to be created
+    pub next_index: u64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// One thread listed in a user's inbox
+#[account]
+#[derive(InitSpace)]
+pub struct ThreadPointer {
+    pub owner: Pubkey,
+
+    pub thread: Pubkey,
+
+    /// Position in the owner's inbox
+    pub index: u64,
+
+    pub created_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Root of the public channel directory
+#[account]
+#[derive(InitSpace)]
+pub struct ChannelDirectory {
+    /// Pages `0..page_count` exist at `[b"directory_page", index]`
+    pub page_count: u32,
+
+    pub listed_count: u64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Fixed-capacity page of publicly listed channels
+#[account]
+#[derive(InitSpace)]
+pub struct DirectoryPage {
+    pub index: u32,
+
+    #[max_len(DIRECTORY_PAGE_CAPACITY)]
+    pub channels: Vec<Pubkey>,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Records where a channel is listed, for delisting
+#[account]
+#[derive(InitSpace)]
+pub struct DirectoryListing {
+    pub channel: Pubkey,
+
+    /// Channel owner at listing time, who may delist
+    pub owner: Pubkey,
+
+    pub page: u32,
+
+    pub listed_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Sending rights delegated by `owner` to a hot device key
+#[account]
+#[derive(InitSpace)]
+pub struct SessionKey {
+    /// Participant the device sends as
+    pub owner: Pubkey,
+
+    /// Device key allowed to sign `send_message`
+    pub device: Pubkey,
+
+    /// Only thread the key may send in; the default key allows all threads
+    pub thread: Pubkey,
+
+    /// Unix time after which the key stops working
+    pub expires_at: i64,
+
+    pub created_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+impl SessionKey {
+    /// Check the key may send in `thread` at `now` and return the owner it sends as
+    pub fn authorize(&self, thread: &Pubkey, now: i64) -> Result<Pubkey> {
+        require!(now < self.expires_at, MessagingError::SessionKeyExpired);
+        require!(
+            self.thread == Pubkey::default() || self.thread == *thread,
+            MessagingError::SessionKeyScope
+        );
+        Ok(self.owner)
+    }
+}
+
+/// Comment counter of one broadcast, created by its first comment
+#[account]
+#[derive(InitSpace)]
+pub struct BroadcastComments {
+    pub channel: Pubkey,
+
+    /// Index of the broadcast being discussed
+    pub broadcast_index: u32,
+
+    /// Comments posted so far; the next comment gets this index
+    pub comment_count: u64,
+
+    pub last_comment_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Kind of account holding a message that a poll or forward refers to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum MessageHostKind {
+    Channel,
+    Thread,
+}
+
+/// Poll attached to a broadcast or thread message, with on-chain tallies
+#[account]
+#[derive(InitSpace)]
+pub struct Poll {
+    /// Broadcast channel or message thread
+    pub target: Pubkey,
+
+    pub kind: MessageHostKind,
+
+    /// Broadcast or message the poll is attached to
+    pub message_index: u32,
+
+    pub creator: Pubkey,
+
+    #[max_len(MAX_POLL_OPTIONS, MAX_POLL_OPTION_LEN)]
+    pub options: Vec<String>,
+
+    /// Votes per option, in option order
+    #[max_len(MAX_POLL_OPTIONS)]
+    pub tallies: Vec<u64>,
+
+    /// Voting deadline (0 = until closed)
+    pub closes_at: i64,
+
+    /// Closed early by the creator
+    pub closed: bool,
+
+    pub created_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+impl Poll {
+    pub fn is_open(&self, now: i64) -> bool {
+        !self.closed && (self.closes_at == 0 || now < self.closes_at)
+    }
+}
+
+/// One voter's ballot; its existence prevents a second vote
+#[account]
+#[derive(InitSpace)]
+pub struct PollVote {
+    pub poll: Pubkey,
+
+    pub voter: Pubkey,
+
+    pub option: u8,
+
+    pub voted_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Provenance of a forwarded message, verified when it was forwarded
+#[account]
+#[derive(InitSpace)]
+pub struct ForwardRecord {
+    pub destination_thread: Pubkey,
+
+    /// Index of the forwarded copy in the destination thread
+    pub message_index: u32,
+
+    /// Thread or channel the message came from
+    pub source: Pubkey,
+
+    pub source_kind: MessageHostKind,
+
+    /// Index of the original message in the source
+    pub source_index: u32,
+
+    /// Who sent the original (the channel owner for broadcasts)
+    pub original_sender: Pubkey,
+
+    pub forwarder: Pubkey,
+
+    pub forwarded_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Envelope of an encrypted media file attached to a message or broadcast
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct Attachment {
+    /// Content id of the encrypted file on IPFS/Arweave
+    #[max_len(MAX_ATTACHMENT_CID_LEN)]
+    pub content_cid: String,
+
+    /// MIME type of the plaintext file, e.g. `image/jpeg` or `audio/ogg`
+    #[max_len(MAX_ATTACHMENT_MIME_LEN)]
+    pub mime_type: String,
+
+    /// Size of the encrypted file in bytes
+    pub byte_size: u64,
+
+    /// Nonce the file was encrypted with
+    pub nonce: [u8; 24],
+
+    /// File key wrapped for the recipients
+    #[max_len(MAX_ATTACHMENT_KEY_WRAP_LEN)]
+    pub key_wrap: Vec<u8>,
+}
+
+impl Attachment {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            !self.content_cid.is_empty() && self.content_cid.len() <= MAX_ATTACHMENT_CID_LEN,
+            MessagingError::InvalidAttachment
+        );
+        let (kind, subtype) = self
+            .mime_type
+            .split_once('/')
+            .ok_or(MessagingError::InvalidAttachment)?;
+        require!(
+            !kind.is_empty()
+                && !subtype.is_empty()
+                && self.mime_type.len() <= MAX_ATTACHMENT_MIME_LEN,
+            MessagingError::InvalidAttachment
+        );
+        require!(
+            self.byte_size > 0 && self.byte_size <= MAX_ATTACHMENT_BYTES,
+            MessagingError::InvalidAttachment
+        );
+        require!(
+            !self.key_wrap.is_empty() && self.key_wrap.len() <= MAX_ATTACHMENT_KEY_WRAP_LEN,
+            MessagingError::InvalidAttachment
+        );
+        Ok(())
+    }
+}
+
+/// Invitation to a private channel, for one wallet or for holders of a code
+#[account]
+#[derive(InitSpace)]
+pub struct ChannelInvite {
+    pub channel: Pubkey,
+
+    /// Wallet the invite is for (default for code invites)
+    pub invitee: Pubkey,
+
+    /// Hash of the invite code (zero for wallet invites)
+    pub code_hash: [u8; 32],
+
+    /// Subscribers the invite admits; 1 makes it single-use
+    pub max_uses: u32,
+
+    pub uses: u32,
+
+    /// When the invite stops working (0 = never)
+    pub expires_at: i64,
+
+    /// Owner or admin who created the invite (receives the rent back)
+    pub created_by: Pubkey,
+
+    pub created_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+impl ChannelInvite {
+    /// Check `subscriber` may use the invite at `now` and count the use
+    pub fn redeem(&mut self, subscriber: &Pubkey, code: Option<&[u8]>, now: i64) -> Result<()> {
+        require!(
+            self.expires_at == 0 || now < self.expires_at,
+            MessagingError::InviteExpired
+        );
+        require!(self.uses < self.max_uses, MessagingError::InviteExhausted);
+        let admitted = if self.invitee != Pubkey::default() {
+            self.invitee == *subscriber
+        } else {
+            code.is_some_and(|code| hash(code).to_bytes() == self.code_hash)
+        };
+        require!(admitted, MessagingError::InvalidInvite);
+
+        self.uses = self.uses.checked_add(1).ok_or(MessagingError::Overflow)?;
+        Ok(())
+    }
+}
+
+/// Wallet banned from a channel; its existence blocks resubscribing
+#[account]
+#[derive(InitSpace)]
+pub struct BanEntry {
+    pub channel: Pubkey,
+
+    pub subscriber: Pubkey,
+
+    /// Owner or admin who issued the ban
+    pub banned_by: Pubkey,
+
+    pub banned_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Abuse report against one message, awaiting a moderator
+#[account]
+#[derive(InitSpace)]
+pub struct Report {
+    pub reporter: Pubkey,
+
+    /// Broadcast channel or message thread holding the message
+    pub target: Pubkey,
+
+    pub target_kind: MessageHostKind,
+
+    pub message_index: u32,
+
+    /// One of the `Report` reason constants
+    pub reason_code: u8,
+
+    /// Lamports locked on top of the rent
+    pub deposit: u64,
+
+    pub created_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+impl Report {
+    pub const SPAM: u8 = 0;
+    pub const HARASSMENT: u8 = 1;
+    pub const ILLEGAL_CONTENT: u8 = 2;
+    pub const IMPERSONATION: u8 = 3;
+    pub const OTHER: u8 = 4;
+}
+
+/// Broadcast waiting for its release time
+#[account]
+#[derive(InitSpace)]
+pub struct ScheduledBroadcast {
+    pub channel: Pubkey,
+
+    /// Owner or admin who queued it, credited as the sender
+    pub author: Pubkey,
+
+    /// SHA-256 of the encrypted content that must be revealed at publish time
+    pub content_hash: [u8; 32],
+
+    /// Earliest publish time of the next occurrence
+    pub release_at: i64,
+
+    pub created_at: i64,
+
+    /// One of the `ScheduledBroadcast` recurrence constants
+    pub recurrence: u8,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+impl ScheduledBroadcast {
+    pub const ONCE: u8 = 0;
+    pub const DAILY: u8 = 1;
+    pub const WEEKLY: u8 = 2;
+    /// Every 30 days, not on a calendar date
+    pub const MONTHLY: u8 = 3;
+
+    /// Seconds between occurrences (0 = published once)
+    pub fn interval(&self) -> i64 {
+        match self.recurrence {
+            Self::DAILY => SECONDS_PER_DAY,
+            Self::WEEKLY => 7 * SECONDS_PER_DAY,
+            Self::MONTHLY => 30 * SECONDS_PER_DAY,
+            _ => 0,
+        }
+    }
+
+    /// First occurrence after `now`, skipping any missed while nobody cranked;
+    /// `None` for a one-off broadcast
+    pub fn next_release(&self, now: i64) -> Result<Option<i64>> {
+        let interval = self.interval();
+        if interval == 0 {
+            return Ok(None);
+        }
+
+        let elapsed = now.saturating_sub(self.release_at).max(0);
+        let next = (elapsed / interval + 1)
+            .checked_mul(interval)
+            .and_then(|offset| self.release_at.checked_add(offset))
+            .ok_or(MessagingError::Overflow)?;
+
+        Ok(Some(next))
+    }
+}
+
+/// Pending scheduled broadcasts of one channel, for a content calendar
+/// Lives at `[b"schedule_index", channel]`.
+#[account]
+#[derive(InitSpace)]
+pub struct ScheduleIndex {
+    pub channel: Pubkey,
+
+    /// Scheduled broadcasts not yet published for the last time
+    pub pending_count: u32,
+
+    /// Release time of the earliest pending one (0 = nothing pending)
+    pub next_due: i64,
+
+    #[max_len(MAX_SCHEDULED_BROADCASTS)]
+    pub entries: Vec<ScheduleEntry>,
+
+    /// Layout version, see `ACCOUNT_VERSION`; 0 until the first schedule
+    pub version: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ScheduleEntry {
+    pub scheduled_broadcast: Pubkey,
+
+    /// Release time of its next occurrence
+    pub release_at: i64,
+}
+
+impl ScheduleIndex {
+    /// Track `scheduled_broadcast` at `release_at`, adding it if it is new
+    pub fn upsert(&mut self, scheduled_broadcast: Pubkey, release_at: i64) -> Result<()> {
+        match self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.scheduled_broadcast == scheduled_broadcast)
+        {
+            Some(entry) => entry.release_at = release_at,
+            None => {
+                require!(
+                    self.entries.len() < MAX_SCHEDULED_BROADCASTS,
+                    MessagingError::ScheduleFull
+                );
+                self.entries.push(ScheduleEntry {
+                    scheduled_broadcast,
+                    release_at,
+                });
+            }
+        }
+        self.refresh();
+
+        Ok(())
+    }
+
+    /// Stop tracking `scheduled_broadcast`; unknown keys are ignored
+    pub fn remove(&mut self, scheduled_broadcast: &Pubkey) {
+        self.entries
+            .retain(|entry| entry.scheduled_broadcast != *scheduled_broadcast);
+        self.refresh();
+    }
+
+    fn refresh(&mut self) {
+        self.pending_count = self.entries.len() as u32;
+        self.next_due = self
+            .entries
+            .iter()
+            .map(|entry| entry.release_at)
+            .min()
+            .unwrap_or(0);
+    }
+}
+
+/// Pinned message indices of one thread or channel
+#[account]
+#[derive(InitSpace)]
+pub struct PinnedMessages {
+    /// Broadcast channel or message thread
+    pub target: Pubkey,
+
+    pub target_kind: MessageHostKind,
+
+    /// In pin order, oldest first
+    #[max_len(MAX_PINNED_MESSAGES)]
+    pub indices: Vec<u32>,
+
+    /// Pinner's note for each entry of `indices`, zero-padded UTF-8
+    #[max_len(MAX_PINNED_MESSAGES)]
+    pub notes: Vec<[u8; 64]>,
+
+    /// Layout version, see `ACCOUNT_VERSION`; 0 until the first pin
+    pub version: u8,
+}
+
+/// One of a user's devices, with its own encryption key
+#[account]
+#[derive(InitSpace)]
+pub struct Device {
+    pub owner: Pubkey,
+
+    /// X25519 public key of the device
+    pub device_key: [u8; 32],
+
+    /// Client-defined read-state counter, increasing with each sync
+    pub last_sync_counter: u64,
+
+    pub registered_at: i64,
+
+    pub last_synced_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Channel symmetric key wrapped to one subscriber
+#[account]
+#[derive(InitSpace)]
+pub struct ChannelKeyWrap {
+    pub channel: Pubkey,
+
+    pub subscriber: Pubkey,
+
+    /// Channel `key_epoch` the wrapped key belongs to
+    pub epoch: u32,
+
+    /// Key sealed to the subscriber's profile encryption key
+    #[max_len(MAX_CHANNEL_KEY_WRAP_LEN)]
+    pub wrapped_key: Vec<u8>,
+
+    pub updated_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DmEscrow {
+    /// Thread the fee was paid for
+    pub thread: Pubkey,
+
+    /// Initiator who paid the fee
+    pub sender: Pubkey,
+
+    /// Recipient who earns the fee by engaging
+    pub recipient: Pubkey,
+
+    /// Fee in lamports, on top of the escrow's rent
+    pub amount: u64,
+
+    /// After this the sender can reclaim the fee from a pending thread
+    pub expires_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Funds locked for a deal between the two participants of a thread
+#[account]
+#[derive(InitSpace)]
+pub struct ThreadEscrow {
+    /// Thread the deal was negotiated in
+    pub thread: Pubkey,
+
+    /// Client-chosen id, unique per thread
+    pub escrow_id: u64,
+
+    /// Participant who locked the funds
+    pub depositor: Pubkey,
+
+    /// Other participant, paid on release
+    pub beneficiary: Pubkey,
+
+    /// Third party who may release or refund (default = none)
+    pub arbiter: Pubkey,
+
+    /// Amount in lamports or token base units
+    pub amount: u64,
+
+    /// Escrowed mint (default = SOL)
+    pub mint: Pubkey,
+
+    /// Hash of the off-chain deal terms
+    pub terms_hash: [u8; 32],
+
+    pub created_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+impl ThreadEscrow {
+    pub fn is_arbiter(&self, key: &Pubkey) -> bool {
+        self.arbiter != Pubkey::default() && *key == self.arbiter
+    }
+}
+
+/// Growth counters of one channel for one UTC day
+#[account]
+#[derive(InitSpace)]
+pub struct ChannelStats {
+    pub channel: Pubkey,
+
+    /// Days since the Unix epoch, see `stats_day`
+    pub day: u32,
+
+    /// Broadcasts sent that day
+    pub broadcast_count: u32,
+
+    /// Subscribes minus unsubscribes that day
+    pub subscriber_delta: i32,
+
+    /// Paid the rent, refunded by `close_stale_stats`
+    pub payer: Pubkey,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+impl ChannelStats {
+    /// Count activity on `channel` at `now`; the stats must be for that day
+    pub fn record(
+        &mut self,
+        channel: &Pubkey,
+        now: i64,
+        broadcasts: u32,
+        subscriber_delta: i32,
+    ) -> Result<()> {
+        require_keys_eq!(self.channel, *channel, MessagingError::InvalidStatsAccount);
+        require!(
+            self.day == stats_day(now)?,
+            MessagingError::InvalidStatsAccount
+        );
+
+        self.broadcast_count = self
+            .broadcast_count
+            .checked_add(broadcasts)
+            .ok_or(MessagingError::Overflow)?;
+        self.subscriber_delta = self
+            .subscriber_delta
+            .checked_add(subscriber_delta)
+            .ok_or(MessagingError::Overflow)?;
+        Ok(())
+    }
+}
+
+/// Latest notifications pushed to a user by other programs
+#[account]
+#[derive(InitSpace)]
+pub struct NotificationInbox {
+    pub owner: Pubkey,
+
+    /// Notifications ever delivered; record `i` sits at `i % MAX_NOTIFICATIONS`
+    pub delivered_count: u64,
+
+    #[max_len(MAX_NOTIFICATIONS)]
+    pub records: Vec<NotificationRecord>,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct NotificationRecord {
+    /// Whitelisted program, or the wallet that paid for an unverified notification
+    pub source: Pubkey,
+
+    /// Whether `source` is a whitelisted program
+    pub verified: bool,
+
+    /// Source-defined notification type
+    pub kind: u16,
+
+    /// Hash of the off-chain payload
+    pub payload_hash: [u8; 32],
+
+    pub delivered_at: i64,
+}
+
+/// Program allowed to deliver notifications without paying the fee
+#[account]
+#[derive(InitSpace)]
+pub struct Notifier {
+    pub program: Pubkey,
+
+    pub approved_by: Pubkey,
+
+    pub approved_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Merkle root of a channel's subscriber set at one point in time
+#[account]
+#[derive(InitSpace)]
+pub struct SubscriberSnapshot {
+    pub channel: Pubkey,
+
+    /// Owner-chosen id, unique per channel
+    pub snapshot_id: u64,
+
+    pub merkle_root: [u8; 32],
+
+    /// Channel subscriber count when the root was committed
+    pub subscriber_count: u64,
+
+    pub created_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Whether a channel takes subscribers and broadcasts, or is winding down
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ChannelStatus {
+    Active,
+    /// `close_channel` was called once; subscribers may leave and reclaim rent
+    Closing,
+}
+
+/// How far one participant has seen a thread, ahead of or level with their read receipt
+/// Lives at `[b"seen_pointer", thread, participant]`.
+#[account]
+#[derive(InitSpace)]
+pub struct SeenPointer {
+    pub thread: Pubkey,
+
+    pub participant: Pubkey,
+
+    /// Messages the participant has seen; only ever increases
+    pub seen_index: u64,
+
+    pub seen_at: i64,
+
+    pub bump: u8,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// A one-sided request to close a thread, executable after a timelock
+/// Lives at `[b"thread_close_request", thread]`.
+#[account]
+#[derive(InitSpace)]
+pub struct ThreadCloseRequest {
+    /// Thread to close
+    pub thread: Pubkey,
+
+    /// Participant who asked to close it and paid this account's rent
+    pub proposer: Pubkey,
+
+    pub requested_at: i64,
+
+    /// The proposer may close the thread alone from this time on
+    pub executable_after: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// One message of a `send_message_batch`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MessageDescriptor {
+    pub encrypted_content: Vec<u8>,
+
+    /// Earlier message of the thread being quoted
+    pub reply_to_index: Option<u32>,
+
+    pub attachment: Option<Attachment>,
+}
+
+/// One broadcast of a `send_broadcast_batch`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BroadcastDescriptor {
+    pub encrypted_content: Vec<u8>,
+
+    /// Ask subscriber clients to delete it once seen
+    pub burn_after_seen: bool,
+
+    /// Earlier broadcast of the channel being quoted
+    pub reply_to_index: Option<u32>,
+
+    pub attachment: Option<Attachment>,
+}
+
+/// Ephemeral status update, like a story
+/// Lives at `[b"status", user_profile, content_hash]`.
+#[account]
+#[derive(InitSpace)]
+pub struct Status {
+    /// Poster, who paid the rent
+    pub owner: Pubkey,
+
+    /// SHA-256 of the encrypted content at `uri`
+    pub content_hash: [u8; 32],
+
+    /// Where clients fetch the content (IPFS/Arweave/HTTPS)
+    #[max_len(MAX_STATUS_URI_LEN)]
+    pub uri: String,
+
+    pub posted_at: i64,
+
+    /// Viewable until this time; `expire_status` closes it afterwards
+    pub expires_at: i64,
+
+    /// View receipts left by the poster's contacts
+    pub view_count: u32,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// A contact's receipt for viewing a status, at `[b"status_view", status, viewer]`
+#[account]
+#[derive(InitSpace)]
+pub struct StatusView {
+    pub status: Pubkey,
+
+    pub viewer: Pubkey,
+
+    pub viewed_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Where a call is in its signaling handshake
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum CallState {
+    /// Offer posted, waiting for the callee
+    Ringing,
+    /// Answer posted; media flows peer to peer
+    Active,
+}
+
+/// Signaling record of a call between the two participants of a thread
+/// Lives at `[b"call_session", thread]` and is closed by `end_call`.
+#[account]
+#[derive(InitSpace)]
+pub struct CallSession {
+    pub thread: Pubkey,
+
+    /// Participant who placed the call and paid the rent
+    pub caller: Pubkey,
+
+    pub callee: Pubkey,
+
+    /// SHA-256 of the caller's WebRTC SDP offer
+    pub sdp_offer_hash: [u8; 32],
+
+    /// SHA-256 of the callee's SDP answer (zero while ringing)
+    pub sdp_answer_hash: [u8; 32],
+
+    pub state: CallState,
+
+    pub started_at: i64,
+
+    /// When the callee answered (0 while ringing)
+    pub answered_at: i64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+/// Token rewards a channel pays its engaged subscribers
+/// Lives at `[b"reward_pool", channel]`; its ATA for `mint` holds the tokens.
+#[account]
+#[derive(InitSpace)]
+pub struct RewardPool {
+    pub channel: Pubkey,
+
+    /// Reward token
+    pub mint: Pubkey,
+
+    /// Tokens (base units) earned per full epoch
+    pub reward_per_epoch: u64,
+
+    pub epoch_seconds: i64,
+
+    /// Seconds a subscription must exist before it starts earning
+    pub min_subscription_age: i64,
+
+    /// Most unread broadcasts a subscriber may have when claiming
+    pub max_unread: u64,
+
+    /// Start of epoch 0
+    pub start_at: i64,
+
+    pub total_claimed: u64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+impl RewardPool {
+    /// Index of the epoch containing `time` (0 before the pool opened)
+    pub fn epoch_at(&self, time: i64) -> u64 {
+        (time.saturating_sub(self.start_at).max(0) / self.epoch_seconds) as u64
+    }
+
+    /// Index of the first epoch that starts at or after `time`
+    pub fn first_full_epoch_from(&self, time: i64) -> u64 {
+        let elapsed = time.saturating_sub(self.start_at).max(0);
+        let epoch = (elapsed / self.epoch_seconds) as u64;
+        if elapsed % self.epoch_seconds == 0 {
+            epoch
+        } else {
+            epoch + 1
+        }
+    }
+}
+
+/// A subscriber's claim progress in one reward pool,
+/// at `[b"reward_claim", reward_pool, subscriber]`
+#[account]
+#[derive(InitSpace)]
+pub struct RewardClaim {
+    pub reward_pool: Pubkey,
+
+    pub subscriber: Pubkey,
+
+    /// Epochs before this one have been paid out
+    pub last_claimed_epoch: u64,
+
+    pub total_claimed: u64,
+
+    /// Layout version, see `ACCOUNT_VERSION`
+    pub version: u8,
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct SubscriptionDataExported {
+    pub subscription: Pubkey,
+    pub subscriber: Pubkey,
+    pub channel: Pubkey,
+    pub subscribed_at: i64,
+    pub last_read_index: u64,
+    pub tipped_amount: u64,
+    pub label: [u8; 16],
+    pub exported_at: i64,
+}
+
+#[event]
+pub struct ChannelStatsExported {
+    pub channel: Pubkey,
+    pub owner: Pubkey,
+    pub channel_name: String,
+    pub message_count: u64,
+    pub subscriber_count: u64,
+    pub created_at: i64,
+    pub last_broadcast_at: i64,
+    pub total_tips: u64,
+    pub exported_at: i64,
+}
+
+#[event]
+pub struct BroadcastSent {
+    pub channel: Pubkey,
+    pub sender: Pubkey,
+    pub message_index: u32,
+    pub content_hash: [u8; 32],
+    pub burn_after_seen: bool,
+    pub timestamp: i64,
+    pub reply_to_index: Option<u32>,
+    pub attachment: Option<Attachment>,
+    /// Sender device used, when one was passed
+    pub device: Option<Pubkey>,
+}
+
+#[event]
+pub struct BroadcastBurned {
+    pub channel: Pubkey,
+    pub subscriber: Pubkey,
+    pub message_index: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageForwarded {
+    pub source: Pubkey,
+    pub source_index: u32,
+    pub destination_thread: Pubkey,
+    pub message_index: u32,
+    pub forwarder: Pubkey,
+    pub content_hash: [u8; 32],
+    pub timestamp: i64,
+    pub original_sender: Pubkey,
+}
+
+#[event]
+pub struct MessageEdited {
+    pub thread: Pubkey,
+    pub sender: Pubkey,
+    pub message_index: u32,
+    pub content_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CountReconciled {
+    pub thread: Pubkey,
+    pub participant: Pubkey,
+    pub message_count: u64,
+    pub a_observed_count: u64,
+    pub b_observed_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReactionAdded {
+    pub thread: Pubkey,
+    pub reactor: Pubkey,
+    pub message_index: u32,
+    pub emoji: [u8; 8],
+    pub notify_sender: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReactionRemoved {
+    pub thread: Pubkey,
+    pub reactor: Pubkey,
+    pub message_index: u32,
+    pub emoji: [u8; 8],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BroadcastReactionAdded {
+    pub channel: Pubkey,
+    pub reactor: Pubkey,
+    pub message_index: u32,
+    pub emoji: [u8; 8],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BroadcastReactionRemoved {
+    pub channel: Pubkey,
+    pub reactor: Pubkey,
+    pub message_index: u32,
+    pub emoji: [u8; 8],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelTipped {
+    pub channel: Pubkey,
+    pub subscriber: Pubkey,
+    pub amount: u64,
+    pub total_tips: u64,
+    pub tipped_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WelcomeThreadOpened {
+    pub channel: Pubkey,
+    pub thread: Pubkey,
+    pub owner: Pubkey,
+    pub subscriber: Pubkey,
+    pub content_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SenderMutedGlobally {
+    pub user: Pubkey,
+    pub muted_sender: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SenderUnmutedGlobally {
+    pub user: Pubkey,
+    pub muted_sender: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ContactVerified {
+    pub verified_contact: Pubkey,
+    pub initiator: Pubkey,
+    pub peer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageAcknowledged {
+    pub thread: Pubkey,
+    pub recipient: Pubkey,
+    pub message_index: u32,
+    pub content_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ThreadInitialized {
+    pub thread: Pubkey,
+    pub participant_a: Pubkey,
+    pub participant_b: Pubkey,
+    pub thread_id: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageSent {
+    pub thread: Pubkey,
+    pub sender: Pubkey,
+    pub message_index: u32,
+    pub content_hash: [u8; 32],
+    pub timestamp: i64,
+    pub reply_to_index: Option<u32>,
+    pub attachment: Option<Attachment>,
+    /// Sender device used, when one was passed
+    pub device: Option<Pubkey>,
+}
+
+#[event]
+pub struct ThreadAccepted {
+    pub thread: Pubkey,
+    pub participant_b: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ThreadRejected {
+    pub thread: Pubkey,
+    pub participant_b: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MaxConsecutiveUpdated {
+    pub thread: Pubkey,
+    pub max_consecutive: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ThreadClosed {
+    pub thread: Pubkey,
+    pub closed_by: Pubkey,
+    pub recipient: Pubkey,
+    pub message_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelCreated {
+    pub channel: Pubkey,
+    pub owner: Pubkey,
+    pub channel_name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelMetadataUpdated {
+    pub channel: Pubkey,
+    pub description: String,
+    pub metadata_uri: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct WelcomeThreadUpdated {
+    pub channel: Pubkey,
+    pub enabled: bool,
+    pub welcome_content_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Subscribed {
+    pub channel: Pubkey,
+    pub subscription: Pubkey,
+    pub subscriber: Pubkey,
+    pub subscriber_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscribersRecounted {
+    pub channel: Pubkey,
+    pub previous_count: u64,
+    pub subscriber_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BroadcastDelivered {
+    pub channel: Pubkey,
+    pub subscriber: Pubkey,
+    pub message_index: u32,
+    pub delivery_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelClosed {
+    pub channel: Pubkey,
+    pub owner: Pubkey,
+    pub recipient: Pubkey,
+    pub message_count: u64,
+    pub subscriber_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionLabelSet {
+    pub subscription: Pubkey,
+    pub subscriber: Pubkey,
+    pub label: [u8; 16],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct QuietHoursSet {
+    pub user: Pubkey,
+    pub start_minute: u16,
+    pub end_minute: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FolderCreated {
+    pub folder: Pubkey,
+    pub user: Pubkey,
+    pub folder_id: u32,
+    pub name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FolderMemberAdded {
+    pub folder: Pubkey,
+    pub user: Pubkey,
+    pub member: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FolderMemberRemoved {
+    pub folder: Pubkey,
+    pub user: Pubkey,
+    pub member: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ContactVerificationStarted {
+    pub verified_contact: Pubkey,
+    pub initiator: Pubkey,
+    pub peer: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GroupCreated {
+    pub group: Pubkey,
+    pub creator: Pubkey,
+    pub group_id: [u8; 32],
+    pub name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GroupMemberAdded {
+    pub group: Pubkey,
+    pub member: Pubkey,
+    pub added_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GroupMemberRemoved {
+    pub group: Pubkey,
+    pub member: Pubkey,
+    pub removed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GroupMessageSent {
+    pub group: Pubkey,
+    pub sender: Pubkey,
+    pub message_index: u32,
+    pub content_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Unsubscribed {
+    pub channel: Pubkey,
+    pub subscription: Pubkey,
+    pub subscriber: Pubkey,
+    pub subscriber_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelRead {
+    pub channel: Pubkey,
+    pub subscription: Pubkey,
+    pub subscriber: Pubkey,
+    pub last_read_index: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReadReceipt {
+    pub thread: Pubkey,
+    pub reader: Pubkey,
+    pub read_index: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessagesSeen {
+    pub thread: Pubkey,
+    pub participant: Pubkey,
+    pub seen_index: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UserBlocked {
+    pub blocker: Pubkey,
+    pub blocked: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UserUnblocked {
+    pub blocker: Pubkey,
+    pub blocked: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProfileRegistered {
+    pub profile: Pubkey,
+    pub owner: Pubkey,
+    pub encryption_key: [u8; 32],
+    pub display_name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProfileUpdated {
+    pub profile: Pubkey,
+    pub owner: Pubkey,
+    pub display_name: String,
+    pub avatar_uri: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EncryptionKeyRotated {
+    pub profile: Pubkey,
+    pub owner: Pubkey,
+    pub encryption_key: [u8; 32],
+    pub key_version: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct KeyLogAppended {
+    pub key_log: Pubkey,
+    pub thread: Pubkey,
+    pub participant: Pubkey,
+    pub encryption_key: [u8; 32],
+    pub entries: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageStored {
+    pub thread: Pubkey,
+    pub record: Pubkey,
+    pub sender: Pubkey,
+    pub message_index: u32,
+    pub content_hash: [u8; 32],
+    pub content_uri: String,
+    pub timestamp: i64,
+    pub reply_to_index: Option<u32>,
+}
+
+#[event]
+pub struct MessageRecordClosed {
+    pub thread: Pubkey,
+    pub record: Pubkey,
+    pub message_index: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionFeeSet {
+    pub channel: Pubkey,
+    pub fee_lamports: u64,
+    pub period_seconds: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionRenewed {
+    pub channel: Pubkey,
+    pub subscription: Pubkey,
+    pub subscriber: Pubkey,
+    pub fee_lamports: u64,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionGifted {
+    pub channel: Pubkey,
+    pub subscription: Pubkey,
+    pub gifter: Pubkey,
+    pub recipient: Pubkey,
+    pub fee_lamports: u64,
+    pub expires_at: i64,
+    pub subscriber_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionWalletFunded {
+    pub wallet: Pubkey,
+    pub subscription: Pubkey,
+    pub subscriber: Pubkey,
+    pub amount: u64,
+    pub total_deposited: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionWalletClosed {
+    pub wallet: Pubkey,
+    pub subscription: Pubkey,
+    pub subscriber: Pubkey,
+    pub refunded: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelFeesWithdrawn {
+    pub channel: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub total_withdrawn: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenGateSet {
+    pub channel: Pubkey,
+    pub mint: Pubkey,
+    pub min_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionPurged {
+    pub channel: Pubkey,
+    pub subscription: Pubkey,
+    pub subscriber: Pubkey,
+    pub balance: u64,
+    pub subscriber_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminAdded {
+    pub channel: Pubkey,
+    pub admin: Pubkey,
+    pub permissions: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminRemoved {
+    pub channel: Pubkey,
+    pub admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminPermissionsUpdated {
+    pub channel: Pubkey,
+    pub admin: Pubkey,
+    pub permissions: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelTransferProposed {
+    pub channel: Pubkey,
+    pub owner: Pubkey,
+    pub pending_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelTransferred {
+    pub channel: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelMigrated {
+    pub channel: Pubkey,
+    pub creator: Pubkey,
+    pub old_len: u32,
+    pub new_len: u32,
+    pub version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelProfileUpdated {
+    pub channel: Pubkey,
+    pub avatar_uri: String,
+    pub banner_uri: String,
+    pub category: String,
+    pub external_link: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ThreadLifecycleChanged {
+    pub thread: Pubkey,
+    pub participant: Pubkey,
+    pub lifecycle: ThreadLifecycle,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ThreadRestored {
+    pub thread: Pubkey,
+    pub participant: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AutoArchived {
+    pub thread: Pubkey,
+    pub participant: Pubkey,
+    pub last_activity: i64,
+    pub auto_archive_after: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ThreadMuted {
+    pub thread: Pubkey,
+    pub participant: Pubkey,
+    pub muted: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageRevoked {
+    pub target: Pubkey,
+    pub message_index: u32,
+    pub revoked_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageTtlUpdated {
+    pub thread: Pubkey,
+    pub participant: Pubkey,
+    pub ttl_seconds: i64,
+    pub applied: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RateLimitUpdated {
+    pub target: Pubkey,
+    pub min_interval: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OutreachDepositSet {
+    pub profile: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OutreachDepositLocked {
+    pub thread: Pubkey,
+    pub vault: Pubkey,
+    pub depositor: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OutreachDepositSettled {
+    pub thread: Pubkey,
+    pub depositor: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub refunded: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageTipped {
+    pub thread: Pubkey,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub message_index: u32,
+    pub amount: u64,
+    /// Default pubkey for SOL tips
+    pub mint: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PaymentRequested {
+    pub request: Pubkey,
+    pub thread: Pubkey,
+    pub requester: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub mint: Pubkey,
+    pub memo_hash: [u8; 32],
+    pub escrow: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PaymentFulfilled {
+    pub request: Pubkey,
+    pub thread: Pubkey,
+    pub requester: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub mint: Pubkey,
+    pub escrowed: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PaymentReleased {
+    pub request: Pubkey,
+    pub thread: Pubkey,
+    pub requester: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub mint: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PaymentRequestCancelled {
+    pub request: Pubkey,
+    pub thread: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub refunded: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ContactAdded {
+    pub owner: Pubkey,
+    pub contact: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ContactRemoved {
+    pub owner: Pubkey,
+    pub contact: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct HandleClaimed {
+    pub handle: String,
+    pub owner: Pubkey,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct HandleTransferred {
+    pub handle: String,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct HandleReleased {
+    pub handle: String,
+    pub owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ThreadMigrated {
+    pub thread: Pubkey,
+    pub old_len: u32,
+    pub new_len: u32,
+    pub version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriptionMigrated {
+    pub subscription: Pubkey,
+    pub channel: Pubkey,
+    pub old_len: u32,
+    pub new_len: u32,
+    pub version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SchemaVersionUpdated {
+    pub authority: Pubkey,
+    pub schema_version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CompressedLogEnabled {
+    pub target: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CompressedLeafAppended {
+    pub target: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub leaf_index: u64,
+    pub leaf: [u8; 32],
+    pub sender: Pubkey,
+    pub message_index: u32,
+    pub content_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ThreadPointerAdded {
+    pub owner: Pubkey,
+    pub thread: Pubkey,
+    pub pointer: Pubkey,
+    pub index: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ThreadPointerRemoved {
+    pub owner: Pubkey,
+    pub thread: Pubkey,
+    pub pointer: Pubkey,
+    pub index: u64,
+}
+
+#[event]
+pub struct ChannelListed {
+    pub channel: Pubkey,
+    pub owner: Pubkey,
+    pub page: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelDelisted {
+    pub channel: Pubkey,
+    pub owner: Pubkey,
+    pub page: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VerifierSet {
+    pub authority: Pubkey,
+    pub verifier: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelVerificationChanged {
+    pub channel: Pubkey,
+    pub verifier: Pubkey,
+    pub verified: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelFlagged {
+    pub channel: Pubkey,
+    pub moderator: Pubkey,
+    pub suspicious: bool,
+    pub reason_code: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub paused: bool,
+    pub default_max_consecutive: u32,
+    pub default_min_message_interval: i64,
+    pub default_min_broadcast_interval: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigAuthorityProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConfigAuthorityTransferred {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageFeeSet {
+    pub authority: Pubkey,
+    pub message_fee_lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolFeePaid {
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolFeesWithdrawn {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageRelayed {
+    pub thread: Pubkey,
+    pub sender: Pubkey,
+    pub relayer: Pubkey,
+    pub message_index: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SessionKeyCreated {
+    pub owner: Pubkey,
+    pub device: Pubkey,
+    pub thread: Pubkey,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SessionKeyRevoked {
+    pub owner: Pubkey,
+    pub device: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CommentsToggled {
+    pub channel: Pubkey,
+    pub enabled: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BroadcastCommentPosted {
+    pub channel: Pubkey,
+    pub broadcast_index: u32,
+    pub commenter: Pubkey,
+    pub comment_index: u64,
+    pub content_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PollCreated {
+    pub poll: Pubkey,
+    pub target: Pubkey,
+    pub message_index: u32,
+    pub creator: Pubkey,
+    pub option_count: u8,
+    pub closes_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoteCast {
+    pub poll: Pubkey,
+    pub voter: Pubkey,
+    pub option: u8,
+    pub tally: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PollClosed {
+    pub poll: Pubkey,
+    pub tallies: Vec<u64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageRecordMigrated {
+    pub record: Pubkey,
+    pub thread: Pubkey,
+    pub old_len: u32,
+    pub new_len: u32,
+    pub version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelPrivacySet {
+    pub channel: Pubkey,
+    pub private: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelInviteCreated {
+    pub channel: Pubkey,
+    pub invite: Pubkey,
+    pub invitee: Pubkey,
+    pub max_uses: u32,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelInviteUsed {
+    pub channel: Pubkey,
+    pub invite: Pubkey,
+    pub subscriber: Pubkey,
+    pub uses: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelInviteRevoked {
+    pub channel: Pubkey,
+    pub invite: Pubkey,
+    pub uses: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriberBanned {
+    pub channel: Pubkey,
+    pub subscriber: Pubkey,
+    pub moderator: Pubkey,
+    pub subscriber_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscriberUnbanned {
+    pub channel: Pubkey,
+    pub subscriber: Pubkey,
+    pub moderator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ContentReported {
+    pub report: Pubkey,
+    pub target: Pubkey,
+    pub message_index: u32,
+    pub reporter: Pubkey,
+    pub reason_code: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReportResolved {
+    pub report: Pubkey,
+    pub target: Pubkey,
+    pub message_index: u32,
+    pub upheld: bool,
+    pub moderator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BroadcastScheduled {
+    pub channel: Pubkey,
+    pub scheduled_broadcast: Pubkey,
+    pub author: Pubkey,
+    pub content_hash: [u8; 32],
+    pub release_at: i64,
+    pub recurrence: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ScheduledBroadcastCancelled {
+    pub channel: Pubkey,
+    pub scheduled_broadcast: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessagePinned {
+    pub target: Pubkey,
+    pub message_index: u32,
+    pub note: [u8; 64],
+    pub pinned_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MessageUnpinned {
+    pub target: Pubkey,
+    pub message_index: u32,
+    pub unpinned_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DeviceRegistered {
+    pub owner: Pubkey,
+    pub device: Pubkey,
+    pub device_key: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DeviceSynced {
+    pub owner: Pubkey,
+    pub device: Pubkey,
+    pub sync_counter: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DeviceRevoked {
+    pub owner: Pubkey,
+    pub device: Pubkey,
+    pub device_key: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelKeyWrapPosted {
+    pub channel: Pubkey,
+    pub subscriber: Pubkey,
+    pub epoch: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelRekeyed {
+    pub channel: Pubkey,
+    pub epoch: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DmFeeSet {
+    pub profile: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DmFeeEscrowed {
+    pub thread: Pubkey,
+    pub escrow: Pubkey,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct DmFeeSettled {
+    pub thread: Pubkey,
+    pub sender: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub released: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProfileMigrated {
+    pub profile: Pubkey,
+    pub owner: Pubkey,
+    pub old_len: u32,
+    pub new_len: u32,
+    pub version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowOpened {
+    pub escrow: Pubkey,
+    pub thread: Pubkey,
+    pub depositor: Pubkey,
+    pub beneficiary: Pubkey,
+    pub arbiter: Pubkey,
+    pub amount: u64,
+    pub mint: Pubkey,
+    pub terms_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EscrowSettled {
+    pub escrow: Pubkey,
+    pub thread: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub mint: Pubkey,
+    pub released: bool,
+    pub settled_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelStatsClosed {
+    pub channel: Pubkey,
+    pub day: u32,
+    pub broadcast_count: u32,
+    pub subscriber_delta: i32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NotifierApproved {
+    pub program: Pubkey,
+    pub approved_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NotifierRevoked {
+    pub program: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NotificationDelivered {
+    pub owner: Pubkey,
+    pub index: u64,
+    pub source: Pubkey,
+    pub verified: bool,
+    pub kind: u16,
+    pub payload_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SubscribersSnapshotted {
+    pub channel: Pubkey,
+    pub snapshot: Pubkey,
+    pub snapshot_id: u64,
+    pub merkle_root: [u8; 32],
+    pub subscriber_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChannelClosing {
+    pub channel: Pubkey,
+    pub owner: Pubkey,
+    pub subscriber_count: u64,
+    pub closes_after: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ThreadCloseRequested {
+    pub thread: Pubkey,
+    pub proposer: Pubkey,
+    pub executable_after: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ThreadCloseVetoed {
+    pub thread: Pubkey,
+    pub proposer: Pubkey,
+    pub vetoed_by: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StatusPosted {
+    pub status: Pubkey,
+    pub owner: Pubkey,
+    pub content_hash: [u8; 32],
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StatusViewed {
+    pub status: Pubkey,
+    pub owner: Pubkey,
+    pub viewer: Pubkey,
+    pub view_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StatusExpired {
+    pub status: Pubkey,
+    pub owner: Pubkey,
+    pub view_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CallInitiated {
+    pub thread: Pubkey,
+    pub call_session: Pubkey,
+    pub caller: Pubkey,
+    pub callee: Pubkey,
+    pub sdp_offer_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CallAnswered {
+    pub thread: Pubkey,
+    pub call_session: Pubkey,
+    pub callee: Pubkey,
+    pub sdp_answer_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CallEnded {
+    pub thread: Pubkey,
+    pub call_session: Pubkey,
+    pub ended_by: Pubkey,
+    pub answered: bool,
+    /// Seconds since the call was answered (0 if it never was)
+    pub duration: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardPoolUpdated {
+    pub reward_pool: Pubkey,
+    pub channel: Pubkey,
+    pub mint: Pubkey,
+    pub reward_per_epoch: u64,
+    pub epoch_seconds: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsClaimed {
+    pub reward_pool: Pubkey,
+    pub channel: Pubkey,
+    pub subscriber: Pubkey,
+    pub epochs: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsWithdrawn {
+    pub reward_pool: Pubkey,
+    pub channel: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Bytes a recipient signs to acknowledge a thread message:
+/// thread key || message index (LE) || content hash
+pub fn ack_message(thread: &Pubkey, message_index: u32, content_hash: &[u8; 32]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(32 + 4 + 32);
+    message.extend_from_slice(thread.as_ref());
+    message.extend_from_slice(&message_index.to_le_bytes());
+    message.extend_from_slice(content_hash);
+    message
+}
+
+/// Bytes a sender signs to have a relayer submit a thread message:
+/// "relay_message" || thread key || message index (LE) || content hash
+pub fn relay_message(thread: &Pubkey, message_index: u32, content_hash: &[u8; 32]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(RELAY_DOMAIN.len() + 32 + 4 + 32);
+    message.extend_from_slice(RELAY_DOMAIN);
+    message.extend_from_slice(thread.as_ref());
+    message.extend_from_slice(&message_index.to_le_bytes());
+    message.extend_from_slice(content_hash);
+    message
+}
+
+/// Commitment both sides of a contact verification submit:
+/// sha256("contact_verification" || initiator || peer || salt || code)
+/// The salt is random and travels with the code out of band, so a short code
+/// can't be brute-forced from the public commitment
+pub fn verification_commitment(
+    initiator: &Pubkey,
+    peer: &Pubkey,
+    salt: &[u8; 32],
+    code: &[u8],
+) -> [u8; 32] {
+    hashv(&[
+        VERIFICATION_DOMAIN,
+        initiator.as_ref(),
+        peer.as_ref(),
+        salt,
+        code,
+    ])
+    .to_bytes()
+}
+
+/// Check that `ix` is an Ed25519 program instruction verifying exactly one
+/// signature by `signer` over `message`, and return that signature.
+/// The Ed25519 program itself rejects the transaction if the signature is invalid.
+fn verify_ed25519_instruction(
+    ix: &Instruction,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<[u8; 64]> {
+    // Layout: num_signatures (u8), padding (u8), then one offsets struct of
+    // seven u16s: signature offset/ix, pubkey offset/ix, message offset/size/ix
+    const OFFSETS_START: usize = 2;
+    const OFFSETS_LEN: usize = 14;
+    const CURRENT_IX: u16 = u16::MAX;
+
+    let data = &ix.data;
+
+    require!(
+        ix.program_id == ed25519_program::ID && ix.accounts.is_empty(),
+        MessagingError::InvalidSignature
+    );
+    require!(
+        data.len() >= OFFSETS_START + OFFSETS_LEN && data[0] == 1,
+        MessagingError::InvalidSignature
+    );
+
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let signature_offset = read_u16(OFFSETS_START) as usize;
+    let signature_ix = read_u16(OFFSETS_START + 2);
+    let pubkey_offset = read_u16(OFFSETS_START + 4) as usize;
+    let pubkey_ix = read_u16(OFFSETS_START + 6);
+    let message_offset = read_u16(OFFSETS_START + 8) as usize;
+    let message_size = read_u16(OFFSETS_START + 10) as usize;
+    let message_ix = read_u16(OFFSETS_START + 12);
+
+    // All referenced data must live inside the Ed25519 instruction itself
+    require!(
+        signature_ix == CURRENT_IX && pubkey_ix == CURRENT_IX && message_ix == CURRENT_IX,
+        MessagingError::InvalidSignature
+    );
+
+    let signed_pubkey = data
+        .get(pubkey_offset..pubkey_offset + 32)
+        .ok_or(MessagingError::InvalidSignature)?;
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(MessagingError::InvalidSignature)?;
+    let signature = data
+        .get(signature_offset..signature_offset + 64)
+        .ok_or(MessagingError::InvalidSignature)?;
+
+    require!(
+        signed_pubkey == signer.as_ref() && signed_message == message,
+        MessagingError::InvalidSignature
+    );
+
+    let mut out = [0u8; 64];
+    out.copy_from_slice(signature);
+    Ok(out)
+}
+
+/// Fail if a `BlockEntry` exists at this (seed-checked) address
+pub fn ensure_not_blocked(block_entry: &UncheckedAccount) -> Result<()> {
+    require!(block_entry.data_is_empty(), MessagingError::UserBlocked);
+    Ok(())
+}
+
+/// Move the channel's subscription fee from the payer into its vault
+/// Free channels need no vault
+pub fn pay_subscription_fee<'info>(
+    channel: &BroadcastChannel,
+    vault: Option<&mut Account<'info, ChannelVault>>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let fee = channel.subscription_fee_lamports;
+    if fee == 0 {
+        return Ok(());
+    }
+
+    let vault = vault.ok_or(MessagingError::MissingChannelVault)?;
+    transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            Transfer {
+                from: payer.to_account_info(),
+                to: vault.to_account_info(),
+            },
+        ),
+        fee,
+    )?;
+
+    vault.total_collected = vault
+        .total_collected
+        .checked_add(fee)
+        .ok_or(MessagingError::Overflow)?;
+
+    Ok(())
+}
+
+/// Record `participant`'s new key in a thread's transparency log, when one was passed
+pub fn log_thread_key(
+    thread: Option<&Account<MessageThread>>,
+    log: Option<&mut Box<Account<KeyTransparencyLog>>>,
+    participant: Pubkey,
+    encryption_key: [u8; 32],
+    now: i64,
+) -> Result<()> {
+    let Some(log) = log else {
+        return Ok(());
+    };
+    let thread = thread.ok_or(MessagingError::NotAParticipant)?;
+    require!(
+        thread.is_participant(&participant),
+        MessagingError::NotAParticipant
+    );
+
+    if log.version == 0 {
+        log.thread = thread.key();
+        log.version = ACCOUNT_VERSION;
+    }
+    require!(
+        log.entries.len() < MAX_KEY_LOG_ENTRIES,
+        MessagingError::KeyLogFull
+    );
+    log.entries.push(KeyLogEntry {
+        participant,
+        encryption_key,
+        timestamp: now,
+    });
+
+    emit!(KeyLogAppended {
+        key_log: log.key(),
+        thread: log.thread,
+        participant,
+        encryption_key,
+        entries: log.entries.len() as u32,
+        timestamp: now,
+    });
+
+    Ok(())
 }
 
-#[derive(Accounts)]
-#[instruction(message_index: u32)]
-pub struct SendMessage<'info> {
-    #[account(mut)]
-    pub message_thread: Account<'info, MessageThread>,
-    
-    pub sender: Signer<'info>,
+/// A fresh subscription of `subscriber` to `channel`, shared by `subscribe_channel`
+/// and `gift_subscription`; a paid channel starts the first period now
+pub fn new_subscription(
+    subscriber: Pubkey,
+    channel: &Account<BroadcastChannel>,
+    gifted_by: Pubkey,
+    bump: u8,
+    now: i64,
+) -> Result<ChannelSubscription> {
+    let expires_at = if channel.subscription_period_seconds > 0 {
+        now.checked_add(channel.subscription_period_seconds)
+            .ok_or(MessagingError::Overflow)?
+    } else {
+        0
+    };
+
+    Ok(ChannelSubscription {
+        subscriber,
+        channel: channel.key(),
+        subscribed_at: now,
+        last_read_index: 0,
+        tipped_amount: 0,
+        label: [0u8; 16],
+        expires_at,
+        gifted_by,
+        bump,
+        version: ACCOUNT_VERSION,
+    })
 }
 
-#[derive(Accounts)]
-#[instruction(channel_name: String)]
-pub struct InitializeChannel<'info> {
-    #[account(
-        init,
-        payer = owner,
-        space = 8 + BroadcastChannel::INIT_SPACE,
-        seeds = [
-            b"broadcast_channel",
-            owner.key().as_ref(),
-            channel_name.as_bytes()
+/// Balance of `mint` held by `holder` in their associated token account
+/// A closed or never-created ATA counts as a zero balance
+pub fn gate_token_balance(
+    holder: &Pubkey,
+    mint: &Pubkey,
+    token_account: &AccountInfo,
+) -> Result<u64> {
+    let token_programs = [anchor_spl::token::ID, anchor_spl::token_2022::ID];
+    let is_ata = |program: &Pubkey| {
+        token_account.key() == get_associated_token_address_with_program_id(holder, mint, program)
+    };
+
+    if !token_programs.contains(token_account.owner) {
+        require!(
+            token_programs.iter().any(is_ata),
+            MessagingError::InvalidGateAccount
+        );
+        return Ok(0);
+    }
+
+    require!(
+        is_ata(token_account.owner),
+        MessagingError::InvalidGateAccount
+    );
+    let account = TokenAccount::try_deserialize(&mut &token_account.try_borrow_data()?[..])?;
+
+    Ok(account.amount)
+}
+
+/// Shared setup for `initialize_thread` and channel welcome threads
+pub fn new_thread(
+    participant_a: Pubkey,
+    participant_b: Pubkey,
+    thread_id: [u8; 32],
+    (a_key_version, b_key_version): (u32, u32),
+    config: Option<&ProgramConfig>,
+    bump: u8,
+    now: i64,
+) -> MessageThread {
+    MessageThread {
+        participant_a,
+        participant_b,
+        thread_id,
+        message_count: 0,
+        created_at: now,
+        last_message_at: 0,
+        status: ThreadStatus::Pending,
+        last_sender: Pubkey::default(),
+        last_content_hash: [0u8; 32],
+        consecutive_sender: Pubkey::default(),
+        consecutive_count: 0,
+        max_consecutive: config.map_or(DEFAULT_MAX_CONSECUTIVE, |config| {
+            config.default_max_consecutive
+        }),
+        a_observed_count: 0,
+        b_observed_count: 0,
+        last_read_by_a: 0,
+        last_read_by_b: 0,
+        a_key_version,
+        b_key_version,
+        lifecycle: ThreadLifecycle::Active,
+        a_muted: false,
+        b_muted: false,
+        message_ttl_seconds: 0,
+        pending_ttl_seconds: 0,
+        ttl_proposer: Pubkey::default(),
+        min_message_interval: config.map_or(0, |config| config.default_min_message_interval),
+        bump,
+        version: ACCOUNT_VERSION,
+    }
+}
+
+/// Shared setup for `initialize_channel` and `initialize_gated_channel`
+pub fn open_channel(
+    accounts: &mut InitializeChannel,
+    bump: u8,
+    channel_name: String,
+    description: String,
+    metadata_uri: String,
+    gate_collection: Pubkey,
+) -> Result<()> {
+    let channel = &mut accounts.broadcast_channel;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(channel_name.len() <= 32, MessagingError::ChannelNameTooLong);
+    require!(
+        description.len() <= 200 && metadata_uri.len() <= 200,
+        MessagingError::MetadataTooLong
+    );
+
+    channel.owner = accounts.owner.key();
+    channel.channel_name = channel_name;
+    channel.message_count = 0;
+    channel.subscriber_count = 0;
+    channel.created_at = now;
+    channel.last_broadcast_at = 0;
+    channel.description = description;
+    channel.metadata_uri = metadata_uri;
+    channel.total_tips = 0;
+    channel.auto_welcome_thread = false;
+    channel.welcome_content_hash = [0u8; 32];
+    channel.subscription_fee_lamports = 0;
+    channel.subscription_period_seconds = 0;
+    channel.gate_mint = Pubkey::default();
+    channel.gate_min_balance = 0;
+    channel.gate_collection = gate_collection;
+    channel.creator = channel.owner;
+    channel.pending_owner = Pubkey::default();
+    channel.avatar_uri = String::new();
+    channel.banner_uri = String::new();
+    channel.category = String::new();
+    channel.external_link = String::new();
+    channel.min_broadcast_interval =
+        read_config(&accounts.config)?.map_or(0, |config| config.default_min_broadcast_interval);
+    channel.bump = bump;
+    channel.version = ACCOUNT_VERSION;
+    channel.verified = false;
+    channel.verified_at = 0;
+
+    emit!(ChannelCreated {
+        channel: channel.key(),
+        owner: channel.owner,
+        channel_name: channel.channel_name.clone(),
+        timestamp: now,
+    });
+
+    msg!("Broadcast channel initialized!");
+    msg!("Owner: {}", channel.owner);
+    msg!("Channel: {}", channel.channel_name);
+
+    Ok(())
+}
+
+/// Check that `holder` owns an NFT from the verified `collection`
+/// `token_account` holds the NFT; `metadata` is its Metaplex metadata PDA
+pub fn verify_collection_nft(
+    holder: &Pubkey,
+    collection: &Pubkey,
+    token_account: &AccountInfo,
+    metadata: &AccountInfo,
+) -> Result<()> {
+    require!(
+        [anchor_spl::token::ID, anchor_spl::token_2022::ID].contains(token_account.owner),
+        MessagingError::InvalidCollectionNft
+    );
+    let token = TokenAccount::try_deserialize(&mut &token_account.try_borrow_data()?[..])?;
+    require!(
+        token.owner == *holder && token.amount >= 1,
+        MessagingError::InvalidCollectionNft
+    );
+
+    let (expected_metadata, _) = Metadata::find_pda(&token.mint);
+    require!(
+        metadata.key() == expected_metadata && *metadata.owner == anchor_spl::metadata::ID,
+        MessagingError::InvalidCollectionNft
+    );
+    let nft = MetadataAccount::try_deserialize(&mut &metadata.try_borrow_data()?[..])?;
+    require!(
+        nft.collection
+            .as_ref()
+            .is_some_and(|c| c.verified && c.key == *collection),
+        MessagingError::InvalidCollectionNft
+    );
+
+    Ok(())
+}
+
+/// Fill in a thread reaction and announce it
+/// The sender is only notified if the reactor asked to and they have not muted the thread
+pub fn set_reaction(
+    reaction: &mut Account<Reaction>,
+    thread: &Account<MessageThread>,
+    reactor: Pubkey,
+    message_index: u32,
+    emoji: [u8; 8],
+    notify_sender: bool,
+) -> Result<()> {
+    require!(emoji != [0u8; 8], MessagingError::InvalidEmoji);
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    reaction.reactor = reactor;
+    reaction.thread = thread.key();
+    reaction.message_index = message_index;
+    reaction.emoji = emoji;
+    reaction.timestamp = timestamp;
+    reaction.version = ACCOUNT_VERSION;
+
+    emit!(ReactionAdded {
+        thread: reaction.thread,
+        reactor,
+        message_index,
+        emoji,
+        notify_sender: notify_sender && !thread.is_muted_by(&thread.other_participant(&reactor)),
+        timestamp,
+    });
+
+    msg!("Reaction added to message {} by {}", message_index, reactor);
+
+    Ok(())
+}
+
+/// Announce that a thread reaction is being removed or replaced
+pub fn emit_reaction_removed(reaction: &Reaction) -> Result<()> {
+    emit!(ReactionRemoved {
+        thread: reaction.thread,
+        reactor: reaction.reactor,
+        message_index: reaction.message_index,
+        emoji: reaction.emoji,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "Reaction removed from message {} by {}",
+        reaction.message_index,
+        reaction.reactor
+    );
+
+    Ok(())
+}
+
+/// Create a program-owned PDA the way Anchor's `init` does
+/// An address pre-funded by someone else can't be created with create_account,
+/// so it is topped up, allocated and assigned instead
+pub fn create_pda_account<'info>(
+    payer: AccountInfo<'info>,
+    target: AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+    space: usize,
+    system_program: AccountInfo<'info>,
+    owner: &Pubkey,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+
+    if target.lamports() == 0 {
+        return create_account(
+            CpiContext::new_with_signer(
+                system_program,
+                CreateAccount {
+                    from: payer,
+                    to: target,
+                },
+                &[signer_seeds],
+            ),
+            rent,
+            space as u64,
+            owner,
+        );
+    }
+
+    let top_up = rent.saturating_sub(target.lamports());
+    if top_up > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer,
+                    to: target.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Allocate {
+                account_to_allocate: target.clone(),
+            },
+            &[signer_seeds],
+        ),
+        space as u64,
+    )?;
+    assign(
+        CpiContext::new_with_signer(
+            system_program,
+            Assign {
+                account_to_assign: target,
+            },
+            &[signer_seeds],
+        ),
+        owner,
+    )
+}
+
+/// Whether `contact` is a completed handshake between `a` and `b` (either direction)
+pub fn are_verified_contacts(
+    contact: Option<&Account<VerifiedContact>>,
+    a: &Pubkey,
+    b: &Pubkey,
+) -> bool {
+    contact.is_some_and(|c| {
+        c.verified && ((c.initiator == *a && c.peer == *b) || (c.initiator == *b && c.peer == *a))
+    })
+}
+
+/// Move a funded escrow out of a payment request to `recipient`
+/// SOL leaves the request's lamports; tokens leave its escrow ATA, which is
+/// then closed with its rent going to the payer who created it
+pub fn pay_out_escrow<'info>(
+    accounts: &SettlePayment<'info>,
+    bump: u8,
+    recipient: &AccountInfo<'info>,
+) -> Result<()> {
+    let request = &accounts.payment_request;
+
+    if request.mint == Pubkey::default() {
+        move_lamports(&request.to_account_info(), recipient, request.amount)?;
+        return Ok(());
+    }
+
+    let (Some(mint), Some(escrow), Some(to), Some(token_program)) = (
+        &accounts.mint,
+        &accounts.escrow_token_account,
+        &accounts.destination_token_account,
+        &accounts.token_program,
+    ) else {
+        return err!(MessagingError::InvalidPaymentAccount);
+    };
+    require_keys_eq!(
+        mint.key(),
+        request.mint,
+        MessagingError::InvalidPaymentAccount
+    );
+    require_keys_eq!(
+        escrow.key(),
+        get_associated_token_address_with_program_id(
+            &request.key(),
+            &request.mint,
+            &token_program.key()
+        ),
+        MessagingError::InvalidPaymentAccount
+    );
+    require_keys_eq!(
+        to.owner,
+        recipient.key(),
+        MessagingError::InvalidPaymentAccount
+    );
+
+    let request_id = request.request_id.to_le_bytes();
+    let seeds: &[&[u8]] = &[
+        b"payment_request",
+        request.thread.as_ref(),
+        &request_id,
+        &[bump],
+    ];
+    transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: escrow.to_account_info(),
+                mint: mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: request.to_account_info(),
+            },
+            &[seeds],
+        ),
+        request.amount,
+        mint.decimals,
+    )?;
+    close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: escrow.to_account_info(),
+            destination: accounts.payer.to_account_info(),
+            authority: request.to_account_info(),
+        },
+        &[seeds],
+    ))
+}
+
+/// Check a handle is already normalized: 3-20 bytes of `a-z`, `0-9` or `_`
+/// Clients lowercase before sending so each name maps to exactly one PDA
+pub fn validate_handle(handle: &str) -> Result<()> {
+    require!(
+        (MIN_HANDLE_LEN..=MAX_HANDLE_LEN).contains(&handle.len())
+            && handle
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_'),
+        MessagingError::InvalidHandle
+    );
+    Ok(())
+}
+
+/// Order a participant pair for thread PDA seeds
+/// Sorting makes A→B and B→A derive the same address, so either side can find
+/// an existing thread; who initiated is still recorded as `participant_a`
+pub fn sorted_participants(a: &Pubkey, b: &Pubkey) -> (Pubkey, Pubkey) {
+    if a <= b {
+        (*a, *b)
+    } else {
+        (*b, *a)
+    }
+}
+
+/// Validate a legacy account and grow it to `new_len`, returning its old length
+/// The signer must match one of the pubkeys stored at `authority_offsets` and
+/// pays any extra rent; the new tail is zeroed
+pub fn grow_for_migration<'info>(
+    account: &AccountInfo<'info>,
+    discriminator: &[u8],
+    authority_offsets: &[usize],
+    authority: &Signer<'info>,
+    new_len: usize,
+    system_program: &Program<'info, System>,
+    invalid: MessagingError,
+) -> Result<usize> {
+    require_keys_eq!(*account.owner, crate::ID, invalid);
+    {
+        let data = account.try_borrow_data()?;
+        if data.len() < 8 || data[..8] != *discriminator {
+            return Err(invalid.into());
+        }
+        let authorized = authority_offsets
+            .iter()
+            .any(|&offset| data.get(offset..offset + 32) == Some(authority.key().as_ref()));
+        require!(authorized, MessagingError::UnauthorizedSender);
+    }
+
+    let old_len = account.data_len();
+    if old_len < new_len {
+        let rent_due = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(account.lamports());
+        if rent_due > 0 {
+            transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    Transfer {
+                        from: authority.to_account_info(),
+                        to: account.clone(),
+                    },
+                ),
+                rent_due,
+            )?;
+        }
+        account.resize(new_len)?;
+    }
+
+    Ok(old_len)
+}
+
+/// Re-encode u32 fields of a legacy layout as u64 by inserting four zero bytes
+/// after each one (a little-endian u32 followed by zeros is the same u64)
+/// `ends` are the old-layout offsets just past each widened field, ascending;
+/// the account must already have room for the extra bytes
+pub fn widen_u32_fields(account: &AccountInfo, old_len: usize, ends: &[usize]) -> Result<()> {
+    let mut data = account.try_borrow_mut_data()?;
+    let mut widened = Vec::with_capacity(data.len());
+    let mut start = 0;
+    for &end in ends.iter().filter(|&&end| end <= old_len) {
+        widened.extend_from_slice(&data[start..end]);
+        widened.extend_from_slice(&[0u8; 4]);
+        start = end;
+    }
+    widened.extend_from_slice(&data[start..old_len]);
+    require!(
+        widened.len() <= data.len(),
+        MessagingError::InvalidMigrationAccount
+    );
+
+    data[..widened.len()].copy_from_slice(&widened);
+    data[widened.len()..].fill(0);
+    Ok(())
+}
+
+/// Whether a channel account already uses the current layout
+/// A legacy layout misparses as the current one, so the stored bump must also
+/// re-derive the account's address
+pub fn is_current_channel(channel_info: &AccountInfo, program_id: &Pubkey) -> bool {
+    let Ok(data) = channel_info.try_borrow_data() else {
+        return false;
+    };
+    let Ok(channel) = BroadcastChannel::try_deserialize(&mut &data[..]) else {
+        return false;
+    };
+    channel.version >= ACCOUNT_VERSION
+        && Pubkey::create_program_address(
+            &[
+                b"broadcast_channel",
+                channel.creator.as_ref(),
+                channel.channel_name.as_bytes(),
+                &[channel.bump],
+            ],
+            program_id,
+        )
+        .is_ok_and(|address| address == channel_info.key())
+}
+
+/// Anchor instruction discriminator of an spl-account-compression instruction
+fn compression_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(format!("global:{name}").as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// Call spl-account-compression with the compressed log PDA as tree authority
+fn invoke_compression<'info>(
+    log: &Account<'info, CompressedLog>,
+    bump: u8,
+    merkle_tree: &UncheckedAccount<'info>,
+    compression_program: &UncheckedAccount<'info>,
+    noop_program: &UncheckedAccount<'info>,
+    data: Vec<u8>,
+) -> Result<()> {
+    let instruction = Instruction {
+        program_id: SPL_ACCOUNT_COMPRESSION_ID,
+        accounts: vec![
+            AccountMeta::new(merkle_tree.key(), false),
+            AccountMeta::new_readonly(log.key(), true),
+            AccountMeta::new_readonly(noop_program.key(), false),
         ],
-        bump
-    )]
-    pub broadcast_channel: Account<'info, BroadcastChannel>,
-    
-    #[account(mut)]
-    pub owner: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
+        data,
+    };
+    invoke_signed(
+        &instruction,
+        &[
+            merkle_tree.to_account_info(),
+            log.to_account_info(),
+            noop_program.to_account_info(),
+            compression_program.to_account_info(),
+        ],
+        &[&[b"compressed_log", log.target.as_ref(), &[bump]]],
+    )?;
+    Ok(())
+}
+
+/// Shared setup for `enable_thread_compression` and `enable_channel_compression`
+#[allow(clippy::too_many_arguments)]
+pub fn open_compressed_log<'info>(
+    log: &mut Account<'info, CompressedLog>,
+    bump: u8,
+    target: Pubkey,
+    merkle_tree: &UncheckedAccount<'info>,
+    compression_program: &UncheckedAccount<'info>,
+    noop_program: &UncheckedAccount<'info>,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Result<()> {
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    log.target = target;
+    log.merkle_tree = merkle_tree.key();
+    log.leaf_count = 0;
+    log.created_at = timestamp;
+    log.version = ACCOUNT_VERSION;
+
+    let mut data = compression_discriminator("init_empty_merkle_tree").to_vec();
+    data.extend_from_slice(&max_depth.to_le_bytes());
+    data.extend_from_slice(&max_buffer_size.to_le_bytes());
+    invoke_compression(
+        log,
+        bump,
+        merkle_tree,
+        compression_program,
+        noop_program,
+        data,
+    )?;
+
+    emit!(CompressedLogEnabled {
+        target,
+        merkle_tree: log.merkle_tree,
+        max_depth,
+        max_buffer_size,
+        timestamp,
+    });
+
+    msg!("Compressed log enabled for {}", target);
+
+    Ok(())
+}
+
+/// Append a message leaf to a compressed log's tree
+#[allow(clippy::too_many_arguments)]
+pub fn append_compressed_leaf<'info>(
+    log: &mut Account<'info, CompressedLog>,
+    bump: u8,
+    merkle_tree: &UncheckedAccount<'info>,
+    compression_program: &UncheckedAccount<'info>,
+    noop_program: &UncheckedAccount<'info>,
+    sender: Pubkey,
+    message_index: u32,
+    content_hash: [u8; 32],
+    timestamp: i64,
+) -> Result<()> {
+    let leaf = hashv(&[
+        sender.as_ref(),
+        &message_index.to_le_bytes(),
+        &timestamp.to_le_bytes(),
+        &content_hash,
+    ])
+    .to_bytes();
+
+    let mut data = compression_discriminator("append").to_vec();
+    data.extend_from_slice(&leaf);
+    invoke_compression(
+        log,
+        bump,
+        merkle_tree,
+        compression_program,
+        noop_program,
+        data,
+    )?;
+
+    let leaf_index = log.leaf_count;
+    log.leaf_count = log
+        .leaf_count
+        .checked_add(1)
+        .ok_or(MessagingError::Overflow)?;
+
+    emit!(CompressedLeafAppended {
+        target: log.target,
+        merkle_tree: log.merkle_tree,
+        leaf_index,
+        leaf,
+        sender,
+        message_index,
+        content_hash,
+        timestamp,
+    });
+
+    Ok(())
+}
+
+/// Create the next pointer in `owner`'s inbox, pointing at `thread`
+#[allow(clippy::too_many_arguments)]
+pub fn register_thread_pointer<'info>(
+    inbox: &mut Account<'info, UserInbox>,
+    pointer: &UncheckedAccount<'info>,
+    owner: Pubkey,
+    thread: Pubkey,
+    payer: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    program_id: &Pubkey,
+    now: i64,
+) -> Result<()> {
+    // Fresh inbox from `init_if_needed`
+    if inbox.owner == Pubkey::default() {
+        inbox.owner = owner;
+        inbox.version = ACCOUNT_VERSION;
+    }
+
+    let index = inbox.next_index;
+    let index_bytes = index.to_le_bytes();
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"thread_pointer", owner.as_ref(), &index_bytes],
+        program_id,
+    );
+    require_keys_eq!(
+        pointer.key(),
+        expected,
+        MessagingError::InvalidThreadPointer
+    );
+
+    let pointer_info = pointer.to_account_info();
+    create_pda_account(
+        payer,
+        pointer_info.clone(),
+        &[b"thread_pointer", owner.as_ref(), &index_bytes, &[bump]],
+        8 + ThreadPointer::INIT_SPACE,
+        system_program,
+        program_id,
+    )?;
+    ThreadPointer {
+        owner,
+        thread,
+        index,
+        created_at: now,
+        version: ACCOUNT_VERSION,
+    }
+    .try_serialize(&mut &mut pointer_info.try_borrow_mut_data()?[..])?;
+
+    inbox.next_index = inbox
+        .next_index
+        .checked_add(1)
+        .ok_or(MessagingError::Overflow)?;
+
+    emit!(ThreadPointerAdded {
+        owner,
+        thread,
+        pointer: expected,
+        index,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+/// Shared body of `verify_channel` and `revoke_verification`
+pub fn set_channel_verification(
+    ctx: Context<SetChannelVerification>,
+    verified: bool,
+) -> Result<()> {
+    let channel = &mut ctx.accounts.broadcast_channel;
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    channel.verified = verified;
+    channel.verified_at = if verified { timestamp } else { 0 };
+
+    emit!(ChannelVerificationChanged {
+        channel: channel.key(),
+        verifier: ctx.accounts.verifier.key(),
+        verified,
+        timestamp,
+    });
+
+    msg!("Channel {} verified: {}", channel.channel_name, verified);
+
+    Ok(())
+}
+
+/// Shared body of `flag_suspicious_channel` and `clear_suspicious_flag`
+pub fn set_channel_suspicion(
+    ctx: Context<SetChannelSuspicion>,
+    suspicious: bool,
+    reason_code: u8,
+) -> Result<()> {
+    let channel = &mut ctx.accounts.broadcast_channel;
+
+    channel.suspicious = suspicious;
+    channel.reason_code = reason_code;
+
+    emit!(ChannelFlagged {
+        channel: channel.key(),
+        moderator: ctx.accounts.authority.key(),
+        suspicious,
+        reason_code,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!(
+        "Channel {} suspicious: {}",
+        channel.channel_name,
+        suspicious
+    );
+
+    Ok(())
+}
+
+/// Bytes v3 appended to the channel layout (`verified`, `verified_at`)
+const CHANNEL_V3_FIELDS_LEN: usize = 1 + 8;
+
+/// Bytes appended to the v2 channel layout by `version`: the v3 verification fields,
+/// v6 `comments_enabled`, v8 `private`, v9 `key_epoch`, v11 `status` / `closing_at`
+/// and v12 `suspicious` / `reason_code`
+const fn channel_tail_len(version: u8) -> usize {
+    match version {
+        0..=2 => 0,
+        3..=5 => CHANNEL_V3_FIELDS_LEN,
+        6..=7 => CHANNEL_V3_FIELDS_LEN + 1,
+        8 => CHANNEL_V3_FIELDS_LEN + 2,
+        9..=10 => CHANNEL_V3_FIELDS_LEN + 2 + 4,
+        11 => CHANNEL_V3_FIELDS_LEN + 2 + 4 + 1 + 8,
+        _ => CHANNEL_V3_FIELDS_LEN + 2 + 4 + 1 + 8 + 1 + 1,
+    }
+}
+
+const CHANNEL_TAIL_LEN: usize = channel_tail_len(ACCOUNT_VERSION);
+
+fn skip_string(data: &[u8], offset: usize) -> Option<usize> {
+    let len = u32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?);
+    offset.checked_add(4 + len as usize)
+}
+
+/// Serialized length of a channel in the v2 layout, which later versions only extend
+/// Parsed by hand because whatever follows the v2 fields is not a valid v3 tail
+pub fn channel_v2_len(data: &[u8]) -> Option<usize> {
+    // channel_name, then message/subscriber counts and timestamps
+    let mut offset = skip_string(data, 8 + 32)? + 8 * 4;
+    // description, metadata_uri
+    offset = skip_string(data, skip_string(data, offset)?)?;
+    // total_tips .. pending_owner
+    offset += 8 + 1 + 32 + 8 + 8 + 32 + 8 + 32 + 32 + 32;
+    // avatar_uri, banner_uri, category, external_link
+    for _ in 0..4 {
+        offset = skip_string(data, offset)?;
+    }
+    // min_broadcast_interval, bump, version
+    offset += 8 + 1 + 1;
+    (offset <= data.len()).then_some(offset)
+}
+
+/// Read the config PDA, or `None` if it has never been created
+/// A config that exists but does not deserialize, e.g. one still on an older layout,
+/// is an error rather than the defaults, so the pause switch cannot silently lapse
+pub fn read_config(config: &UncheckedAccount) -> Result<Option<ProgramConfig>> {
+    let data = config.try_borrow_data()?;
+    if data.is_empty() {
+        return Ok(None);
+    }
+    ProgramConfig::try_deserialize(&mut &data[..])
+        .map(Some)
+        .map_err(|_| error!(MessagingError::InvalidConfig))
+}
+
+/// Pause switch checked by every user instruction
+pub fn is_paused(config: &UncheckedAccount) -> Result<bool> {
+    Ok(read_config(config)?.is_some_and(|config| config.paused))
+}
+
+/// Move lamports out of an account this program owns, with checked balances
+pub fn move_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    if from.key == to.key {
+        return Ok(());
+    }
+    let remaining = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(MessagingError::Overflow)?;
+    let credited = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(MessagingError::Overflow)?;
+    **from.try_borrow_mut_lamports()? = remaining;
+    **to.try_borrow_mut_lamports()? = credited;
+    Ok(())
+}
+
+/// Move a protocol fee from `payer` into the treasury PDA
+/// The treasury and system program are only required when there is a fee
+pub fn pay_protocol_fee<'info>(
+    payer: &Signer<'info>,
+    treasury: Option<&UncheckedAccount<'info>>,
+    system_program: Option<AccountInfo<'info>>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let (Some(treasury), Some(system_program)) = (treasury, system_program) else {
+        return err!(MessagingError::TreasuryRequired);
+    };
+
+    transfer(
+        CpiContext::new(
+            system_program,
+            Transfer {
+                from: payer.to_account_info(),
+                to: treasury.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    emit!(ProtocolFeePaid {
+        payer: payer.key(),
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Accounts of a send instruction that charges the flat message fee
+pub trait MessageFeeAccounts<'info> {
+    /// Signer the fee is taken from
+    fn fee_payer(&self) -> &Signer<'info>;
+
+    fn config(&self) -> &UncheckedAccount<'info>;
+
+    fn treasury(&self) -> Option<&UncheckedAccount<'info>>;
+
+    fn system_program(&self) -> Option<AccountInfo<'info>>;
+
+    /// Contacts of the recipient message fee-free, verified channels broadcast fee-free
+    fn fee_exempt(&self) -> bool;
+}
+
+/// Charge the config's message fee for `count` messages, unless the sender is exempt
+pub fn charge_message_fee<'info>(
+    accounts: &impl MessageFeeAccounts<'info>,
+    count: u64,
+) -> Result<()> {
+    if accounts.fee_exempt() {
+        return Ok(());
+    }
+    let fee = read_config(accounts.config())?.map_or(0, |c| c.message_fee_lamports);
+    pay_protocol_fee(
+        accounts.fee_payer(),
+        accounts.treasury(),
+        accounts.system_program(),
+        fee.checked_mul(count).ok_or(MessagingError::Overflow)?,
+    )
+}
+
+impl<'info> MessageFeeAccounts<'info> for SendMessage<'info> {
+    fn fee_payer(&self) -> &Signer<'info> {
+        &self.sender
+    }
+
+    fn config(&self) -> &UncheckedAccount<'info> {
+        &self.config
+    }
+
+    fn treasury(&self) -> Option<&UncheckedAccount<'info>> {
+        self.treasury.as_ref()
+    }
+
+    fn system_program(&self) -> Option<AccountInfo<'info>> {
+        self.system_program.as_ref().map(|p| p.to_account_info())
+    }
+
+    fn fee_exempt(&self) -> bool {
+        self.sender_contact.is_some()
+    }
+}
+
+impl<'info> MessageFeeAccounts<'info> for SendMessageStored<'info> {
+    fn fee_payer(&self) -> &Signer<'info> {
+        &self.sender
+    }
+
+    fn config(&self) -> &UncheckedAccount<'info> {
+        &self.config
+    }
+
+    fn treasury(&self) -> Option<&UncheckedAccount<'info>> {
+        self.treasury.as_ref()
+    }
+
+    fn system_program(&self) -> Option<AccountInfo<'info>> {
+        Some(self.system_program.to_account_info())
+    }
+
+    fn fee_exempt(&self) -> bool {
+        self.sender_contact.is_some()
+    }
+}
+
+impl<'info> MessageFeeAccounts<'info> for SendMessageCompressed<'info> {
+    fn fee_payer(&self) -> &Signer<'info> {
+        &self.sender
+    }
+
+    fn config(&self) -> &UncheckedAccount<'info> {
+        &self.config
+    }
+
+    fn treasury(&self) -> Option<&UncheckedAccount<'info>> {
+        self.treasury.as_ref()
+    }
+
+    fn system_program(&self) -> Option<AccountInfo<'info>> {
+        self.system_program.as_ref().map(|p| p.to_account_info())
+    }
+
+    fn fee_exempt(&self) -> bool {
+        self.sender_contact.is_some()
+    }
+}
+
+impl<'info> MessageFeeAccounts<'info> for SendMessageRelayed<'info> {
+    fn fee_payer(&self) -> &Signer<'info> {
+        &self.relayer
+    }
+
+    fn config(&self) -> &UncheckedAccount<'info> {
+        &self.config
+    }
+
+    fn treasury(&self) -> Option<&UncheckedAccount<'info>> {
+        self.treasury.as_ref()
+    }
+
+    fn system_program(&self) -> Option<AccountInfo<'info>> {
+        self.system_program.as_ref().map(|p| p.to_account_info())
+    }
+
+    fn fee_exempt(&self) -> bool {
+        self.sender_contact.is_some()
+    }
+}
+
+impl<'info> MessageFeeAccounts<'info> for ForwardMessage<'info> {
+    fn fee_payer(&self) -> &Signer<'info> {
+        &self.forwarder
+    }
+
+    fn config(&self) -> &UncheckedAccount<'info> {
+        &self.config
+    }
+
+    fn treasury(&self) -> Option<&UncheckedAccount<'info>> {
+        self.treasury.as_ref()
+    }
+
+    fn system_program(&self) -> Option<AccountInfo<'info>> {
+        Some(self.system_program.to_account_info())
+    }
+
+    fn fee_exempt(&self) -> bool {
+        self.sender_contact.is_some()
+    }
+}
+
+impl<'info> MessageFeeAccounts<'info> for SendBroadcast<'info> {
+    fn fee_payer(&self) -> &Signer<'info> {
+        self.fee_payer.as_ref().unwrap_or(&self.sender)
+    }
+
+    fn config(&self) -> &UncheckedAccount<'info> {
+        &self.config
+    }
+
+    fn treasury(&self) -> Option<&UncheckedAccount<'info>> {
+        self.treasury.as_ref()
+    }
+
+    fn system_program(&self) -> Option<AccountInfo<'info>> {
+        self.system_program.as_ref().map(|p| p.to_account_info())
+    }
+
+    fn fee_exempt(&self) -> bool {
+        self.broadcast_channel.verified
+    }
+}
+
+impl<'info> MessageFeeAccounts<'info> for SendBroadcastCompressed<'info> {
+    fn fee_payer(&self) -> &Signer<'info> {
+        self.fee_payer.as_ref().unwrap_or(&self.sender)
+    }
+
+    fn config(&self) -> &UncheckedAccount<'info> {
+        &self.config
+    }
+
+    fn treasury(&self) -> Option<&UncheckedAccount<'info>> {
+        self.treasury.as_ref()
+    }
+
+    fn system_program(&self) -> Option<AccountInfo<'info>> {
+        self.system_program.as_ref().map(|p| p.to_account_info())
+    }
+
+    fn fee_exempt(&self) -> bool {
+        self.broadcast_channel.verified
+    }
+}
+
+impl<'info> MessageFeeAccounts<'info> for ScheduleBroadcast<'info> {
+    fn fee_payer(&self) -> &Signer<'info> {
+        &self.payer
+    }
+
+    fn config(&self) -> &UncheckedAccount<'info> {
+        &self.config
+    }
+
+    fn treasury(&self) -> Option<&UncheckedAccount<'info>> {
+        self.treasury.as_ref()
+    }
+
+    fn system_program(&self) -> Option<AccountInfo<'info>> {
+        Some(self.system_program.to_account_info())
+    }
+
+    fn fee_exempt(&self) -> bool {
+        self.broadcast_channel.verified
+    }
+}
+
+impl<'info> MessageFeeAccounts<'info> for SendMultiBroadcast<'info> {
+    fn fee_payer(&self) -> &Signer<'info> {
+        self.fee_payer.as_ref().unwrap_or(&self.sender)
+    }
+
+    fn config(&self) -> &UncheckedAccount<'info> {
+        &self.config
+    }
+
+    fn treasury(&self) -> Option<&UncheckedAccount<'info>> {
+        self.treasury.as_ref()
+    }
+
+    fn system_program(&self) -> Option<AccountInfo<'info>> {
+        self.system_program.as_ref().map(|p| p.to_account_info())
+    }
+
+    fn fee_exempt(&self) -> bool {
+        // Each channel is checked by the handler, which charges per unverified channel
+        false
+    }
 }
 
-#[derive(Accounts)]
-pub struct SubscribeChannel<'info> {
-    #[account(
-        init,
-        payer = subscriber,
-        space = 8 + ChannelSubscription::INIT_SPACE,
-        seeds = [
-            b"subscription",
-            broadcast_channel.key().as_ref(),
-            subscriber.key().as_ref()
-        ],
-        bump
-    )]
-    pub subscription: Account<'info, ChannelSubscription>,
-    
-    #[account(mut)]
-    pub broadcast_channel: Account<'info, BroadcastChannel>,
-    
-    #[account(mut)]
-    pub subscriber: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
+/// Participant a `send_message` signer acts for: the session key's owner when one
+/// is passed, otherwise the signer itself
+pub fn sending_as(signer: &Pubkey, session_key: &Option<Account<SessionKey>>) -> Pubkey {
+    session_key
+        .as_ref()
+        .map_or(*signer, |session| session.owner)
 }
 
-#[derive(Accounts)]
-#[instruction(message_index: u32)]
-pub struct SendBroadcast<'info> {
-    #[account(mut)]
-    pub broadcast_channel: Account<'info, BroadcastChannel>,
-    
-    pub sender: Signer<'info>,
+/// Account holding indexed messages, deserialized as whichever type it is
+pub enum MessageHost {
+    Channel(Box<BroadcastChannel>),
+    Thread(Box<MessageThread>),
 }
 
-#[derive(Accounts)]
-pub struct CloseThread<'info> {
-    #[account(
-        mut,
-        has_one = participant_a @ MessagingError::UnauthorizedSender,
-        close = recipient
-    )]
-    pub message_thread: Account<'info, MessageThread>,
-    
-    pub participant_a: Signer<'info>,
-    
-    /// CHECK: Recipient can be any account
-    #[account(mut)]
-    pub recipient: UncheckedAccount<'info>,
+impl MessageHost {
+    pub fn kind(&self) -> MessageHostKind {
+        match self {
+            MessageHost::Channel(_) => MessageHostKind::Channel,
+            MessageHost::Thread(_) => MessageHostKind::Thread,
+        }
+    }
+
+    pub fn message_count(&self) -> u64 {
+        match self {
+            MessageHost::Channel(channel) => channel.message_count,
+            MessageHost::Thread(thread) => thread.message_count,
+        }
+    }
 }
 
-#[derive(Accounts)]
-pub struct CloseChannel<'info> {
-    #[account(
-        mut,
-        has_one = owner @ MessagingError::UnauthorizedSender,
-        close = recipient
-    )]
-    pub broadcast_channel: Account<'info, BroadcastChannel>,
-    
-    pub owner: Signer<'info>,
-    
-    /// CHECK: Recipient can be any account
-    #[account(mut)]
-    pub recipient: UncheckedAccount<'info>,
+/// Parse a channel or thread owned by this program by its account discriminator
+pub fn read_message_host(host: &UncheckedAccount, invalid: MessagingError) -> Result<MessageHost> {
+    require_keys_eq!(*host.owner, crate::ID, invalid);
+    let data = host.try_borrow_data()?;
+    if data.starts_with(BroadcastChannel::DISCRIMINATOR) {
+        let channel = BroadcastChannel::try_deserialize(&mut &data[..])?;
+        Ok(MessageHost::Channel(Box::new(channel)))
+    } else if data.starts_with(MessageThread::DISCRIMINATOR) {
+        let thread = MessageThread::try_deserialize(&mut &data[..])?;
+        Ok(MessageHost::Thread(Box::new(thread)))
+    } else {
+        Err(invalid.into())
+    }
 }
 
-// ============================================================================
-// Account Structures
-// ============================================================================
+/// A reply must quote a message sent before `message_index`
+pub fn check_reply_to(reply_to_index: Option<u32>, message_index: u32) -> Result<()> {
+    if let Some(reply_to) = reply_to_index {
+        require!(reply_to < message_index, MessagingError::InvalidReplyTarget);
+    }
+    Ok(())
+}
 
-#[account]
-#[derive(InitSpace)]
-pub struct MessageThread {
-    /// First participant (thread initiator)
-    pub participant_a: Pubkey,
-    
-    /// Second participant
-    pub participant_b: Pubkey,
-    
-    /// Unique thread identifier
-    pub thread_id: [u8; 32],
-    
-    /// Total number of messages sent
-    pub message_count: u32,
-    
-    /// Timestamp of thread creation
-    pub created_at: i64,
-    
-    /// Timestamp of last message
-    pub last_message_at: i64,
+/// Seed distinguishing a channel's invites: the invitee's key or the code hash
+pub fn invite_seed(invitee: &Option<Pubkey>, code_hash: &Option<[u8; 32]>) -> [u8; 32] {
+    match (invitee, code_hash) {
+        (Some(invitee), _) => invitee.to_bytes(),
+        (None, Some(code_hash)) => *code_hash,
+        (None, None) => [0; 32],
+    }
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct BroadcastChannel {
-    /// Channel owner (broadcaster)
-    pub owner: Pubkey,
-    
-    /// Channel name (max 32 chars)
-    #[max_len(32)]
-    pub channel_name: String,
-    
-    /// Total messages broadcast
-    pub message_count: u32,
-    
-    /// Number of subscribers
-    pub subscriber_count: u32,
-    
-    /// Timestamp of channel creation
-    pub created_at: i64,
-    
-    /// Timestamp of last broadcast
-    pub last_broadcast_at: i64,
+/// Thread participants pin in threads; channel owners and moderating admins in channels
+pub fn can_pin(host: &MessageHost, signer: &Pubkey, admin: Option<&Account<ChannelAdmin>>) -> bool {
+    match host {
+        MessageHost::Thread(thread) => {
+            *signer == thread.participant_a || *signer == thread.participant_b
+        }
+        MessageHost::Channel(channel) => {
+            *signer == channel.owner
+                || admin.is_some_and(|admin| admin.has_permission(ChannelAdmin::MODERATE))
+        }
+    }
 }
 
-#[account]
-#[derive(InitSpace)]
-pub struct ChannelSubscription {
-    /// Subscriber's public key
-    pub subscriber: Pubkey,
-    
-    /// Channel being subscribed to
-    pub channel: Pubkey,
-    
-    /// When subscription was created
-    pub subscribed_at: i64,
-    
-    /// Last message index read by subscriber
-    pub last_read_index: u32,
+/// Parse a profile PDA that may not exist yet
+/// Profiles written before a field was appended are zero-padded, so the newer
+/// fields read as unset until `migrate_profile` grows the account
+pub fn read_profile(info: &AccountInfo) -> Result<Option<UserProfile>> {
+    if info.data_is_empty() {
+        return Ok(None);
+    }
+    let mut data = info.try_borrow_data()?.to_vec();
+    data.resize(data.len().max(8 + UserProfile::INIT_SPACE), 0);
+    UserProfile::try_deserialize(&mut &data[..]).map(Some)
+}
+
+/// Move a thread escrow's funds to `recipient`, closing its token account
+pub fn pay_out_thread_escrow<'info>(
+    accounts: &SettleEscrow<'info>,
+    bump: u8,
+    recipient: &AccountInfo<'info>,
+) -> Result<()> {
+    let escrow = &accounts.thread_escrow;
+
+    if escrow.mint == Pubkey::default() {
+        move_lamports(&escrow.to_account_info(), recipient, escrow.amount)?;
+        return Ok(());
+    }
+
+    let (Some(mint), Some(escrow_tokens), Some(to), Some(token_program)) = (
+        &accounts.mint,
+        &accounts.escrow_token_account,
+        &accounts.destination_token_account,
+        &accounts.token_program,
+    ) else {
+        return err!(MessagingError::InvalidPaymentAccount);
+    };
+    require_keys_eq!(
+        mint.key(),
+        escrow.mint,
+        MessagingError::InvalidPaymentAccount
+    );
+    require_keys_eq!(
+        escrow_tokens.key(),
+        get_associated_token_address_with_program_id(
+            &escrow.key(),
+            &escrow.mint,
+            &token_program.key()
+        ),
+        MessagingError::InvalidPaymentAccount
+    );
+    require_keys_eq!(
+        to.owner,
+        recipient.key(),
+        MessagingError::InvalidPaymentAccount
+    );
+
+    let escrow_id = escrow.escrow_id.to_le_bytes();
+    let seeds: &[&[u8]] = &[
+        b"thread_escrow",
+        escrow.thread.as_ref(),
+        &escrow_id,
+        &[bump],
+    ];
+    transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: escrow_tokens.to_account_info(),
+                mint: mint.to_account_info(),
+                to: to.to_account_info(),
+                authority: escrow.to_account_info(),
+            },
+            &[seeds],
+        ),
+        escrow.amount,
+        mint.decimals,
+    )?;
+    close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: escrow_tokens.to_account_info(),
+            destination: accounts.depositor.to_account_info(),
+            authority: escrow.to_account_info(),
+        },
+        &[seeds],
+    ))
+}
+
+/// UTC day of a timestamp, as days since the Unix epoch
+pub fn stats_day(now: i64) -> Result<u32> {
+    u32::try_from(now.div_euclid(SECONDS_PER_DAY)).map_err(|_| MessagingError::Overflow.into())
+}
+
+/// Snapshot leaf of one subscription: `hash(0x00, subscriber, subscribed_at)`
+pub fn snapshot_leaf(subscriber: &Pubkey, subscribed_at: i64) -> [u8; 32] {
+    hashv(&[&[0], subscriber.as_ref(), &subscribed_at.to_le_bytes()]).to_bytes()
+}
+
+/// Fold a proof into a root; inner nodes are `hash(0x01, min(a, b), max(a, b))`
+pub fn merkle_root_from_proof(leaf: [u8; 32], proof: &[[u8; 32]]) -> [u8; 32] {
+    proof.iter().fold(leaf, |node, sibling| {
+        let (first, second) = if node <= *sibling {
+            (&node, sibling)
+        } else {
+            (sibling, &node)
+        };
+        hashv(&[&[1], first, second]).to_bytes()
+    })
 }
 
 // ============================================================================
@@ -355,4 +16989,370 @@ pub enum MessagingError {
     
     #[msg("Thread is closed and cannot receive new messages")]
     ThreadClosed,
+
+    #[msg("Quiet hours must be distinct minutes between 0 and 1439")]
+    InvalidQuietHours,
+
+    #[msg("Reaction emoji cannot be empty")]
+    InvalidEmoji,
+
+    #[msg("Channel description and metadata URI cannot exceed 200 characters")]
+    MetadataTooLong,
+
+    #[msg("Subscription does not belong to this channel")]
+    SubscriptionMismatch,
+
+    #[msg("Batch must contain at least one entry")]
+    EmptyBatch,
+
+    #[msg("The same account was passed more than once")]
+    DuplicateAccount,
+
+    #[msg("Thread has no messages")]
+    NoMessages,
+
+    #[msg("The edit window for this message has closed")]
+    EditWindowClosed,
+
+    #[msg("Too many messages in a row; wait for the other participant to reply")]
+    AwaitingReply,
+
+    #[msg("Cannot verify yourself as a contact")]
+    InvalidContact,
+
+    #[msg("Contact is already verified")]
+    AlreadyVerified,
+
+    #[msg("Revealed secret does not match the initiator's commitment")]
+    VerificationMismatch,
+
+    #[msg("Amount must be greater than zero")]
+    InvalidAmount,
+
+    #[msg("Arithmetic overflow")]
+    Overflow,
+
+    #[msg("Welcome thread account is missing or not the expected PDA")]
+    InvalidWelcomeThread,
+
+    #[msg("Missing or invalid Ed25519 signature")]
+    InvalidSignature,
+
+    #[msg("Subscription label cannot exceed 16 bytes")]
+    LabelTooLong,
+
+    #[msg("Folder name cannot exceed 32 characters")]
+    FolderNameTooLong,
+
+    #[msg("Folder cannot hold more than 64 entries")]
+    FolderFull,
+
+    #[msg("Account is already in this folder")]
+    AlreadyInFolder,
+
+    #[msg("Account is not in this folder")]
+    NotInFolder,
+
+    #[msg("Group name cannot exceed 32 characters")]
+    GroupNameTooLong,
+
+    #[msg("Group cannot have more than 32 members")]
+    GroupFull,
+
+    #[msg("Account is already a member of this group")]
+    AlreadyMember,
+
+    #[msg("Account is not a member of this group")]
+    NotAMember,
+
+    #[msg("The group creator cannot be removed")]
+    CannotRemoveCreator,
+
+    #[msg("Thread was rejected by the recipient")]
+    ThreadRejected,
+
+    #[msg("Thread is not a pending message request")]
+    ThreadNotPending,
+
+    #[msg("One participant has blocked the other")]
+    UserBlocked,
+
+    #[msg("Display name exceeds maximum length of 32 characters")]
+    DisplayNameTooLong,
+
+    #[msg("Channel charges a subscription fee but no vault was provided")]
+    MissingChannelVault,
+
+    #[msg("Channel subscriptions do not expire")]
+    SubscriptionDoesNotExpire,
+
+    #[msg("Vault balance is too low for this withdrawal")]
+    InsufficientVaultBalance,
+
+    #[msg("Token account is not the subscriber's associated account for the gate mint")]
+    InvalidGateAccount,
+
+    #[msg("Subscriber does not hold enough of the gate token")]
+    InsufficientGateBalance,
+
+    #[msg("Subscriber must present an NFT from the channel's verified collection")]
+    InvalidCollectionNft,
+
+    #[msg("Account is not a broadcast channel owned by this program")]
+    InvalidChannelAccount,
+
+    #[msg("Message record has not expired yet")]
+    MessageNotExpired,
+
+    #[msg("Sending too fast; wait for the minimum interval to pass")]
+    RateLimited,
+
+    #[msg("Recipient requires an outreach deposit; pass the thread vault")]
+    DepositRequired,
+
+    #[msg("Recipient has not accepted or rejected the thread yet")]
+    DepositUnsettled,
+
+    #[msg("Tip recipient or token accounts do not match the thread")]
+    InvalidTipAccount,
+
+    #[msg("Payment request is not in the right state for this action")]
+    InvalidPaymentState,
+
+    #[msg("Payment accounts do not match the request")]
+    InvalidPaymentAccount,
+
+    #[msg("Contact alias cannot exceed 32 bytes")]
+    AliasTooLong,
+
+    #[msg("Handle must be 3-20 characters of a-z, 0-9 or _")]
+    InvalidHandle,
+
+    #[msg("Account is not a migratable account of this program")]
+    InvalidMigrationAccount,
+
+    #[msg("Merkle tree does not belong to this compressed log")]
+    InvalidMerkleTree,
+
+    #[msg("Thread pointer does not match the inbox or thread")]
+    InvalidThreadPointer,
+
+    #[msg("Directory page does not exist or does not list this channel")]
+    InvalidDirectoryPage,
+
+    #[msg("Directory page is full")]
+    DirectoryPageFull,
+
+    #[msg("The program is paused")]
+    ProgramPaused,
+
+    #[msg("Invalid config value")]
+    InvalidConfig,
+
+    #[msg("The protocol treasury and system program are required to pay the fee")]
+    TreasuryRequired,
+
+    #[msg("Invalid session key")]
+    InvalidSessionKey,
+
+    #[msg("Session key has expired")]
+    SessionKeyExpired,
+
+    #[msg("Session key is not valid for this thread")]
+    SessionKeyScope,
+
+    #[msg("Comments are disabled on this channel")]
+    CommentsDisabled,
+
+    #[msg("Subscription is not active")]
+    SubscriptionInactive,
+
+    #[msg("A poll needs 2 to 10 non-empty options of up to 50 bytes and a future deadline")]
+    InvalidPollOptions,
+
+    #[msg("No such poll option")]
+    InvalidPollOption,
+
+    #[msg("The poll is closed")]
+    PollClosed,
+
+    #[msg("Polls attach to a broadcast channel or message thread")]
+    InvalidPollTarget,
+
+    #[msg("Messages can only be forwarded from a thread or channel of this program")]
+    InvalidForwardSource,
+
+    #[msg("Pass the source message's record to forward anything but the latest message")]
+    ProvenanceUnavailable,
+
+    #[msg("A reply must quote an earlier message")]
+    InvalidReplyTarget,
+
+    #[msg("Attachment needs a content id, a type/subtype MIME type, a size and a wrapped key")]
+    InvalidAttachment,
+
+    #[msg("This channel is invite-only")]
+    InviteRequired,
+
+    #[msg("Invalid channel invite")]
+    InvalidInvite,
+
+    #[msg("Channel invite has expired")]
+    InviteExpired,
+
+    #[msg("Channel invite has no uses left")]
+    InviteExhausted,
+
+    #[msg("You are banned from this channel")]
+    SubscriberBanned,
+
+    #[msg("The channel owner cannot be banned")]
+    CannotBanOwner,
+
+    #[msg("Unknown report reason")]
+    InvalidReportReason,
+
+    #[msg("Only channel and thread messages can be reported")]
+    InvalidReportTarget,
+
+    #[msg("Release time must be in the future")]
+    InvalidReleaseTime,
+
+    #[msg("Scheduled broadcast is not released yet")]
+    BroadcastNotReleased,
+
+    #[msg("Content does not match the committed hash")]
+    ContentHashMismatch,
+
+    #[msg("Only channel and thread messages can be pinned")]
+    InvalidPinTarget,
+
+    #[msg("Message is already pinned")]
+    AlreadyPinned,
+
+    #[msg("Too many pinned messages")]
+    TooManyPins,
+
+    #[msg("Message is not pinned")]
+    NotPinned,
+
+    #[msg("Sync counter must increase")]
+    StaleSyncCounter,
+
+    #[msg("Wrapped key is empty or too long")]
+    InvalidKeyWrap,
+
+    #[msg("Key wrap belongs to the current epoch")]
+    KeyWrapCurrent,
+
+    #[msg("Recipient charges a DM fee; pass the DM escrow")]
+    DmFeeRequired,
+
+    #[msg("Arbiter cannot be a party to the escrow")]
+    InvalidArbiter,
+
+    #[msg("Stats account is for another channel or day")]
+    InvalidStatsAccount,
+
+    #[msg("Stats day is out of range")]
+    InvalidStatsDay,
+
+    #[msg("Sender is not the notifier PDA of the whitelisted program")]
+    NotifierMismatch,
+
+    #[msg("Merkle proof does not match the snapshot root")]
+    InvalidMerkleProof,
+
+    #[msg("Channel is closing and no longer active")]
+    ChannelInactive,
+
+    #[msg("Channel still has subscribers and its closing grace period has not passed")]
+    ChannelClosingPending,
+
+    #[msg("Channel has not been closed")]
+    ChannelStillOpen,
+
+    #[msg("Closing a thread needs the other participant's signature or your own close request")]
+    ThreadCloseNotApproved,
+
+    #[msg("Thread close request is still within its veto window")]
+    ThreadCloseTimelocked,
+
+    #[msg("Batch has more entries than MAX_BATCH_MESSAGES")]
+    BatchTooLarge,
+
+    #[msg("Status must expire in the future and within MAX_STATUS_LIFETIME_SECONDS")]
+    InvalidStatusExpiry,
+
+    #[msg("Status has expired")]
+    StatusExpired,
+
+    #[msg("Status has not expired yet")]
+    StatusNotExpired,
+
+    #[msg("Status URI cannot exceed MAX_STATUS_URI_LEN bytes")]
+    StatusUriTooLong,
+
+    #[msg("Call is not ringing")]
+    CallNotRinging,
+
+    #[msg("Signer is not a participant of this thread")]
+    NotAParticipant,
+
+    #[msg("Signer is not the channel owner or an admin with the required permission")]
+    NotChannelOwner,
+
+    #[msg("Reward epochs must be positive and the minimum subscription age non-negative")]
+    InvalidRewardConfig,
+
+    #[msg("No rewards earned yet: subscription too new, too far behind, or already claimed")]
+    RewardsNotEarned,
+
+    #[msg("Reward vault does not hold enough tokens")]
+    RewardPoolDepleted,
+
+    #[msg("Thread has not been accepted by the recipient yet")]
+    ThreadNotAccepted,
+
+    #[msg("Pin note exceeds 64 bytes")]
+    NoteTooLong,
+
+    #[msg("A subscription can't be gifted to yourself")]
+    CannotGiftSelf,
+
+    #[msg("Private and token-gated channels can't be gifted")]
+    GiftNotAllowed,
+
+    #[msg("Unknown broadcast recurrence")]
+    InvalidRecurrence,
+
+    #[msg("Channel has the maximum number of pending scheduled broadcasts")]
+    ScheduleFull,
+
+    #[msg("Thread key transparency log is full")]
+    KeyLogFull,
+
+    #[msg("Message records must be passed as (record, sender) pairs of one thread")]
+    InvalidRecordBatch,
+
+    #[msg("Thread has not been closed")]
+    ThreadStillOpen,
+
+    #[msg("Auto-archive period cannot be negative")]
+    InvalidAutoArchivePeriod,
+
+    #[msg("Thread has not been idle past the participant's auto-archive period")]
+    AutoArchiveNotDue,
+
+    #[msg("Thread is not soft-closed")]
+    ThreadNotClosed,
+
+    #[msg("Subscription is not close enough to expiry to renew")]
+    RenewalNotDue,
+
+    #[msg("Subscription has already expired and must be renewed by the subscriber")]
+    SubscriptionLapsed,
+
+    #[msg("Subscription wallet cannot cover the renewal fee")]
+    InsufficientWalletBalance,
 }
\ No newline at end of file
@@ -1,16 +1,319 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import { expect } from "chai";
 import { WhatsappSol } from "../target/types/whatsapp_sol";
 
+const { Keypair, LAMPORTS_PER_SOL, PublicKey } = anchor.web3;
+
 describe("whatsapp-sol", () => {
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.whatsappSol as Program<WhatsappSol>;
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+  // ------------------------------------
+  // HELPERS
+  // ------------------------------------
+
+  async function fundedKeypair(): Promise<anchor.web3.Keypair> {
+    const keypair = Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(
+      keypair.publicKey,
+      2 * LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(sig, "confirmed");
+    return keypair;
+  }
+
+  // Thread PDAs sort the participant pair so either side derives the same address
+  function sortedParticipants(
+    a: anchor.web3.PublicKey,
+    b: anchor.web3.PublicKey
+  ): Buffer[] {
+    const [first, second] = [a.toBuffer(), b.toBuffer()];
+    return Buffer.compare(first, second) <= 0 ? [first, second] : [second, first];
+  }
+
+  function deriveThreadPDA(
+    participantA: anchor.web3.PublicKey,
+    participantB: anchor.web3.PublicKey,
+    threadId: Buffer
+  ): anchor.web3.PublicKey {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("message_thread"),
+        ...sortedParticipants(participantA, participantB),
+        threadId,
+      ],
+      program.programId
+    )[0];
+  }
+
+  async function initThread(
+    participantA: anchor.web3.Keypair,
+    participantB: anchor.web3.PublicKey
+  ): Promise<anchor.web3.PublicKey> {
+    const threadId = Keypair.generate().publicKey.toBuffer();
+    const thread = deriveThreadPDA(
+      participantA.publicKey,
+      participantB,
+      threadId
+    );
+    await program.methods
+      .initializeThread([...threadId])
+      .accountsPartial({
+        messageThread: thread,
+        participantA: participantA.publicKey,
+        participantB,
+      })
+      .signers([participantA])
+      .rpc();
+    return thread;
+  }
+
+  function sendMessage(
+    thread: anchor.web3.PublicKey,
+    sender: anchor.web3.Keypair,
+    messageIndex: number,
+    content = "hello"
+  ): Promise<string> {
+    return program.methods
+      .sendMessage(messageIndex, Buffer.from(content), null, null)
+      .accountsPartial({ messageThread: thread, sender: sender.publicKey })
+      .signers([sender])
+      .rpc();
+  }
+
+  async function expectAnchorError(
+    promise: Promise<unknown>,
+    code: string
+  ): Promise<void> {
+    let caught: unknown;
+    try {
+      await promise;
+    } catch (err) {
+      caught = err;
+    }
+    expect(caught, `expected ${code}`).to.be.instanceOf(anchor.AnchorError);
+    expect((caught as anchor.AnchorError).error.errorCode.code).to.equal(code);
+  }
+
+  // ------------------------------------
+  // THREAD ACCEPTANCE
+  // ------------------------------------
+
+  describe("thread acceptance", () => {
+    let alice: anchor.web3.Keypair;
+    let bob: anchor.web3.Keypair;
+
+    beforeEach(async () => {
+      alice = await fundedKeypair();
+      bob = await fundedKeypair();
+    });
+
+    it("allows one request message before acceptance", async () => {
+      const thread = await initThread(alice, bob.publicKey);
+
+      await sendMessage(thread, alice, 0);
+      await expectAnchorError(
+        sendMessage(thread, alice, 1),
+        "ThreadNotAccepted"
+      );
+
+      const account = await program.account.messageThread.fetch(thread);
+      expect(account.status).to.deep.equal({ pending: {} });
+      expect(account.messageCount.toNumber()).to.equal(1);
+    });
+
+    it("lets the initiator send after accept_thread", async () => {
+      const thread = await initThread(alice, bob.publicKey);
+
+      await program.methods
+        .acceptThread()
+        .accountsPartial({ messageThread: thread, participantB: bob.publicKey })
+        .signers([bob])
+        .rpc();
+      await sendMessage(thread, alice, 0);
+      await sendMessage(thread, alice, 1);
+
+      const account = await program.account.messageThread.fetch(thread);
+      expect(account.status).to.deep.equal({ accepted: {} });
+      expect(account.messageCount.toNumber()).to.equal(2);
+    });
+
+    it("treats a reply from participant B as acceptance", async () => {
+      const thread = await initThread(alice, bob.publicKey);
+
+      await sendMessage(thread, bob, 0);
+      await sendMessage(thread, alice, 1);
+
+      const account = await program.account.messageThread.fetch(thread);
+      expect(account.status).to.deep.equal({ accepted: {} });
+      expect(account.messageCount.toNumber()).to.equal(2);
+    });
+
+    it("only lets participant B accept", async () => {
+      const thread = await initThread(alice, bob.publicKey);
+
+      await expectAnchorError(
+        program.methods
+          .acceptThread()
+          .accountsPartial({
+            messageThread: thread,
+            participantB: alice.publicKey,
+          })
+          .signers([alice])
+          .rpc(),
+        "UnauthorizedSender"
+      );
+    });
+
+    it("blocks all messages once participant B rejects", async () => {
+      const thread = await initThread(alice, bob.publicKey);
+
+      await sendMessage(thread, alice, 0);
+      await program.methods
+        .rejectThread()
+        .accountsPartial({ messageThread: thread, participantB: bob.publicKey })
+        .signers([bob])
+        .rpc();
+
+      await expectAnchorError(sendMessage(thread, alice, 1), "ThreadRejected");
+      await expectAnchorError(sendMessage(thread, bob, 1), "ThreadRejected");
+
+      const account = await program.account.messageThread.fetch(thread);
+      expect(account.status).to.deep.equal({ rejected: {} });
+    });
+
+    it("only rejects pending threads", async () => {
+      const thread = await initThread(alice, bob.publicKey);
+      await sendMessage(thread, bob, 0);
+
+      await expectAnchorError(
+        program.methods
+          .rejectThread()
+          .accountsPartial({
+            messageThread: thread,
+            participantB: bob.publicKey,
+          })
+          .signers([bob])
+          .rpc(),
+        "ThreadNotPending"
+      );
+    });
+  });
+
+  // ------------------------------------
+  // THREAD ADDRESSING
+  // ------------------------------------
+
+  describe("thread addressing", () => {
+    it("derives the thread PDA from the sorted participant pair", async () => {
+      const alice = await fundedKeypair();
+      const bob = Keypair.generate().publicKey;
+      const threadId = Keypair.generate().publicKey.toBuffer();
+      const [first, second] = sortedParticipants(alice.publicKey, bob);
+      const unsorted = PublicKey.findProgramAddressSync(
+        [Buffer.from("message_thread"), second, first, threadId],
+        program.programId
+      )[0];
+
+      await expectAnchorError(
+        program.methods
+          .initializeThread([...threadId])
+          .accountsPartial({
+            messageThread: unsorted,
+            participantA: alice.publicKey,
+            participantB: bob,
+          })
+          .signers([alice])
+          .rpc(),
+        "ConstraintSeeds"
+      );
+
+      const thread = deriveThreadPDA(bob, alice.publicKey, threadId);
+      await program.methods
+        .initializeThread([...threadId])
+        .accountsPartial({
+          messageThread: thread,
+          participantA: alice.publicKey,
+          participantB: bob,
+        })
+        .signers([alice])
+        .rpc();
+
+      const account = await program.account.messageThread.fetch(thread);
+      expect(account.participantA.toBase58()).to.equal(
+        alice.publicKey.toBase58()
+      );
+    });
+  });
+
+  // ------------------------------------
+  // CONSECUTIVE MESSAGE LIMIT
+  // ------------------------------------
+
+  describe("consecutive message limit", () => {
+    let alice: anchor.web3.Keypair;
+    let bob: anchor.web3.Keypair;
+    let thread: anchor.web3.PublicKey;
+
+    beforeEach(async () => {
+      alice = await fundedKeypair();
+      bob = await fundedKeypair();
+      thread = await initThread(alice, bob.publicKey);
+
+      await program.methods
+        .acceptThread()
+        .accountsPartial({ messageThread: thread, participantB: bob.publicKey })
+        .signers([bob])
+        .rpc();
+      await program.methods
+        .setMaxConsecutive(2)
+        .accountsPartial({ messageThread: thread, participantB: bob.publicKey })
+        .signers([bob])
+        .rpc();
+    });
+
+    it("allows alternating senders indefinitely", async () => {
+      for (let i = 0; i < 6; i++) {
+        await sendMessage(thread, i % 2 === 0 ? alice : bob, i);
+      }
+
+      const account = await program.account.messageThread.fetch(thread);
+      expect(account.messageCount.toNumber()).to.equal(6);
+      expect(account.consecutiveSender.toBase58()).to.equal(
+        bob.publicKey.toBase58()
+      );
+      expect(account.consecutiveCount).to.equal(1);
+    });
+
+    it("blocks a flooding sender until the other party replies", async () => {
+      await sendMessage(thread, alice, 0);
+      await sendMessage(thread, alice, 1);
+      await expectAnchorError(sendMessage(thread, alice, 2), "AwaitingReply");
+
+      await sendMessage(thread, bob, 2);
+      await sendMessage(thread, alice, 3);
+
+      const account = await program.account.messageThread.fetch(thread);
+      expect(account.messageCount.toNumber()).to.equal(4);
+      expect(account.consecutiveCount).to.equal(1);
+    });
+
+    it("only lets participant B change the limit", async () => {
+      await expectAnchorError(
+        program.methods
+          .setMaxConsecutive(0)
+          .accountsPartial({
+            messageThread: thread,
+            participantB: alice.publicKey,
+          })
+          .signers([alice])
+          .rpc(),
+        "UnauthorizedSender"
+      );
+    });
   });
 });